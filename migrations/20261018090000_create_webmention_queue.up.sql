@@ -0,0 +1,14 @@
+CREATE TABLE webmention_queue (
+  id SERIAL PRIMARY KEY,
+  source_url TEXT NOT NULL,
+  target_url TEXT NOT NULL,
+  status CHARACTER VARYING(32) NOT NULL DEFAULT 'pending',
+  attempts INTEGER NOT NULL DEFAULT 0,
+  last_error TEXT,
+  next_try_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+  mention_id INTEGER REFERENCES mentions(id) ON DELETE SET NULL,
+  inserted_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+  updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
+
+CREATE INDEX webmention_queue_status_next_try_at_idx ON webmention_queue (status, next_try_at);
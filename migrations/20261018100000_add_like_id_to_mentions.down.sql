@@ -0,0 +1,2 @@
+ALTER TABLE mentions
+  DROP COLUMN like_id;
@@ -0,0 +1,4 @@
+ALTER TABLE mentions
+  ADD COLUMN like_id INTEGER REFERENCES likes(id);
+
+CREATE INDEX mentions_like_id_idx ON mentions (like_id);
@@ -0,0 +1,4 @@
+DROP TABLE mention_domains;
+
+ALTER TABLE mentions
+  DROP COLUMN status;
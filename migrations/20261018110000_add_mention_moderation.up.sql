@@ -0,0 +1,14 @@
+ALTER TABLE mentions
+  ADD COLUMN status CHARACTER VARYING(32) NOT NULL DEFAULT 'approved';
+ALTER TABLE mentions
+  ALTER COLUMN status SET DEFAULT 'pending';
+
+CREATE INDEX mentions_status_idx ON mentions (status);
+
+CREATE TABLE mention_domains (
+  id SERIAL PRIMARY KEY,
+  domain CHARACTER VARYING(255) NOT NULL UNIQUE,
+  rule CHARACTER VARYING(32) NOT NULL,
+  inserted_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+  updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
@@ -0,0 +1 @@
+DROP TABLE outgoing_webmentions;
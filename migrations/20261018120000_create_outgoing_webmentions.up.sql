@@ -0,0 +1,16 @@
+CREATE TABLE outgoing_webmentions (
+  id SERIAL PRIMARY KEY,
+  source_url TEXT NOT NULL,
+  target_url TEXT NOT NULL,
+  endpoint TEXT,
+  status CHARACTER VARYING(32) NOT NULL DEFAULT 'pending',
+  response_code INTEGER,
+  attempts INTEGER NOT NULL DEFAULT 0,
+  last_error TEXT,
+  next_retry_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+  inserted_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+  updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+  UNIQUE (source_url, target_url)
+);
+
+CREATE INDEX outgoing_webmentions_status_next_retry_at_idx ON outgoing_webmentions (status, next_retry_at);
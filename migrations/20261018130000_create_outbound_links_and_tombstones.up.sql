@@ -0,0 +1,16 @@
+CREATE TABLE outbound_links (
+  id SERIAL PRIMARY KEY,
+  source_url TEXT NOT NULL,
+  target_url TEXT NOT NULL,
+  inserted_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+  UNIQUE (source_url, target_url)
+);
+
+INSERT INTO outbound_links (source_url, target_url, inserted_at)
+  SELECT source_url, target_url, inserted_at FROM outgoing_webmentions;
+
+CREATE TABLE tombstones (
+  id SERIAL PRIMARY KEY,
+  url TEXT NOT NULL UNIQUE,
+  deleted_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
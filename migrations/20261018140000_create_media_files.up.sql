@@ -0,0 +1,9 @@
+CREATE TABLE media_files (
+  id SERIAL PRIMARY KEY,
+  author_id INTEGER NOT NULL REFERENCES authors(id),
+  file_name CHARACTER VARYING(255) NOT NULL,
+  content_type CHARACTER VARYING(255) NOT NULL,
+  file_size INTEGER NOT NULL,
+  inserted_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+  updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
@@ -0,0 +1,27 @@
+CREATE TABLE authorization_codes (
+  id SERIAL PRIMARY KEY,
+  code_hash TEXT NOT NULL UNIQUE,
+  author_id INTEGER NOT NULL REFERENCES authors(id) ON DELETE CASCADE,
+  client_id TEXT NOT NULL,
+  redirect_uri TEXT NOT NULL,
+  scope TEXT NOT NULL,
+  code_challenge TEXT NOT NULL,
+  me TEXT NOT NULL,
+  expires_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+  inserted_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
+
+CREATE TABLE access_tokens (
+  id SERIAL PRIMARY KEY,
+  token_hash TEXT NOT NULL UNIQUE,
+  author_id INTEGER NOT NULL REFERENCES authors(id) ON DELETE CASCADE,
+  client_id TEXT NOT NULL,
+  scope TEXT NOT NULL,
+  me TEXT NOT NULL,
+  last_used_at TIMESTAMP WITHOUT TIME ZONE,
+  revoked_at TIMESTAMP WITHOUT TIME ZONE,
+  inserted_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+  updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
+
+CREATE INDEX access_tokens_author_id_idx ON access_tokens (author_id);
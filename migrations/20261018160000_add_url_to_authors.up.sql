@@ -0,0 +1 @@
+ALTER TABLE authors ADD COLUMN url TEXT UNIQUE;
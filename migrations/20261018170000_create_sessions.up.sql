@@ -0,0 +1,7 @@
+CREATE TABLE sessions (
+  id TEXT PRIMARY KEY,
+  data JSONB NOT NULL,
+  expiry_date TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
+
+CREATE INDEX sessions_expiry_date_idx ON sessions (expiry_date);
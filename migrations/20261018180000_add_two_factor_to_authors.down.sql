@@ -0,0 +1,5 @@
+DROP TABLE recovery_codes;
+
+ALTER TABLE authors DROP COLUMN totp_last_step;
+ALTER TABLE authors DROP COLUMN totp_enabled_at;
+ALTER TABLE authors DROP COLUMN totp_secret;
@@ -0,0 +1,13 @@
+ALTER TABLE authors ADD COLUMN totp_secret TEXT;
+ALTER TABLE authors ADD COLUMN totp_enabled_at TIMESTAMP WITHOUT TIME ZONE;
+ALTER TABLE authors ADD COLUMN totp_last_step BIGINT;
+
+CREATE TABLE recovery_codes (
+  id SERIAL PRIMARY KEY,
+  author_id INTEGER NOT NULL REFERENCES authors(id) ON DELETE CASCADE,
+  code_hash TEXT NOT NULL,
+  used_at TIMESTAMP WITHOUT TIME ZONE,
+  inserted_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
+
+CREATE INDEX recovery_codes_author_id_idx ON recovery_codes (author_id);
@@ -0,0 +1,12 @@
+CREATE TABLE passkeys (
+  id SERIAL PRIMARY KEY,
+  author_id INTEGER NOT NULL REFERENCES authors(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  credential_id TEXT NOT NULL UNIQUE,
+  passkey JSONB NOT NULL,
+  last_used_at TIMESTAMP WITHOUT TIME ZONE,
+  inserted_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+  updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
+
+CREATE INDEX passkeys_author_id_idx ON passkeys (author_id);
@@ -0,0 +1,4 @@
+DROP TABLE audit_log;
+
+ALTER TABLE authors DROP COLUMN locked_until;
+ALTER TABLE authors DROP COLUMN failed_login_attempts;
@@ -0,0 +1,15 @@
+ALTER TABLE authors ADD COLUMN failed_login_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE authors ADD COLUMN locked_until TIMESTAMP WITHOUT TIME ZONE;
+
+CREATE TABLE audit_log (
+  id SERIAL PRIMARY KEY,
+  author_id INTEGER REFERENCES authors(id) ON DELETE SET NULL,
+  action TEXT NOT NULL,
+  path TEXT NOT NULL,
+  email TEXT,
+  ip TEXT,
+  inserted_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
+
+CREATE INDEX audit_log_author_id_idx ON audit_log (author_id);
+CREATE INDEX audit_log_inserted_at_idx ON audit_log (inserted_at);
@@ -0,0 +1,6 @@
+DROP TABLE password_tokens;
+
+DROP INDEX authors_email_key;
+
+ALTER TABLE authors DROP COLUMN disabled_at;
+ALTER TABLE authors DROP COLUMN is_admin;
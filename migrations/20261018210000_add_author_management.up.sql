@@ -0,0 +1,19 @@
+ALTER TABLE authors ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE authors ADD COLUMN disabled_at TIMESTAMP WITHOUT TIME ZONE;
+
+-- all authors so far have been created by hand by the owner of the site
+UPDATE authors SET is_admin = true;
+
+CREATE UNIQUE INDEX authors_email_key ON authors (email);
+
+CREATE TABLE password_tokens (
+  id SERIAL PRIMARY KEY,
+  author_id INTEGER NOT NULL REFERENCES authors(id) ON DELETE CASCADE,
+  token_hash TEXT NOT NULL UNIQUE,
+  purpose TEXT NOT NULL,
+  expires_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+  used_at TIMESTAMP WITHOUT TIME ZONE,
+  inserted_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
+
+CREATE INDEX password_tokens_author_id_idx ON password_tokens (author_id);
@@ -0,0 +1,7 @@
+ALTER TABLE sessions ADD COLUMN author_id INTEGER REFERENCES authors(id) ON DELETE CASCADE;
+ALTER TABLE sessions ADD COLUMN ip TEXT;
+ALTER TABLE sessions ADD COLUMN user_agent TEXT;
+ALTER TABLE sessions ADD COLUMN inserted_at TIMESTAMP WITHOUT TIME ZONE;
+ALTER TABLE sessions ADD COLUMN last_seen_at TIMESTAMP WITHOUT TIME ZONE;
+
+CREATE INDEX sessions_author_id_idx ON sessions (author_id);
@@ -0,0 +1,11 @@
+CREATE TABLE syndications (
+  id SERIAL PRIMARY KEY,
+  object_type TEXT NOT NULL,
+  object_id INTEGER NOT NULL,
+  service TEXT NOT NULL,
+  url TEXT NOT NULL,
+  remote_id TEXT,
+  inserted_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+  updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+  UNIQUE (object_type, object_id, service)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE syndications DROP COLUMN failed_at;
+ALTER TABLE syndications DROP COLUMN last_error;
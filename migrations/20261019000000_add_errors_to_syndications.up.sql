@@ -0,0 +1,2 @@
+ALTER TABLE syndications ADD COLUMN last_error TEXT;
+ALTER TABLE syndications ADD COLUMN failed_at TIMESTAMP WITHOUT TIME ZONE;
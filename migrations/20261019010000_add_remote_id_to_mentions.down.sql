@@ -0,0 +1,2 @@
+DROP INDEX mentions_remote_id_key;
+ALTER TABLE mentions DROP COLUMN remote_id;
@@ -0,0 +1,2 @@
+ALTER TABLE mentions ADD COLUMN remote_id CHARACTER VARYING(255);
+CREATE UNIQUE INDEX mentions_remote_id_key ON mentions (remote_id);
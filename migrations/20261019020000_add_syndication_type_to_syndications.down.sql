@@ -0,0 +1 @@
+ALTER TABLE syndications DROP COLUMN syndication_type;
@@ -0,0 +1 @@
+ALTER TABLE syndications ADD COLUMN syndication_type TEXT NOT NULL DEFAULT 'post';
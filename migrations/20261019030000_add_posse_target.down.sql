@@ -0,0 +1,5 @@
+ALTER TABLE notes DROP COLUMN posse_target;
+ALTER TABLE pictures DROP COLUMN posse_target;
+ALTER TABLE articles DROP COLUMN posse_target;
+ALTER TABLE deafies DROP COLUMN posse_target;
+ALTER TABLE likes DROP COLUMN posse_target;
@@ -0,0 +1,5 @@
+ALTER TABLE notes ADD COLUMN posse_target VARCHAR(255);
+ALTER TABLE pictures ADD COLUMN posse_target VARCHAR(255);
+ALTER TABLE articles ADD COLUMN posse_target VARCHAR(255);
+ALTER TABLE deafies ADD COLUMN posse_target VARCHAR(255);
+ALTER TABLE likes ADD COLUMN posse_target VARCHAR(255);
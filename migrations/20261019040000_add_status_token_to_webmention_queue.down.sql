@@ -0,0 +1,2 @@
+DROP INDEX webmention_queue_status_token_key;
+ALTER TABLE webmention_queue DROP COLUMN status_token;
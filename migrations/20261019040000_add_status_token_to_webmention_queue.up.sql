@@ -0,0 +1,4 @@
+ALTER TABLE webmention_queue ADD COLUMN status_token CHARACTER VARYING(255);
+UPDATE webmention_queue SET status_token = md5(random()::text || clock_timestamp()::text) || md5(random()::text);
+ALTER TABLE webmention_queue ALTER COLUMN status_token SET NOT NULL;
+CREATE UNIQUE INDEX webmention_queue_status_token_key ON webmention_queue (status_token);
@@ -0,0 +1,3 @@
+ALTER TABLE access_tokens ADD COLUMN expires_at TIMESTAMP WITHOUT TIME ZONE;
+UPDATE access_tokens SET expires_at = inserted_at + INTERVAL '90 days';
+ALTER TABLE access_tokens ALTER COLUMN expires_at SET NOT NULL;
@@ -0,0 +1,15 @@
+ALTER TABLE notes ADD COLUMN posse_target VARCHAR(255);
+UPDATE notes SET posse_target = posse_targets[1] WHERE cardinality(posse_targets) = 1;
+ALTER TABLE notes DROP COLUMN posse_targets;
+ALTER TABLE pictures ADD COLUMN posse_target VARCHAR(255);
+UPDATE pictures SET posse_target = posse_targets[1] WHERE cardinality(posse_targets) = 1;
+ALTER TABLE pictures DROP COLUMN posse_targets;
+ALTER TABLE articles ADD COLUMN posse_target VARCHAR(255);
+UPDATE articles SET posse_target = posse_targets[1] WHERE cardinality(posse_targets) = 1;
+ALTER TABLE articles DROP COLUMN posse_targets;
+ALTER TABLE deafies ADD COLUMN posse_target VARCHAR(255);
+UPDATE deafies SET posse_target = posse_targets[1] WHERE cardinality(posse_targets) = 1;
+ALTER TABLE deafies DROP COLUMN posse_targets;
+ALTER TABLE likes ADD COLUMN posse_target VARCHAR(255);
+UPDATE likes SET posse_target = posse_targets[1] WHERE cardinality(posse_targets) = 1;
+ALTER TABLE likes DROP COLUMN posse_targets;
@@ -0,0 +1,15 @@
+ALTER TABLE notes ADD COLUMN posse_targets VARCHAR(255)[] NOT NULL DEFAULT '{}';
+UPDATE notes SET posse_targets = ARRAY[posse_target] WHERE posse_target IS NOT NULL;
+ALTER TABLE notes DROP COLUMN posse_target;
+ALTER TABLE pictures ADD COLUMN posse_targets VARCHAR(255)[] NOT NULL DEFAULT '{}';
+UPDATE pictures SET posse_targets = ARRAY[posse_target] WHERE posse_target IS NOT NULL;
+ALTER TABLE pictures DROP COLUMN posse_target;
+ALTER TABLE articles ADD COLUMN posse_targets VARCHAR(255)[] NOT NULL DEFAULT '{}';
+UPDATE articles SET posse_targets = ARRAY[posse_target] WHERE posse_target IS NOT NULL;
+ALTER TABLE articles DROP COLUMN posse_target;
+ALTER TABLE deafies ADD COLUMN posse_targets VARCHAR(255)[] NOT NULL DEFAULT '{}';
+UPDATE deafies SET posse_targets = ARRAY[posse_target] WHERE posse_target IS NOT NULL;
+ALTER TABLE deafies DROP COLUMN posse_target;
+ALTER TABLE likes ADD COLUMN posse_targets VARCHAR(255)[] NOT NULL DEFAULT '{}';
+UPDATE likes SET posse_targets = ARRAY[posse_target] WHERE posse_target IS NOT NULL;
+ALTER TABLE likes DROP COLUMN posse_target;
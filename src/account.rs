@@ -0,0 +1,47 @@
+use axum::Router;
+use axum::middleware::from_fn;
+use axum::routing::{get, post};
+use axum_login::login_required;
+
+use crate::AppRouter;
+use crate::middleware::two_factor_middleware;
+use crate::store::Store;
+
+pub mod actions;
+pub mod mail_sender;
+
+pub mod authors;
+pub mod passkeys;
+pub mod profile;
+pub mod sessions;
+pub mod two_factor;
+
+pub fn configure(app: AppRouter) -> AppRouter {
+    // the two-factor enrollment must stay reachable when two-factor authentication is required
+    let authed_router: AppRouter = Router::new()
+        .route("/admin/account", get(profile::show))
+        .route("/admin/account", post(profile::update))
+        .route("/admin/account/password", post(profile::update_password))
+        .route("/admin/account/two-factor", get(two_factor::show))
+        .route("/admin/account/two-factor", post(two_factor::enable))
+        .route("/admin/account/two-factor/recovery-codes", post(two_factor::recovery_codes))
+        .route("/admin/account/two-factor/disable", post(two_factor::disable))
+        .route("/admin/account/passkeys", get(passkeys::index))
+        .route("/admin/account/passkeys", post(passkeys::create))
+        .route("/admin/account/passkeys/challenge", post(passkeys::challenge))
+        .route("/admin/account/passkeys/{id}/delete", post(passkeys::delete))
+        .route("/admin/account/sessions", get(sessions::index))
+        .route("/admin/account/sessions/others/delete", post(sessions::delete_others))
+        .route("/admin/account/sessions/{key}/delete", post(sessions::delete))
+        .route_layer(login_required!(Store, login_url = "/login"));
+
+    let admin_router: AppRouter = Router::new()
+        .route("/admin/authors", get(authors::index))
+        .route("/admin/authors", post(authors::invite))
+        .route("/admin/authors/{id}/disable", post(authors::disable))
+        .route("/admin/authors/{id}/enable", post(authors::enable))
+        .route_layer(from_fn(two_factor_middleware))
+        .route_layer(login_required!(Store, login_url = "/login"));
+
+    app.merge(authed_router).merge(admin_router)
+}
@@ -0,0 +1,392 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{Duration, Utc};
+use sqlx::{Connection, PgConnection, query, query_as, query_scalar};
+use validator::Validate;
+
+use crate::models::{ActiveSession, Author, AuthorProfile, Passkey, PasswordToken};
+use crate::utils::tokens::{generate_token, hash_token};
+use crate::utils::totp::{generate_recovery_codes, normalize_recovery_code};
+
+/// Enables TOTP for an author and returns a fresh set of recovery codes; `step` is the time step of the
+/// code used to confirm the enrollment, so that it can't be used again for logging in.
+pub async fn enable_two_factor(
+    author_id: i32,
+    secret: &str,
+    step: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<String>, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    query!(
+        "UPDATE authors SET totp_secret = $1, totp_enabled_at = $2, totp_last_step = $3 WHERE id = $4",
+        secret,
+        Utc::now().naive_utc(),
+        step,
+        author_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let codes = replace_recovery_codes(author_id, &mut tx).await?;
+    tx.commit().await?;
+
+    Ok(codes)
+}
+
+pub async fn disable_two_factor(author_id: i32, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    query!(
+        "UPDATE authors SET totp_secret = NULL, totp_enabled_at = NULL, totp_last_step = NULL WHERE id = $1",
+        author_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    query!("DELETE FROM recovery_codes WHERE author_id = $1", author_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Invalidates all recovery codes of an author and generates new ones; only their hashes are stored, so
+/// the returned codes can be shown exactly once.
+pub async fn replace_recovery_codes(author_id: i32, conn: &mut PgConnection) -> Result<Vec<String>, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+    let now = Utc::now().naive_utc();
+    let codes = generate_recovery_codes();
+
+    query!("DELETE FROM recovery_codes WHERE author_id = $1", author_id)
+        .execute(&mut *tx)
+        .await?;
+
+    for code in &codes {
+        query!(
+            "INSERT INTO recovery_codes (author_id, code_hash, inserted_at) VALUES ($1, $2, $3)",
+            author_id,
+            hash_token(&normalize_recovery_code(code)),
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(codes)
+}
+
+pub async fn count_unused_recovery_codes(author_id: i32, conn: &mut PgConnection) -> Result<i64, sqlx::Error> {
+    query_scalar("SELECT COUNT(*) FROM recovery_codes WHERE author_id = $1 AND used_at IS NULL")
+        .bind(author_id)
+        .fetch_one(conn)
+        .await
+}
+
+pub async fn list_passkeys(author_id: i32, conn: &mut PgConnection) -> Result<Vec<Passkey>, sqlx::Error> {
+    query_as!(Passkey, "SELECT * FROM passkeys WHERE author_id = $1 ORDER BY inserted_at, id", author_id)
+        .fetch_all(conn)
+        .await
+}
+
+pub async fn create_passkey(
+    author_id: i32,
+    name: &str,
+    credential: &webauthn_rs::prelude::Passkey,
+    conn: &mut PgConnection,
+) -> Result<Passkey, sqlx::Error> {
+    let now = Utc::now().naive_utc();
+    let credential_id = URL_SAFE_NO_PAD.encode(credential.cred_id());
+    let passkey = serde_json::to_value(credential).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+
+    query_as!(
+        Passkey,
+        r#"
+            INSERT INTO passkeys (author_id, name, credential_id, passkey, inserted_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+        "#,
+        author_id,
+        name,
+        credential_id,
+        passkey,
+        now,
+        now
+    )
+    .fetch_one(conn)
+    .await
+}
+
+pub async fn delete_passkey(id: i32, author_id: i32, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    query!("DELETE FROM passkeys WHERE id = $1 AND author_id = $2", id, author_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Turns the violation of the unique email address index into a readable error
+fn email_taken(error: sqlx::Error) -> anyhow::Error {
+    match error {
+        sqlx::Error::Database(e) if e.is_unique_violation() => {
+            anyhow::anyhow!("there already is an author with this email address")
+        }
+        e => e.into(),
+    }
+}
+
+pub async fn update_profile(
+    author_id: i32,
+    profile: &AuthorProfile,
+    conn: &mut PgConnection,
+) -> Result<Author, anyhow::Error> {
+    profile.validate()?;
+
+    let author = query_as!(
+        Author,
+        "UPDATE authors SET name = $1, email = $2, avatar = $3, updated_at = $4 WHERE id = $5 RETURNING *",
+        profile.name,
+        profile.email,
+        profile.avatar,
+        Utc::now().naive_utc(),
+        author_id
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(email_taken)?;
+
+    Ok(author)
+}
+
+/// Stores a new password hash; as the hash is part of the session auth hash, this ends all sessions of the
+/// author
+pub async fn update_password(
+    author_id: i32,
+    encrypted_password: &str,
+    conn: &mut PgConnection,
+) -> Result<Author, sqlx::Error> {
+    query_as!(
+        Author,
+        "UPDATE authors SET encrypted_password = $1, updated_at = $2 WHERE id = $3 RETURNING *",
+        encrypted_password,
+        Utc::now().naive_utc(),
+        author_id
+    )
+    .fetch_one(conn)
+    .await
+}
+
+pub async fn list_authors(conn: &mut PgConnection) -> Result<Vec<Author>, sqlx::Error> {
+    query_as!(Author, "SELECT * FROM authors ORDER BY name, id")
+        .fetch_all(conn)
+        .await
+}
+
+/// Creates an author without a password; the author chooses one with the invitation link
+pub async fn create_author(name: &str, email: &str, conn: &mut PgConnection) -> Result<Author, anyhow::Error> {
+    let profile = AuthorProfile {
+        name: name.to_owned(),
+        email: email.to_owned(),
+        avatar: "".to_owned(),
+    };
+    profile.validate()?;
+
+    let now = Utc::now().naive_utc();
+    let author = query_as!(
+        Author,
+        r#"
+            INSERT INTO authors (name, email, avatar, encrypted_password, inserted_at, updated_at)
+            VALUES ($1, $2, $3, '', $4, $5)
+            RETURNING *
+        "#,
+        profile.name,
+        profile.email,
+        profile.avatar,
+        now,
+        now
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(email_taken)?;
+
+    Ok(author)
+}
+
+/// Disables an author and revokes the access tokens issued to them; their sessions end with the next request
+pub async fn disable_author(author_id: i32, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    let mut tx = conn.begin().await?;
+    let now = Utc::now().naive_utc();
+
+    query!(
+        "UPDATE authors SET disabled_at = $1, updated_at = $1 WHERE id = $2 AND disabled_at IS NULL",
+        now,
+        author_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    query!(
+        "UPDATE access_tokens SET revoked_at = $1, updated_at = $1 WHERE author_id = $2 AND revoked_at IS NULL",
+        now,
+        author_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn enable_author(author_id: i32, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    query!("UPDATE authors SET disabled_at = NULL, updated_at = $1 WHERE id = $2", Utc::now().naive_utc(), author_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Creates a token to choose a new password with; older tokens of the author are invalidated, so that only
+/// the link in the latest mail works
+pub async fn create_password_token(
+    author_id: i32,
+    purpose: &str,
+    valid_for: Duration,
+    conn: &mut PgConnection,
+) -> Result<String, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+    let now = Utc::now().naive_utc();
+    let token = generate_token();
+
+    query!("DELETE FROM password_tokens WHERE author_id = $1", author_id)
+        .execute(&mut *tx)
+        .await?;
+
+    query!(
+        r#"
+            INSERT INTO password_tokens (author_id, token_hash, purpose, expires_at, inserted_at)
+            VALUES ($1, $2, $3, $4, $5)
+        "#,
+        author_id,
+        hash_token(&token),
+        purpose,
+        now + valid_for,
+        now
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(token)
+}
+
+/// Returns the password token if it hasn't been used and hasn't expired
+pub async fn get_password_token(token: &str, conn: &mut PgConnection) -> Result<Option<PasswordToken>, sqlx::Error> {
+    query_as!(
+        PasswordToken,
+        "SELECT * FROM password_tokens WHERE token_hash = $1 AND used_at IS NULL AND expires_at > $2",
+        hash_token(token),
+        Utc::now().naive_utc()
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+/// Sets the password chosen with a password token and uses up the token; a lockout because of failed logins
+/// ends as well
+pub async fn reset_password(
+    token: &PasswordToken,
+    encrypted_password: &str,
+    conn: &mut PgConnection,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+    let now = Utc::now().naive_utc();
+
+    let used = query!("UPDATE password_tokens SET used_at = $1 WHERE id = $2 AND used_at IS NULL", now, token.id)
+        .execute(&mut *tx)
+        .await?;
+
+    if used.rows_affected() != 1 {
+        return Ok(false);
+    }
+
+    query!(
+        r#"
+            UPDATE authors
+            SET encrypted_password = $1, failed_login_attempts = 0, locked_until = NULL, updated_at = $2
+            WHERE id = $3
+        "#,
+        encrypted_password,
+        now,
+        token.author_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(true)
+}
+
+/// the minimal length of a new password
+pub const MIN_PASSWORD_LENGTH: usize = 10;
+
+/// Checks a new password and its confirmation, returning the message to show when they don't fit
+pub fn check_new_password(password: &str, confirmation: &str) -> Result<(), String> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(format!("the password must have at least {} characters", MIN_PASSWORD_LENGTH));
+    }
+
+    if password != confirmation {
+        return Err("the password and its confirmation differ".to_owned());
+    }
+
+    Ok(())
+}
+
+pub async fn list_sessions(author_id: i32, conn: &mut PgConnection) -> Result<Vec<ActiveSession>, sqlx::Error> {
+    query_as!(
+        ActiveSession,
+        r#"
+            SELECT encode(sha256(convert_to(id, 'UTF8')), 'hex') AS "key!", ip, user_agent, inserted_at, last_seen_at,
+              expiry_date
+            FROM sessions
+            WHERE author_id = $1 AND expiry_date > $2
+            ORDER BY last_seen_at DESC NULLS LAST
+        "#,
+        author_id,
+        Utc::now().naive_utc()
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Deletes a session of an author by the hash of its ID; the session is logged out with its next request
+pub async fn delete_session(author_id: i32, key: &str, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    query!(
+        "DELETE FROM sessions WHERE author_id = $1 AND encode(sha256(convert_to(id, 'UTF8')), 'hex') = $2",
+        author_id,
+        key
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_other_sessions(
+    author_id: i32,
+    current_id: &str,
+    conn: &mut PgConnection,
+) -> Result<(), sqlx::Error> {
+    query!("DELETE FROM sessions WHERE author_id = $1 AND id != $2", author_id, current_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
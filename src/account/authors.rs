@@ -0,0 +1,141 @@
+use askama::Template;
+use axum::extract::{Form, Path, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Redirect, Response};
+use chrono::Duration;
+use serde::Deserialize;
+
+use super::{actions, mail_sender};
+use crate::errors::AppError;
+use crate::models::Author;
+use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
+use crate::{AppState, AuthSession, utils as filters};
+
+/// how long the link in an invitation can be used
+const INVITATION_VALIDITY: Duration = Duration::days(7);
+
+#[derive(Debug, Deserialize, Default)]
+pub struct InvitationForm {
+    name: String,
+    email: String,
+}
+
+#[derive(Template)]
+#[template(path = "account/authors.html.j2")]
+pub struct Index<'a> {
+    lang: &'a str,
+    title: Option<&'a str>,
+    page_type: Option<&'a str>,
+    page_image: Option<&'a str>,
+    body_id: Option<&'a str>,
+    logged_in: bool,
+
+    current_author_id: i32,
+    authors: Vec<Author>,
+    form_data: InvitationForm,
+    error: Option<String>,
+    notice: Option<String>,
+}
+
+fn admin(auth: &AuthSession) -> Result<&Author, AppError> {
+    match &auth.user {
+        Some(user) if user.is_admin => Ok(user),
+        Some(_) => Err(AppError::Forbidden("only admins can manage authors".to_owned())),
+        None => Err(AppError::Unauthorized),
+    }
+}
+
+async fn render(
+    user: &Author,
+    state: &AppState,
+    form_data: InvitationForm,
+    error: Option<String>,
+    notice: Option<String>,
+) -> Result<String, AppError> {
+    let mut conn = state.pool.acquire().await?;
+    let authors = actions::list_authors(&mut conn).await?;
+
+    let html = Index {
+        lang: "en",
+        title: Some("Authors"),
+        page_type: None,
+        page_image: None,
+        body_id: None,
+        logged_in: true,
+        current_author_id: user.id,
+        authors,
+        form_data,
+        error,
+        notice,
+    }
+    .render()?;
+
+    Ok(html)
+}
+
+pub async fn index(auth: AuthSession, State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let user = admin(&auth)?;
+    let html = render(user, &state, InvitationForm::default(), None, None).await?;
+
+    Ok(Html(html))
+}
+
+/// Creates an author without a password and mails them a link to choose one
+pub async fn invite(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    Form(form): Form<InvitationForm>,
+) -> Result<Response, AppError> {
+    let user = admin(&auth)?;
+    let mut conn = state.pool.acquire().await?;
+
+    let author = match actions::create_author(form.name.trim(), form.email.trim(), &mut conn).await {
+        Ok(author) => author,
+        Err(error) => {
+            let html = render(user, &state, form, Some(error.to_string()), None).await?;
+            return Ok((StatusCode::UNPROCESSABLE_ENTITY, Html(html)).into_response());
+        }
+    };
+
+    let token = actions::create_password_token(author.id, "invitation", INVITATION_VALIDITY, &mut conn).await?;
+    let inviter = user.name.clone();
+    let author_ = author.clone();
+
+    tokio::task::spawn_blocking(move || mail_sender::send_invitation(&author_, &inviter, &token));
+
+    let notice = format!("An invitation has been sent to {}.", author.email);
+    let html = render(user, &state, InvitationForm::default(), None, Some(notice)).await?;
+
+    Ok(Html(html).into_response())
+}
+
+pub async fn disable(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = admin(&auth)?;
+
+    if user.id == id {
+        return Err(AppError::BadRequest("you can't disable yourself".to_owned()));
+    }
+
+    let mut conn = state.pool.acquire().await?;
+    actions::disable_author(id, &mut conn).await?;
+
+    Ok(Redirect::to(&authors_uri()))
+}
+
+pub async fn enable(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    admin(&auth)?;
+
+    let mut conn = state.pool.acquire().await?;
+    actions::enable_author(id, &mut conn).await?;
+
+    Ok(Redirect::to(&authors_uri()))
+}
@@ -0,0 +1,50 @@
+use crate::models::Author;
+use crate::uri_helpers::password_reset_token_uri;
+use crate::utils::mail;
+
+fn mailbox(author: &Author) -> String {
+    format!("{} <{}>", author.name.replace(['<', '>', '"'], ""), author.email)
+}
+
+pub fn send_password_reset(author: &Author, token: &str) {
+    let body = format!(
+        "Hi {},
+
+someone (hopefully you) asked to reset your password. Choose a new one here:
+
+{}
+
+The link is valid for one hour. If you didn't ask for it, just ignore this mail.
+
+Regards,
+ WWWTech",
+        author.name,
+        password_reset_token_uri(token)
+    );
+
+    if let Err(e) = mail::send_mail(&mailbox(author), "Reset your password", body) {
+        tracing::error!("could not send password reset mail: {}", e);
+    }
+}
+
+pub fn send_invitation(author: &Author, inviter: &str, token: &str) {
+    let body = format!(
+        "Hi {},
+
+{} invited you to write on WWWTech. Choose your password here to get started:
+
+{}
+
+The link is valid for seven days.
+
+Regards,
+ WWWTech",
+        author.name,
+        inviter,
+        password_reset_token_uri(token)
+    );
+
+    if let Err(e) = mail::send_mail(&mailbox(author), "Your invitation to WWWTech", body) {
+        tracing::error!("could not send invitation mail: {}", e);
+    }
+}
@@ -0,0 +1,132 @@
+use askama::Template;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Redirect, Response};
+use serde::Deserialize;
+use serde_json::json;
+use webauthn_rs::prelude::{PasskeyRegistration, RegisterPublicKeyCredential, Uuid};
+
+use super::actions;
+use crate::errors::AppError;
+use crate::models::Passkey;
+use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
+use crate::{AppState, AuthSession, utils as filters};
+
+/// the session key of a passkey registration in progress
+const REGISTRATION_KEY: &str = "passkey_registration";
+
+#[derive(Debug, Deserialize)]
+pub struct RegistrationForm {
+    name: String,
+    credential: RegisterPublicKeyCredential,
+}
+
+#[derive(Template)]
+#[template(path = "account/passkeys.html.j2")]
+pub struct Index<'a> {
+    lang: &'a str,
+    title: Option<&'a str>,
+    page_type: Option<&'a str>,
+    page_image: Option<&'a str>,
+    body_id: Option<&'a str>,
+    logged_in: bool,
+
+    passkeys: Vec<Passkey>,
+}
+
+pub async fn index(auth: AuthSession, State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let Some(user) = auth.user else {
+        return Err(AppError::Unauthorized);
+    };
+
+    let mut conn = state.pool.acquire().await?;
+    let passkeys = actions::list_passkeys(user.id, &mut conn).await?;
+
+    let html = Index {
+        lang: "en",
+        title: Some("Passkeys"),
+        page_type: None,
+        page_image: None,
+        body_id: None,
+        logged_in: true,
+        passkeys,
+    }
+    .render()?;
+
+    Ok(Html(html))
+}
+
+/// Starts a registration: returns the creation options for `navigator.credentials.create()`; already
+/// registered passkeys are excluded so that an authenticator isn't registered twice.
+pub async fn challenge(auth: AuthSession, State(state): State<AppState>) -> Result<Response, AppError> {
+    let Some(user) = &auth.user else {
+        return Err(AppError::Unauthorized);
+    };
+
+    let mut conn = state.pool.acquire().await?;
+    let existing = actions::list_passkeys(user.id, &mut conn)
+        .await?
+        .iter()
+        .filter_map(|passkey| passkey.credential().ok())
+        .map(|passkey| passkey.cred_id().clone())
+        .collect::<Vec<_>>();
+
+    let (options, registration) = state
+        .webauthn
+        .start_passkey_registration(Uuid::from_u128(user.id as u128), &user.email, &user.name, Some(existing))
+        .map_err(|e| AppError::InternalError(format!("could not start passkey registration: {}", e)))?;
+
+    auth.session
+        .insert(REGISTRATION_KEY, &registration)
+        .await
+        .map_err(|e| AppError::InternalError(format!("error writing session: {}", e)))?;
+
+    Ok(Json(options).into_response())
+}
+
+pub async fn create(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    Json(form): Json<RegistrationForm>,
+) -> Result<Response, AppError> {
+    let Some(user) = &auth.user else {
+        return Err(AppError::Unauthorized);
+    };
+
+    let registration = auth
+        .session
+        .remove::<PasskeyRegistration>(REGISTRATION_KEY)
+        .await
+        .map_err(|e| AppError::InternalError(format!("error reading session: {}", e)))?
+        .ok_or_else(|| AppError::BadRequest("no passkey registration in progress".to_owned()))?;
+
+    let name = form.name.trim();
+    let name = if name.is_empty() { "passkey" } else { name };
+
+    let credential = state
+        .webauthn
+        .finish_passkey_registration(&form.credential, &registration)
+        .map_err(|e| AppError::BadRequest(format!("could not register passkey: {}", e)))?;
+
+    let mut conn = state.pool.acquire().await?;
+    actions::create_passkey(user.id, name, &credential, &mut conn).await?;
+
+    Ok((StatusCode::CREATED, Json(json!({ "location": passkeys_uri() }))).into_response())
+}
+
+pub async fn delete(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    let Some(user) = auth.user else {
+        return Err(AppError::Unauthorized);
+    };
+
+    let mut conn = state.pool.acquire().await?;
+    actions::delete_passkey(id, user.id, &mut conn).await?;
+
+    Ok(Redirect::to(&passkeys_uri()))
+}
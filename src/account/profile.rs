@@ -0,0 +1,152 @@
+use askama::Template;
+use axum::extract::{Form, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Response};
+use serde::Deserialize;
+
+use super::actions;
+use crate::errors::AppError;
+use crate::models::{Author, AuthorProfile};
+use crate::session::actions::{hash_password, verify_password};
+use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
+use crate::{AppState, AuthSession};
+
+#[derive(Debug, Deserialize)]
+pub struct PasswordForm {
+    current_password: String,
+    password: String,
+    password_confirmation: String,
+}
+
+#[derive(Template)]
+#[template(path = "account/show.html.j2")]
+pub struct Show<'a> {
+    lang: &'a str,
+    title: Option<&'a str>,
+    page_type: Option<&'a str>,
+    page_image: Option<&'a str>,
+    body_id: Option<&'a str>,
+    logged_in: bool,
+
+    is_admin: bool,
+    form_data: AuthorProfile,
+    error: Option<String>,
+    password_error: Option<String>,
+    notice: Option<&'a str>,
+}
+
+impl<'a> Show<'a> {
+    fn new(author: &Author, form_data: AuthorProfile) -> Self {
+        Self {
+            lang: "en",
+            title: Some("Account"),
+            page_type: None,
+            page_image: None,
+            body_id: None,
+            logged_in: true,
+            is_admin: author.is_admin,
+            form_data,
+            error: None,
+            password_error: None,
+            notice: None,
+        }
+    }
+}
+
+fn profile(author: &Author) -> AuthorProfile {
+    AuthorProfile {
+        name: author.name.clone(),
+        email: author.email.clone(),
+        avatar: author.avatar.clone(),
+    }
+}
+
+pub async fn show(auth: AuthSession) -> Result<impl IntoResponse, AppError> {
+    let Some(user) = &auth.user else {
+        return Err(AppError::Unauthorized);
+    };
+
+    let html = Show::new(user, profile(user)).render()?;
+    Ok(Html(html))
+}
+
+pub async fn update(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    Form(form): Form<AuthorProfile>,
+) -> Result<Response, AppError> {
+    let Some(user) = &auth.user else {
+        return Err(AppError::Unauthorized);
+    };
+
+    let mut conn = state.pool.acquire().await?;
+
+    match actions::update_profile(user.id, &form, &mut conn).await {
+        Ok(author) => {
+            let html = Show {
+                notice: Some("Your profile has been saved."),
+                ..Show::new(&author, profile(&author))
+            }
+            .render()?;
+
+            Ok(Html(html).into_response())
+        }
+
+        Err(error) => {
+            let html = Show {
+                error: Some(error.to_string()),
+                ..Show::new(user, form)
+            }
+            .render()?;
+
+            Ok((StatusCode::UNPROCESSABLE_ENTITY, Html(html)).into_response())
+        }
+    }
+}
+
+/// Changes the password of the logged in author; all other sessions of the author end, the current one is
+/// renewed
+pub async fn update_password(
+    mut auth: AuthSession,
+    State(state): State<AppState>,
+    Form(form): Form<PasswordForm>,
+) -> Result<Response, AppError> {
+    let Some(user) = auth.user.clone() else {
+        return Err(AppError::Unauthorized);
+    };
+
+    let check = if verify_password(Some(&user), &form.current_password) {
+        actions::check_new_password(&form.password, &form.password_confirmation)
+    } else {
+        Err("the current password is wrong".to_owned())
+    };
+
+    if let Err(error) = check {
+        let html = Show {
+            password_error: Some(error),
+            ..Show::new(&user, profile(&user))
+        }
+        .render()?;
+
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Html(html)).into_response());
+    }
+
+    let encrypted_password = hash_password(&form.password)
+        .map_err(|e| AppError::InternalError(format!("could not hash password: {}", e)))?;
+
+    let mut conn = state.pool.acquire().await?;
+    let author = actions::update_password(user.id, &encrypted_password, &mut conn).await?;
+
+    auth.login(&author)
+        .await
+        .map_err(|e| AppError::InternalError(format!("error logging in: {}", e)))?;
+
+    let html = Show {
+        notice: Some("Your password has been changed."),
+        ..Show::new(&author, profile(&author))
+    }
+    .render()?;
+
+    Ok(Html(html).into_response())
+}
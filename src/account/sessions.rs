@@ -0,0 +1,78 @@
+use askama::Template;
+use axum::extract::{Path, State};
+use axum::response::{Html, IntoResponse, Redirect};
+
+use super::actions;
+use crate::errors::AppError;
+use crate::models::ActiveSession;
+use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
+use crate::utils::tokens::hash_token;
+use crate::{AppState, AuthSession, utils as filters};
+
+#[derive(Template)]
+#[template(path = "account/sessions.html.j2")]
+pub struct Index<'a> {
+    lang: &'a str,
+    title: Option<&'a str>,
+    page_type: Option<&'a str>,
+    page_image: Option<&'a str>,
+    body_id: Option<&'a str>,
+    logged_in: bool,
+
+    sessions: Vec<ActiveSession>,
+    current_key: Option<String>,
+}
+
+pub async fn index(auth: AuthSession, State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let Some(user) = &auth.user else {
+        return Err(AppError::Unauthorized);
+    };
+
+    let mut conn = state.pool.acquire().await?;
+    let sessions = actions::list_sessions(user.id, &mut conn).await?;
+
+    let html = Index {
+        lang: "en",
+        title: Some("Sessions"),
+        page_type: None,
+        page_image: None,
+        body_id: None,
+        logged_in: true,
+        sessions,
+        current_key: auth.session.id().map(|id| hash_token(&id.to_string())),
+    }
+    .render()?;
+
+    Ok(Html(html))
+}
+
+pub async fn delete(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let Some(user) = &auth.user else {
+        return Err(AppError::Unauthorized);
+    };
+
+    let mut conn = state.pool.acquire().await?;
+    actions::delete_session(user.id, &key, &mut conn).await?;
+
+    Ok(Redirect::to(&sessions_uri()))
+}
+
+pub async fn delete_others(auth: AuthSession, State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let Some(user) = &auth.user else {
+        return Err(AppError::Unauthorized);
+    };
+
+    let Some(current_id) = auth.session.id() else {
+        return Err(AppError::BadRequest("there is no current session".to_owned()));
+    };
+
+    let mut conn = state.pool.acquire().await?;
+    actions::delete_other_sessions(user.id, &current_id.to_string(), &mut conn).await?;
+
+    Ok(Redirect::to(&sessions_uri()))
+}
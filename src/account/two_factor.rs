@@ -0,0 +1,193 @@
+use askama::Template;
+use axum::extract::{Form, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Redirect, Response};
+use serde::Deserialize;
+
+use super::actions;
+use crate::errors::AppError;
+use crate::models::Author;
+use crate::session::actions::verify_second_factor;
+use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
+use crate::utils::totp::{generate_secret, qr_code, verify_code};
+use crate::{AppState, AuthSession};
+
+/// the session key of the secret of an enrollment in progress
+const ENROLLMENT_KEY: &str = "totp_enrollment";
+
+#[derive(Debug, Deserialize)]
+pub struct CodeForm {
+    code: String,
+}
+
+#[derive(Template)]
+#[template(path = "account/two_factor.html.j2")]
+pub struct Show<'a> {
+    lang: &'a str,
+    title: Option<&'a str>,
+    page_type: Option<&'a str>,
+    page_image: Option<&'a str>,
+    body_id: Option<&'a str>,
+    logged_in: bool,
+
+    enabled: bool,
+    required: bool,
+    secret: Option<String>,
+    qr_code: Option<String>,
+    remaining_codes: i64,
+    recovery_codes: Vec<String>,
+    error: Option<String>,
+}
+
+async fn render(
+    author: &Author,
+    auth: &AuthSession,
+    state: &AppState,
+    recovery_codes: Vec<String>,
+    error: Option<String>,
+) -> Result<String, AppError> {
+    let mut conn = state.pool.acquire().await?;
+    let enabled = author.has_two_factor();
+
+    let (secret, qr_code) = if enabled {
+        (None, None)
+    } else {
+        let secret = enrollment_secret(auth).await?;
+        let qr_code = qr_code(&secret, &author.email).map_err(AppError::InternalError)?;
+        (Some(secret), Some(qr_code))
+    };
+
+    let remaining_codes = actions::count_unused_recovery_codes(author.id, &mut conn).await?;
+
+    let html = Show {
+        lang: "en",
+        title: Some("Two-factor authentication"),
+        page_type: None,
+        page_image: None,
+        body_id: None,
+        logged_in: true,
+        enabled,
+        required: crate::utils::two_factor_required(),
+        secret,
+        qr_code,
+        remaining_codes,
+        recovery_codes,
+        error,
+    }
+    .render()?;
+
+    Ok(html)
+}
+
+/// The secret is kept in the session until the author confirmed it with a valid code, so that reloading
+/// the page doesn't invalidate an already scanned QR code
+async fn enrollment_secret(auth: &AuthSession) -> Result<String, AppError> {
+    let secret = auth
+        .session
+        .get::<String>(ENROLLMENT_KEY)
+        .await
+        .map_err(|e| AppError::InternalError(format!("error reading session: {}", e)))?;
+
+    if let Some(secret) = secret {
+        return Ok(secret);
+    }
+
+    let secret = generate_secret();
+    auth.session
+        .insert(ENROLLMENT_KEY, &secret)
+        .await
+        .map_err(|e| AppError::InternalError(format!("error writing session: {}", e)))?;
+
+    Ok(secret)
+}
+
+pub async fn show(auth: AuthSession, State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let Some(user) = &auth.user else {
+        return Err(AppError::Unauthorized);
+    };
+
+    let html = render(user, &auth, &state, Vec::new(), None).await?;
+    Ok(Html(html))
+}
+
+pub async fn enable(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    Form(form): Form<CodeForm>,
+) -> Result<Response, AppError> {
+    let Some(user) = auth.user.clone() else {
+        return Err(AppError::Unauthorized);
+    };
+
+    if user.has_two_factor() {
+        return Ok(Redirect::to(&two_factor_uri()).into_response());
+    }
+
+    let secret = enrollment_secret(&auth).await?;
+    let Some(step) = verify_code(&secret, &form.code, None) else {
+        let html = render(&user, &auth, &state, Vec::new(), Some("the code is invalid".to_owned())).await?;
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Html(html)).into_response());
+    };
+
+    let mut conn = state.pool.acquire().await?;
+    let recovery_codes = actions::enable_two_factor(user.id, &secret, step, &mut conn).await?;
+
+    auth.session
+        .remove::<String>(ENROLLMENT_KEY)
+        .await
+        .map_err(|e| AppError::InternalError(format!("error writing session: {}", e)))?;
+
+    let user = Author {
+        totp_secret: Some(secret),
+        totp_enabled_at: Some(chrono::Utc::now().naive_utc()),
+        totp_last_step: Some(step),
+        ..user
+    };
+
+    let html = render(&user, &auth, &state, recovery_codes, None).await?;
+    Ok(Html(html).into_response())
+}
+
+pub async fn recovery_codes(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    Form(form): Form<CodeForm>,
+) -> Result<Response, AppError> {
+    let Some(user) = &auth.user else {
+        return Err(AppError::Unauthorized);
+    };
+
+    let mut conn = state.pool.acquire().await?;
+
+    if !verify_second_factor(user, &form.code, &mut conn).await? {
+        let html = render(user, &auth, &state, Vec::new(), Some("the code is invalid".to_owned())).await?;
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Html(html)).into_response());
+    }
+
+    let recovery_codes = actions::replace_recovery_codes(user.id, &mut conn).await?;
+    let html = render(user, &auth, &state, recovery_codes, None).await?;
+
+    Ok(Html(html).into_response())
+}
+
+pub async fn disable(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    Form(form): Form<CodeForm>,
+) -> Result<Response, AppError> {
+    let Some(user) = &auth.user else {
+        return Err(AppError::Unauthorized);
+    };
+
+    let mut conn = state.pool.acquire().await?;
+
+    if !verify_second_factor(user, &form.code, &mut conn).await? {
+        let html = render(user, &auth, &state, Vec::new(), Some("the code is invalid".to_owned())).await?;
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Html(html)).into_response());
+    }
+
+    actions::disable_two_factor(user.id, &mut conn).await?;
+
+    Ok(Redirect::to(&two_factor_uri()).into_response())
+}
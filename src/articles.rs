@@ -1,11 +1,11 @@
 use axum::Router;
-use axum::middleware::map_response_with_state;
+use axum::middleware::{from_fn, map_response_with_state};
 use axum::routing::{get, post};
 use axum_login::login_required;
 use chrono::Duration;
 
 use crate::AppRouter;
-use crate::middleware::caching_middleware;
+use crate::middleware::{caching_middleware, two_factor_middleware};
 use crate::store::Store;
 
 pub mod actions;
@@ -25,6 +25,7 @@ pub fn configure(app: AppRouter) -> AppRouter {
         .route("/admin/articles/{id}/edit", get(edit::edit))
         .route("/admin/articles/{id}", post(edit::update))
         .route("/admin/articles/{id}/delete", post(delete::delete))
+        .route_layer(from_fn(two_factor_middleware))
         .route_layer(login_required!(Store, login_url = "/login"));
 
     let caching_router: AppRouter = Router::new()
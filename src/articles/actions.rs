@@ -142,10 +142,10 @@ pub async fn create_article(
         r#"
             INSERT INTO articles (
                 author_id, in_reply_to, title, slug, guid, article_format, excerpt, body, published, posse,
-                lang, inserted_at, updated_at, posse_visibility, content_warning
+                lang, inserted_at, updated_at, posse_visibility, content_warning, posse_targets
             )
             VALUES (
-                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16
             )
             RETURNING *
         "#,
@@ -163,7 +163,8 @@ pub async fn create_article(
         data.inserted_at,
         data.updated_at,
         data.posse_visibility,
-        data.content_warning
+        data.content_warning,
+        &data.posse_targets
     )
     .fetch_one(conn)
     .await?;
@@ -203,8 +204,9 @@ pub async fn update_article(
                 published = $6,
                 posse = $7,
                 lang = $8,
-                updated_at = $9
-            WHERE id = $10
+                updated_at = $9,
+                posse_targets = $10
+            WHERE id = $11
             RETURNING *
         "#,
         data.in_reply_to,
@@ -216,6 +218,7 @@ pub async fn update_article(
         data.posse,
         data.lang,
         now,
+        &data.posse_targets,
         article_id
     )
     .fetch_one(conn)
@@ -10,6 +10,7 @@ use super::{PER_PAGE, actions};
 use crate::errors::AppError;
 use crate::models::Article;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
 use crate::utils::paging::*;
 use crate::{AppState, AuthSession, utils as filters};
 
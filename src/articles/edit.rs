@@ -1,13 +1,16 @@
 use askama::Template;
-use axum::extract::{Form, Path, State};
+use axum::extract::{Path, State};
 use axum::http::StatusCode;
 use axum::response::{Html, IntoResponse, Redirect, Response};
+use axum_extra::extract::Form;
 
 use super::actions;
 use crate::errors::AppError;
 use crate::models::{Article, NewArticle};
-use crate::posse::mastodon::post_article;
+use crate::posse::syndicate::{syndicate_article, update_syndicated_article};
+use crate::posse::targets_added;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
 use crate::webmentions::send::send_mentions;
 use crate::{AppState, AuthSession, utils as filters};
 
@@ -49,6 +52,7 @@ pub async fn edit(State(state): State<AppState>, Path(id): Path<i32>) -> Result<
             lang: article.lang.clone(),
             posse_visibility: article.posse_visibility.clone(),
             content_warning: article.content_warning.clone(),
+            posse_targets: article.posse_targets.clone(),
             ..Default::default()
         },
         article,
@@ -84,17 +88,19 @@ pub async fn update(
             let uri = article_uri(&updated_article);
 
             if updated_article.published {
-                if updated_article.posse && (!article.posse || !article.published) {
-                    let article = updated_article.clone();
-                    tokio::task::spawn(async move {
-                        let _ = post_article(&article).await;
-                    });
+                let added = targets_added(
+                    updated_article.posse,
+                    &updated_article.posse_targets,
+                    article.posse,
+                    &article.posse_targets,
+                );
+                if !article.published || added {
+                    tokio::task::spawn(syndicate_article(updated_article.clone(), state.pool.clone()));
                 }
 
-                tokio::task::spawn_blocking(move || {
-                    let uri = article_uri(&updated_article);
-                    let _ = send_mentions(&uri);
-                });
+                tokio::task::spawn(update_syndicated_article(updated_article.clone(), state.pool.clone()));
+
+                tokio::task::spawn(send_mentions(uri.clone(), state.pool.clone()));
             }
 
             Ok(Redirect::to(&uri).into_response())
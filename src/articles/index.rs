@@ -9,6 +9,7 @@ use super::{PER_PAGE, actions};
 use crate::errors::AppError;
 use crate::models::Article;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
 use crate::utils::paging::{PageParams, Paging, get_page, get_paging};
 use crate::{AppState, AuthSession, utils as filters};
 
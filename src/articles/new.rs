@@ -1,13 +1,15 @@
 use askama::Template;
-use axum::extract::{Form, State};
+use axum::extract::State;
 use axum::http::StatusCode;
 use axum::response::{Html, IntoResponse, Redirect, Response};
+use axum_extra::extract::Form;
 
 use super::actions;
 use crate::errors::AppError;
 use crate::models::NewArticle;
-use crate::posse::mastodon::post_article;
+use crate::posse::syndicate::syndicate_article;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
 use crate::webmentions::send::send_mentions;
 use crate::{AppState, AuthSession, utils as filters};
 
@@ -62,16 +64,10 @@ pub(crate) async fn create(
 
             if article.published {
                 if article.posse {
-                    let article = article.clone();
-                    tokio::task::spawn(async move {
-                        let _ = post_article(&article).await;
-                    });
+                    tokio::task::spawn(syndicate_article(article.clone(), state.pool.clone()));
                 }
 
-                tokio::task::spawn_blocking(move || {
-                    let uri = article_uri(&article);
-                    let _ = send_mentions(&uri);
-                });
+                tokio::task::spawn(send_mentions(uri.clone(), state.pool.clone()));
             }
 
             Ok(Redirect::to(&uri).into_response())
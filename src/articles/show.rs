@@ -5,8 +5,11 @@ use sqlx::PgConnection;
 
 use super::actions;
 use crate::errors::AppError;
-use crate::models::Article;
+use crate::models::{Article, GroupedMentions, Syndication};
+use crate::posse::actions::list_syndications;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
+use crate::webmentions::actions::{ObjectType, list_mentions};
 use crate::{AppState, AuthSession, utils as filters};
 
 #[derive(Template)]
@@ -22,6 +25,8 @@ pub struct Show<'a> {
     article: &'a Article,
     index: bool,
     atom: bool,
+    mentions: Option<GroupedMentions>,
+    syndications: Vec<Syndication>,
 }
 
 async fn redirect_or_error(slug: String, conn: &mut PgConnection, logged_in: bool) -> Result<Response, AppError> {
@@ -67,6 +72,9 @@ pub async fn show(
         return redirect_or_error(slug, &mut conn, logged_in).await;
     };
 
+    let mentions = list_mentions(ObjectType::Article, article.id, &mut conn).await?;
+    let syndications = list_syndications(ObjectType::Article, article.id, &mut conn).await?;
+
     let html = Show {
         lang: "en",
         title: Some(&article.title),
@@ -77,6 +85,8 @@ pub async fn show(
         article: &article,
         index: false,
         atom: false,
+        mentions: Some(mentions.into()),
+        syndications,
     }
     .render()?;
 
@@ -0,0 +1,21 @@
+use chrono::Utc;
+use sqlx::{PgConnection, query};
+
+use crate::models::NewAuditLogEntry;
+
+/// Records security relevant events: logins, failed logins, logouts and changes made in the admin
+pub async fn record(entry: &NewAuditLogEntry, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    query!(
+        "INSERT INTO audit_log (author_id, action, path, email, ip, inserted_at) VALUES ($1, $2, $3, $4, $5, $6)",
+        entry.author_id,
+        entry.action,
+        entry.path,
+        entry.email,
+        entry.ip,
+        Utc::now().naive_utc()
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
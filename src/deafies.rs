@@ -1,13 +1,13 @@
 use axum::Router;
 use axum::extract::DefaultBodyLimit;
-use axum::middleware::map_response_with_state;
+use axum::middleware::{from_fn, map_response_with_state};
 use axum::routing::{get, post};
 use axum_login::login_required;
 use axum_typed_multipart::{FieldData, TryFromMultipart};
 use chrono::Duration;
 use tempfile::NamedTempFile;
 
-use crate::middleware::caching_middleware;
+use crate::middleware::{caching_middleware, two_factor_middleware};
 use crate::store::Store;
 use crate::{AppRouter, MAX_UPLOAD_SIZE};
 
@@ -30,6 +30,7 @@ pub struct DeafieData {
     pub published: bool,
     pub posse_visibility: String,
     pub content_warning: Option<String>,
+    pub posse_targets: Vec<String>,
 
     #[form_data(limit = "unlimited")]
     pub picture: Option<FieldData<NamedTempFile>>,
@@ -43,6 +44,7 @@ pub fn configure(app: AppRouter) -> AppRouter {
         .route("/admin/the-life-of-alfons/{id}", post(edit::update))
         .route("/admin/the-life-of-alfons/{id}/delete", post(delete::delete))
         .layer(DefaultBodyLimit::max(MAX_UPLOAD_SIZE))
+        .route_layer(from_fn(two_factor_middleware))
         .route_layer(login_required!(Store, login_url = "/login"));
 
     let caching_router: AppRouter = Router::new()
@@ -122,9 +122,9 @@ pub async fn create_deafie(
         r#"
         INSERT INTO deafies (
             author_id, title, slug, guid, image_name, image_content_type, excerpt,
-            body, published, inserted_at, updated_at, posse_visibility, content_warning
+            body, published, inserted_at, updated_at, posse_visibility, content_warning, posse_targets
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
         RETURNING *
         "#,
         data.author_id,
@@ -139,7 +139,8 @@ pub async fn create_deafie(
         data.inserted_at,
         data.updated_at,
         data.posse_visibility,
-        data.content_warning
+        data.content_warning,
+        &data.posse_targets
     )
     .fetch_one(&mut *tx)
     .await?;
@@ -193,9 +194,10 @@ pub async fn update_deafie(
                 excerpt = $3,
                 body = $4,
                 published = $5,
-                updated_at = $6
+                updated_at = $6,
+                posse_targets = $7
             WHERE
-                id = $7
+                id = $8
             RETURNING *
         "#,
         data.title,
@@ -204,6 +206,7 @@ pub async fn update_deafie(
         data.body,
         data.published,
         now,
+        &data.posse_targets,
         deafie_id
     )
     .fetch_one(conn)
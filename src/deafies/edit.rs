@@ -7,8 +7,10 @@ use axum_typed_multipart::TypedMultipart;
 use super::{DeafieData, actions};
 use crate::errors::AppError;
 use crate::models::{Deafie, NewDeafie, generate_deafie_pictures};
-use crate::posse::mastodon::post_deafie;
+use crate::posse::syndicate::{syndicate_deafie, update_syndicated_deafie};
+use crate::posse::targets_added;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
 use crate::webmentions::send::send_mentions;
 use crate::{AppState, AuthSession, utils as filters};
 
@@ -47,6 +49,7 @@ pub(crate) async fn edit(State(state): State<AppState>, Path(id): Path<i32>) ->
             published: deafie.published,
             posse_visibility: deafie.posse_visibility.clone(),
             content_warning: deafie.content_warning.clone(),
+            posse_targets: deafie.posse_targets.clone(),
             ..Default::default()
         },
 
@@ -92,6 +95,7 @@ pub async fn update(
         published: data.published,
         posse_visibility: data.posse_visibility,
         content_warning: data.content_warning,
+        posse_targets: data.posse_targets,
         author_id: Some(user.id),
         ..Default::default()
     };
@@ -112,18 +116,20 @@ pub async fn update(
 
             let uri = deafie_uri(&deafie);
 
+            let pool = state.pool.clone();
             tokio::task::spawn_blocking(move || {
                 let _ = generate_deafie_pictures(&deafie);
 
                 if deafie.published {
-                    let uri = deafie_uri(&deafie);
-                    let _ = send_mentions(&uri);
+                    tokio::task::spawn(send_mentions(deafie_uri(&deafie), pool.clone()));
 
-                    if !old_deafie.published {
-                        tokio::task::spawn(async move {
-                            let _ = post_deafie(&deafie).await;
-                        });
+                    if !old_deafie.published
+                        || targets_added(true, &deafie.posse_targets, true, &old_deafie.posse_targets)
+                    {
+                        tokio::task::spawn(syndicate_deafie(deafie.clone(), pool.clone()));
                     }
+
+                    tokio::task::spawn(update_syndicated_deafie(deafie, pool));
                 }
             });
 
@@ -7,8 +7,9 @@ use axum_typed_multipart::TypedMultipart;
 use super::{DeafieData, actions};
 use crate::errors::AppError;
 use crate::models::{NewDeafie, generate_deafie_pictures};
-use crate::posse::mastodon::post_deafie;
+use crate::posse::syndicate::syndicate_deafie;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
 use crate::webmentions::send::send_mentions;
 use crate::{AppState, AuthSession, utils as filters};
 
@@ -72,6 +73,7 @@ pub(crate) async fn create(
         published: data.published,
         posse_visibility: data.posse_visibility,
         content_warning: data.content_warning,
+        posse_targets: data.posse_targets,
         author_id: Some(user.id),
         ..Default::default()
     };
@@ -90,16 +92,14 @@ pub(crate) async fn create(
         Ok(deafie) => {
             let uri = deafie_uri(&deafie);
 
+            let pool = state.pool.clone();
             tokio::task::spawn_blocking(move || {
                 let _ = generate_deafie_pictures(&deafie);
 
                 if deafie.published {
-                    let uri = deafie_uri(&deafie);
-                    let _ = send_mentions(&uri);
+                    tokio::task::spawn(send_mentions(deafie_uri(&deafie), pool.clone()));
 
-                    tokio::task::spawn(async move {
-                        let _ = post_deafie(&deafie).await;
-                    });
+                    tokio::task::spawn(syndicate_deafie(deafie, pool));
                 }
             });
 
@@ -10,10 +10,13 @@ use tokio_util::io::ReaderStream;
 
 use super::actions;
 use crate::errors::AppError;
-use crate::models::Deafie;
+use crate::models::{Deafie, GroupedMentions, Syndication};
 use crate::pictures::{ImageTypes, TypeParams};
+use crate::posse::actions::list_syndications;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
 use crate::utils::deafie_image_base_path;
+use crate::webmentions::actions::{ObjectType, list_mentions};
 use crate::{AppState, AuthSession, utils as filters};
 
 #[derive(Template)]
@@ -29,6 +32,8 @@ pub struct Show<'a> {
     deafie: &'a Deafie,
     index: bool,
     atom: bool,
+    mentions: Option<GroupedMentions>,
+    syndications: Vec<Syndication>,
 }
 
 pub async fn show(
@@ -112,6 +117,9 @@ pub async fn show_post(state: AppState, logged_in: bool, guid: String) -> Result
         None
     };
 
+    let mentions = list_mentions(ObjectType::Deafie, deafie.id, &mut conn).await?;
+    let syndications = list_syndications(ObjectType::Deafie, deafie.id, &mut conn).await?;
+
     let html = Show {
         lang: "de",
         title: Some(&deafie.title),
@@ -122,6 +130,8 @@ pub async fn show_post(state: AppState, logged_in: bool, guid: String) -> Result
         deafie: &deafie,
         index: false,
         atom: false,
+        mentions: Some(mentions.into()),
+        syndications,
     }
     .render()?;
 
@@ -7,6 +7,8 @@ pub enum AppError {
     InternalError(String),
     BadRequest(String),
     Unauthorized,
+    Forbidden(String),
+    TooManyRequests(String),
     TemplateError(String),
 }
 
@@ -18,6 +20,8 @@ impl IntoResponse for AppError {
             AppError::InternalError(s) => (StatusCode::INTERNAL_SERVER_ERROR, format!("internal error: {}", s)),
             AppError::BadRequest(s) => (StatusCode::BAD_REQUEST, format!("bad request: {}", s)),
             AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "unauthorized".to_string()),
+            AppError::Forbidden(s) => (StatusCode::FORBIDDEN, format!("forbidden: {}", s)),
+            AppError::TooManyRequests(s) => (StatusCode::TOO_MANY_REQUESTS, format!("too many requests: {}", s)),
             AppError::TemplateError(s) => (StatusCode::INTERNAL_SERVER_ERROR, format!("template error: {}", s)),
         };
 
@@ -27,7 +31,10 @@ impl IntoResponse for AppError {
 
 impl From<sqlx::Error> for AppError {
     fn from(e: sqlx::Error) -> Self {
-        AppError::DbError(e)
+        match e {
+            sqlx::Error::RowNotFound => AppError::NotFound("no such record".to_owned()),
+            e => AppError::DbError(e),
+        }
     }
 }
 
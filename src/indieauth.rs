@@ -0,0 +1,99 @@
+use axum::http::StatusCode;
+use axum::middleware::from_fn;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use axum_login::login_required;
+use serde_json::json;
+use url::Url;
+
+use crate::AppRouter;
+use crate::errors::AppError;
+use crate::middleware::two_factor_middleware;
+use crate::store::Store;
+
+pub mod actions;
+pub mod authorize;
+pub mod client;
+pub mod discovery;
+pub mod metadata;
+pub mod token;
+
+/// the scopes we grant; requested scopes not in this list are dropped
+pub static SCOPES: [&str; 4] = ["create", "update", "delete", "media"];
+
+pub fn configure(app: AppRouter) -> AppRouter {
+    let authed_router: AppRouter = Router::new()
+        .route("/indieauth/auth", get(authorize::authorize))
+        .route("/indieauth/auth/approve", post(authorize::approve))
+        .route_layer(from_fn(two_factor_middleware))
+        .route_layer(login_required!(Store, login_url = "/login"));
+
+    app.merge(authed_router)
+        .route("/indieauth/auth", post(authorize::redeem))
+        .route("/indieauth/token", get(token::verify).post(token::token))
+        .route("/indieauth/introspect", post(token::introspect))
+        .route("/indieauth/revoke", post(token::revoke))
+        .route("/.well-known/oauth-authorization-server", get(metadata::metadata))
+}
+
+/// An OAuth 2.0 error response
+pub struct OAuthError {
+    status: StatusCode,
+    error: &'static str,
+    description: String,
+}
+
+impl OAuthError {
+    pub fn new(error: &'static str, description: impl Into<String>) -> Self {
+        let status = match error {
+            "invalid_token" | "unauthorized" => StatusCode::UNAUTHORIZED,
+            "server_error" => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        };
+
+        Self {
+            status,
+            error,
+            description: description.into(),
+        }
+    }
+}
+
+impl IntoResponse for OAuthError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.error, "error_description": self.description }))).into_response()
+    }
+}
+
+impl From<sqlx::Error> for OAuthError {
+    fn from(e: sqlx::Error) -> Self {
+        OAuthError::new("server_error", format!("db error: {}", e))
+    }
+}
+
+/// Parses and checks a `client_id`: it has to be a http(s) URL without fragment and credentials
+pub fn parse_client_id(client_id: &str) -> Result<Url, AppError> {
+    let url = Url::parse(client_id).map_err(|_| AppError::BadRequest("client_id is not a valid URL".to_owned()))?;
+
+    if !matches!(url.scheme(), "http" | "https")
+        || url.fragment().is_some()
+        || !url.username().is_empty()
+        || url.password().is_some()
+    {
+        return Err(AppError::BadRequest("client_id is not a valid client identifier".to_owned()));
+    }
+
+    Ok(url)
+}
+
+/// Reduces a space separated list of requested scopes to the ones we support
+pub fn granted_scopes<'a>(requested: impl IntoIterator<Item = &'a str>) -> Vec<&'static str> {
+    let requested = requested.into_iter().collect::<Vec<_>>();
+
+    SCOPES
+        .iter()
+        .filter(|scope| requested.contains(scope))
+        .copied()
+        .collect()
+}
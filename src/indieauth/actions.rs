@@ -0,0 +1,136 @@
+use chrono::Duration;
+use sqlx::{PgConnection, query, query_as};
+
+use crate::models::{AccessToken, AuthorizationCode};
+use crate::utils::tokens::{generate_token, hash_token};
+
+/// how long an authorization code may be redeemed
+const CODE_LIFETIME: Duration = Duration::minutes(10);
+/// how long an access token is valid; the client has to ask for authorization again afterwards
+pub const TOKEN_LIFETIME: Duration = Duration::days(90);
+
+pub struct NewAuthorizationCode<'a> {
+    pub author_id: i32,
+    pub client_id: &'a str,
+    pub redirect_uri: &'a str,
+    pub scope: &'a str,
+    pub code_challenge: &'a str,
+    pub me: &'a str,
+}
+
+/// Creates an authorization code and returns it in plain text; only its hash is stored
+pub async fn create_authorization_code(
+    data: &NewAuthorizationCode<'_>,
+    conn: &mut PgConnection,
+) -> Result<String, sqlx::Error> {
+    let code = generate_token();
+    let now = chrono::Utc::now().naive_utc();
+
+    query!(
+        r#"
+            INSERT INTO authorization_codes
+              (code_hash, author_id, client_id, redirect_uri, scope, code_challenge, me, expires_at, inserted_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+        hash_token(&code),
+        data.author_id,
+        data.client_id,
+        data.redirect_uri,
+        data.scope,
+        data.code_challenge,
+        data.me,
+        now + CODE_LIFETIME,
+        now
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(code)
+}
+
+/// Looks up an authorization code and deletes it, so every code can be redeemed only once
+pub async fn take_authorization_code(
+    code: &str,
+    conn: &mut PgConnection,
+) -> Result<Option<AuthorizationCode>, sqlx::Error> {
+    let now = chrono::Utc::now().naive_utc();
+
+    query!("DELETE FROM authorization_codes WHERE expires_at < $1", now)
+        .execute(&mut *conn)
+        .await?;
+
+    query_as!(AuthorizationCode, "DELETE FROM authorization_codes WHERE code_hash = $1 RETURNING *", hash_token(code))
+        .fetch_optional(conn)
+        .await
+}
+
+/// Issues an access token for a redeemed authorization code and returns it in plain text
+pub async fn create_access_token(code: &AuthorizationCode, conn: &mut PgConnection) -> Result<String, sqlx::Error> {
+    let token = generate_token();
+    let now = chrono::Utc::now().naive_utc();
+
+    query!(
+        r#"
+            INSERT INTO access_tokens (token_hash, author_id, client_id, scope, me, expires_at, inserted_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+        hash_token(&token),
+        code.author_id,
+        code.client_id,
+        code.scope,
+        code.me,
+        now + TOKEN_LIFETIME,
+        now,
+        now
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(token)
+}
+
+/// Returns the access token if it is valid, has not expired and has not been revoked
+pub async fn get_active_access_token(token: &str, conn: &mut PgConnection) -> Result<Option<AccessToken>, sqlx::Error> {
+    let now = chrono::Utc::now().naive_utc();
+
+    query_as!(
+        AccessToken,
+        "SELECT * FROM access_tokens WHERE token_hash = $1 AND revoked_at IS NULL AND expires_at > $2",
+        hash_token(token),
+        now
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+/// Like `get_active_access_token`, but records the use of the token
+pub async fn use_access_token(token: &str, conn: &mut PgConnection) -> Result<Option<AccessToken>, sqlx::Error> {
+    let now = chrono::Utc::now().naive_utc();
+
+    query_as!(
+        AccessToken,
+        r#"
+            UPDATE access_tokens SET last_used_at = $1
+            WHERE token_hash = $2 AND revoked_at IS NULL AND expires_at > $1
+            RETURNING *
+        "#,
+        now,
+        hash_token(token)
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+pub async fn revoke_access_token(token: &str, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    let now = chrono::Utc::now().naive_utc();
+
+    query!(
+        "UPDATE access_tokens SET revoked_at = $1, updated_at = $1 WHERE token_hash = $2 AND revoked_at IS NULL",
+        now,
+        hash_token(token)
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,244 @@
+use askama::Template;
+use axum::extract::{Form, Query, RawForm, State};
+use axum::response::{Html, IntoResponse, Json, Redirect, Response};
+use serde::Deserialize;
+use serde_json::json;
+use url::Url;
+
+use super::actions::{NewAuthorizationCode, create_authorization_code, take_authorization_code};
+use super::client::fetch_client_info;
+use super::{OAuthError, SCOPES, granted_scopes, parse_client_id};
+use crate::errors::AppError;
+use crate::models::{Author, AuthorizationCode};
+use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
+use crate::utils::tokens::{constant_time_eq, pkce_challenge};
+use crate::{AppState, AuthSession};
+
+#[derive(Debug, Deserialize)]
+pub struct AuthorizationRequest {
+    response_type: Option<String>,
+    client_id: String,
+    redirect_uri: String,
+    state: String,
+    code_challenge: Option<String>,
+    code_challenge_method: Option<String>,
+    scope: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "indieauth/authorize.html.j2")]
+pub struct Authorize<'a> {
+    lang: &'a str,
+    title: Option<&'a str>,
+    page_type: Option<&'a str>,
+    page_image: Option<&'a str>,
+    body_id: Option<&'a str>,
+    logged_in: bool,
+
+    client_id: String,
+    client_name: Option<String>,
+    redirect_uri: String,
+    state: String,
+    code_challenge: String,
+    me: String,
+    scopes: Vec<(&'static str, bool)>,
+}
+
+/// Checks that `redirect_uri` belongs to the client: it has to be on the same host as the `client_id` or
+/// be registered on the client's page.
+async fn verify_client(client_id: &str, redirect_uri: &str) -> Result<(Url, Option<String>), AppError> {
+    let client_id = parse_client_id(client_id)?;
+    let redirect_uri =
+        Url::parse(redirect_uri).map_err(|_| AppError::BadRequest("redirect_uri is not a valid URL".to_owned()))?;
+
+    let info = fetch_client_info(&client_id).await;
+
+    let same_origin = redirect_uri.scheme() == client_id.scheme()
+        && redirect_uri.host_str() == client_id.host_str()
+        && redirect_uri.port_or_known_default() == client_id.port_or_known_default();
+
+    if !same_origin && !info.redirect_uris.iter().any(|uri| uri == redirect_uri.as_str()) {
+        return Err(AppError::BadRequest("redirect_uri is not registered for this client".to_owned()));
+    }
+
+    Ok((redirect_uri, info.name))
+}
+
+/// The identity a token of `author` asserts: the site for the owner, the author's own URL for everyone else
+fn identity(author: &Author) -> Result<String, AppError> {
+    if author.is_admin {
+        return Ok(root_uri());
+    }
+
+    author
+        .url
+        .clone()
+        .filter(|url| !url.is_empty())
+        .ok_or_else(|| AppError::Forbidden("you have no identity URL to sign in with".to_owned()))
+}
+
+fn redirect_with(mut redirect_uri: Url, params: &[(&str, &str)]) -> Response {
+    {
+        let mut query = redirect_uri.query_pairs_mut();
+        for (key, value) in params {
+            query.append_pair(key, value);
+        }
+        query.append_pair("iss", &root_uri());
+    }
+
+    Redirect::to(redirect_uri.as_str()).into_response()
+}
+
+/// The authorization endpoint: asks the logged in author whether the client may act on their behalf
+pub async fn authorize(auth: AuthSession, Query(request): Query<AuthorizationRequest>) -> Result<Response, AppError> {
+    let Some(user) = auth.user else {
+        return Err(AppError::Unauthorized);
+    };
+    let me = identity(&user)?;
+
+    let (redirect_uri, client_name) = verify_client(&request.client_id, &request.redirect_uri).await?;
+
+    if request.response_type.as_deref().unwrap_or("code") != "code" {
+        return Ok(redirect_with(redirect_uri, &[("error", "unsupported_response_type"), ("state", &request.state)]));
+    }
+
+    let code_challenge = match (request.code_challenge, request.code_challenge_method.as_deref()) {
+        (Some(challenge), Some("S256")) if !challenge.is_empty() => challenge,
+        _ => {
+            return Ok(redirect_with(
+                redirect_uri,
+                &[
+                    ("error", "invalid_request"),
+                    ("error_description", "a S256 code_challenge is required"),
+                    ("state", &request.state),
+                ],
+            ));
+        }
+    };
+
+    let requested = request.scope.unwrap_or_default();
+    let requested = granted_scopes(requested.split_whitespace());
+
+    let html = Authorize {
+        lang: "en",
+        title: Some("Authorize application"),
+        page_type: None,
+        page_image: None,
+        body_id: None,
+        logged_in: true,
+
+        client_id: request.client_id,
+        client_name,
+        redirect_uri: redirect_uri.to_string(),
+        state: request.state,
+        code_challenge,
+        me,
+        scopes: SCOPES.iter().map(|scope| (*scope, requested.contains(scope))).collect(),
+    }
+    .render()?;
+
+    Ok(Html(html).into_response())
+}
+
+/// Handles the consent form: redirects back to the client with an authorization code or an error
+pub async fn approve(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    RawForm(body): RawForm,
+) -> Result<Response, AppError> {
+    let Some(user) = auth.user else {
+        return Err(AppError::Unauthorized);
+    };
+    let me = identity(&user)?;
+
+    let params = url::form_urlencoded::parse(&body).into_owned().collect::<Vec<_>>();
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+            .unwrap_or_default()
+    };
+
+    let (redirect_uri, _) = verify_client(param("client_id"), param("redirect_uri")).await?;
+
+    if param("decision") != "approve" {
+        return Ok(redirect_with(redirect_uri, &[("error", "access_denied"), ("state", param("state"))]));
+    }
+
+    let scopes = granted_scopes(
+        params
+            .iter()
+            .filter(|(key, _)| key == "scope")
+            .map(|(_, value)| value.as_str()),
+    );
+
+    // both URLs are stored normalized, `redeem_code()` normalizes the ones it gets the same way
+    let client_id = parse_client_id(param("client_id"))?;
+
+    let mut conn = state.pool.acquire().await?;
+    let code = create_authorization_code(
+        &NewAuthorizationCode {
+            author_id: user.id,
+            client_id: client_id.as_str(),
+            redirect_uri: redirect_uri.as_str(),
+            scope: &scopes.join(" "),
+            code_challenge: param("code_challenge"),
+            me: &me,
+        },
+        &mut conn,
+    )
+    .await?;
+
+    Ok(redirect_with(redirect_uri, &[("code", &code), ("state", param("state"))]))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CodeRedemption {
+    pub grant_type: Option<String>,
+    pub code: String,
+    pub client_id: String,
+    pub redirect_uri: String,
+    pub code_verifier: String,
+}
+
+/// Redeems an authorization code: checks that it was issued for this client and redirect URL and that the
+/// PKCE code verifier matches the challenge.
+pub async fn redeem_code(
+    request: &CodeRedemption,
+    conn: &mut sqlx::PgConnection,
+) -> Result<AuthorizationCode, OAuthError> {
+    if request.grant_type.as_deref().unwrap_or("authorization_code") != "authorization_code" {
+        return Err(OAuthError::new("unsupported_grant_type", "only authorization_code is supported"));
+    }
+
+    let Some(code) = take_authorization_code(&request.code, conn).await? else {
+        return Err(OAuthError::new("invalid_grant", "the authorization code is invalid or expired"));
+    };
+
+    let same_client = parse_client_id(&request.client_id).is_ok_and(|client_id| client_id.as_str() == code.client_id);
+    let same_redirect_uri =
+        Url::parse(&request.redirect_uri).is_ok_and(|redirect_uri| redirect_uri.as_str() == code.redirect_uri);
+
+    if !same_client || !same_redirect_uri {
+        return Err(OAuthError::new("invalid_grant", "the authorization code was issued to another client"));
+    }
+
+    if !constant_time_eq(&pkce_challenge(&request.code_verifier), &code.code_challenge) {
+        return Err(OAuthError::new("invalid_grant", "the code verifier does not match"));
+    }
+
+    Ok(code)
+}
+
+/// Code redemption at the authorization endpoint, for clients only interested in the user's identity
+pub async fn redeem(
+    State(state): State<AppState>,
+    Form(request): Form<CodeRedemption>,
+) -> Result<Response, OAuthError> {
+    let mut conn = state.pool.acquire().await?;
+    let code = redeem_code(&request, &mut conn).await?;
+
+    Ok(Json(json!({ "me": code.me })).into_response())
+}
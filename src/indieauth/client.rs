@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use reqwest::header::{CONTENT_TYPE, LINK};
+use serde::Deserialize;
+use url::Url;
+use visdom::Vis;
+use visdom::types::IAttrValue;
+
+use crate::webmentions::fetch::{build_client, is_public_url};
+use crate::webmentions::send::parse_link_header;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What a client tells about itself on its `client_id` page
+#[derive(Debug, Default)]
+pub struct ClientInfo {
+    pub name: Option<String>,
+    pub redirect_uris: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ClientMetadata {
+    client_name: Option<String>,
+    #[serde(default)]
+    redirect_uris: Vec<String>,
+}
+
+/// Fetches the `client_id` URL for the client's name and its registered redirect URLs, either from a
+/// JSON metadata document, from `Link` headers or from `h-app` markup and `rel="redirect_uri"` links.
+/// Clients on non-public hosts (e.g. running on localhost) are not fetched.
+pub async fn fetch_client_info(client_id: &Url) -> ClientInfo {
+    if !is_public_url(client_id) {
+        return ClientInfo::default();
+    }
+
+    let Ok(client) = build_client(FETCH_TIMEOUT) else {
+        return ClientInfo::default();
+    };
+
+    let rsp = match client.get(client_id.as_str()).send().await {
+        Ok(rsp) if rsp.status().is_success() => rsp,
+        _ => return ClientInfo::default(),
+    };
+
+    let base_url = rsp.url().clone();
+    let resolve = |uri: &str| base_url.join(uri).ok().map(|url| url.to_string());
+
+    let mut redirect_uris = rsp
+        .headers()
+        .get_all(LINK)
+        .into_iter()
+        .filter_map(|hdr| hdr.to_str().ok())
+        .flat_map(parse_link_header)
+        .filter(|link| is_redirect_uri_rel(&link.rel))
+        .filter_map(|link| resolve(&link.uri))
+        .collect::<Vec<_>>();
+
+    let content_type = rsp
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|hdr| hdr.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let Ok(body) = rsp.text().await else {
+        return ClientInfo {
+            name: None,
+            redirect_uris,
+        };
+    };
+
+    if content_type.contains("json") {
+        if let Ok(metadata) = serde_json::from_str::<ClientMetadata>(&body) {
+            redirect_uris.extend(metadata.redirect_uris);
+
+            return ClientInfo {
+                name: metadata.client_name,
+                redirect_uris,
+            };
+        }
+    }
+
+    let Ok(tree) = Vis::load(&body) else {
+        return ClientInfo {
+            name: None,
+            redirect_uris,
+        };
+    };
+
+    for element in tree.find("[rel][href]") {
+        let tag_name = element.tag_name();
+        if tag_name != "LINK" && tag_name != "A" {
+            continue;
+        }
+
+        if let (Some(IAttrValue::Value(rel, _)), Some(IAttrValue::Value(href, _))) =
+            (element.get_attribute("rel"), element.get_attribute("href"))
+        {
+            if is_redirect_uri_rel(&rel) {
+                redirect_uris.extend(resolve(&href));
+            }
+        }
+    }
+
+    let name = tree
+        .find(".h-app .p-name, .h-x-app .p-name")
+        .first()
+        .text()
+        .trim()
+        .to_owned();
+
+    ClientInfo {
+        name: if name.is_empty() { None } else { Some(name) },
+        redirect_uris,
+    }
+}
+
+fn is_redirect_uri_rel(rel: &str) -> bool {
+    rel.split_ascii_whitespace()
+        .any(|rel| rel.eq_ignore_ascii_case("redirect_uri"))
+}
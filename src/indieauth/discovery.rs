@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use reqwest::header::{CONTENT_TYPE, LINK};
+use serde::Deserialize;
+use url::{Host, Url};
+use visdom::Vis;
+use visdom::types::IAttrValue;
+
+use crate::webmentions::fetch::build_client;
+use crate::webmentions::send::parse_link_header;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The IndieAuth endpoints of a profile URL
+#[derive(Debug)]
+pub struct Endpoints {
+    /// the profile URL after following redirects
+    pub me: Url,
+    pub issuer: Option<String>,
+    pub authorization_endpoint: Url,
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    issuer: Option<String>,
+    authorization_endpoint: Option<String>,
+}
+
+/// Turns user input like `example.com` into a profile URL as required by the IndieAuth spec: http(s), a
+/// domain name instead of an IP address, no fragment and credentials, and at least `/` as path.
+pub fn normalize_profile_url(input: &str) -> Option<Url> {
+    let input = input.trim();
+    let url = if input.contains("://") {
+        Url::parse(input).ok()?
+    } else {
+        Url::parse(&format!("https://{}", input)).ok()?
+    };
+
+    let valid = matches!(url.scheme(), "http" | "https")
+        && matches!(url.host(), Some(Host::Domain(_)))
+        && url.fragment().is_none()
+        && url.username().is_empty()
+        && url.password().is_none()
+        && !url
+            .path_segments()
+            .is_some_and(|mut segments| segments.any(|s| s == "." || s == ".."));
+
+    if valid { Some(url) } else { None }
+}
+
+/// Discovers the authorization endpoint of a profile URL: via the `indieauth-metadata` document or,
+/// for servers implementing earlier versions of the spec, the `authorization_endpoint` link.
+pub async fn discover_endpoints(me: &Url) -> Result<Endpoints> {
+    let client = build_client(FETCH_TIMEOUT)?;
+    let rsp = client.get(me.as_str()).send().await?.error_for_status()?;
+    let base_url = rsp.url().clone();
+
+    let mut links = rsp
+        .headers()
+        .get_all(LINK)
+        .into_iter()
+        .filter_map(|hdr| hdr.to_str().ok())
+        .flat_map(parse_link_header)
+        .map(|link| (link.rel, link.uri))
+        .collect::<Vec<_>>();
+
+    let is_html = rsp
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|hdr| hdr.to_str().ok())
+        .is_none_or(|content_type| content_type.contains("html"));
+
+    if is_html {
+        let body = rsp.text().await?;
+        links.extend(find_links_in_html(&body)?);
+    }
+
+    let find = |rel: &str| {
+        links
+            .iter()
+            .find(|(rels, _)| rels.split_ascii_whitespace().any(|r| r.eq_ignore_ascii_case(rel)))
+            .and_then(|(_, uri)| base_url.join(uri).ok())
+    };
+
+    if let Some(metadata_url) = find("indieauth-metadata") {
+        let metadata = client
+            .get(metadata_url.as_str())
+            .header("Accept", "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Metadata>()
+            .await?;
+
+        let endpoint = metadata
+            .authorization_endpoint
+            .ok_or_else(|| anyhow!("the metadata document lacks an authorization endpoint"))?;
+
+        return Ok(Endpoints {
+            me: base_url.clone(),
+            issuer: metadata.issuer,
+            authorization_endpoint: base_url.join(&endpoint)?,
+        });
+    }
+
+    let authorization_endpoint =
+        find("authorization_endpoint").ok_or_else(|| anyhow!("{} does not announce IndieAuth endpoints", me))?;
+
+    Ok(Endpoints {
+        me: base_url.clone(),
+        issuer: None,
+        authorization_endpoint,
+    })
+}
+
+fn find_links_in_html(body: &str) -> Result<Vec<(String, String)>> {
+    let tree = Vis::load(body).map_err(|e| anyhow!("could not parse profile page: {}", e))?;
+
+    let links = tree
+        .find("link[rel][href]")
+        .into_iter()
+        .filter_map(|element| match (element.get_attribute("rel"), element.get_attribute("href")) {
+            (Some(IAttrValue::Value(rel, _)), Some(IAttrValue::Value(href, _))) => Some((rel, href)),
+            _ => None,
+        })
+        .collect();
+
+    Ok(links)
+}
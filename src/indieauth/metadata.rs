@@ -0,0 +1,22 @@
+use axum::Json;
+use axum::response::IntoResponse;
+use serde_json::json;
+
+use super::SCOPES;
+use crate::uri_helpers::*;
+
+/// The IndieAuth server metadata (RFC 8414)
+pub async fn metadata() -> impl IntoResponse {
+    Json(json!({
+        "issuer": root_uri(),
+        "authorization_endpoint": authorization_endpoint_uri(),
+        "token_endpoint": token_endpoint_uri(),
+        "introspection_endpoint": introspection_endpoint_uri(),
+        "revocation_endpoint": revocation_endpoint_uri(),
+        "scopes_supported": SCOPES,
+        "response_types_supported": ["code"],
+        "grant_types_supported": ["authorization_code"],
+        "code_challenge_methods_supported": ["S256"],
+        "authorization_response_iss_parameter_supported": true,
+    }))
+}
@@ -0,0 +1,124 @@
+use axum::Json;
+use axum::extract::{Form, State};
+use axum::http::HeaderMap;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::OAuthError;
+use super::actions::{
+    TOKEN_LIFETIME, create_access_token, get_active_access_token, revoke_access_token, use_access_token,
+};
+use super::authorize::{CodeRedemption, redeem_code};
+use crate::micropub::auth::access_token;
+use crate::{AppState, AuthSession};
+
+#[derive(Debug, Deserialize)]
+pub struct TokenRequest {
+    action: Option<String>,
+    token: Option<String>,
+
+    #[serde(flatten)]
+    redemption: Option<CodeRedemption>,
+}
+
+/// The token endpoint: exchanges an authorization code for an access token
+pub async fn token(State(state): State<AppState>, Form(request): Form<TokenRequest>) -> Result<Response, OAuthError> {
+    let mut conn = state.pool.acquire().await?;
+
+    // revocation as specified by earlier versions of IndieAuth
+    if request.action.as_deref() == Some("revoke") {
+        if let Some(token) = &request.token {
+            revoke_access_token(token, &mut conn).await?;
+        }
+
+        return Ok(StatusCode::OK.into_response());
+    }
+
+    let Some(redemption) = request.redemption else {
+        return Err(OAuthError::new("invalid_request", "code, client_id, redirect_uri and code_verifier are required"));
+    };
+
+    let code = redeem_code(&redemption, &mut conn).await?;
+
+    if code.scope.is_empty() {
+        return Err(OAuthError::new(
+            "invalid_grant",
+            "no scope has been granted, there is nothing to issue a token for",
+        ));
+    }
+
+    let token = create_access_token(&code, &mut conn).await?;
+
+    Ok(Json(json!({
+        "access_token": token,
+        "token_type": "Bearer",
+        "expires_in": TOKEN_LIFETIME.num_seconds(),
+        "scope": code.scope,
+        "me": code.me,
+    }))
+    .into_response())
+}
+
+/// Token verification as specified by earlier versions of IndieAuth: a GET with the token as bearer token
+pub async fn verify(State(state): State<AppState>, headers: HeaderMap) -> Result<Response, OAuthError> {
+    let Some(token) = access_token(&headers, None) else {
+        return Err(OAuthError::new("unauthorized", "no access token given"));
+    };
+
+    let mut conn = state.pool.acquire().await?;
+    let Some(token) = use_access_token(&token, &mut conn).await? else {
+        return Err(OAuthError::new("invalid_token", "the access token is invalid, expired or has been revoked"));
+    };
+
+    Ok(Json(json!({ "me": token.me, "client_id": token.client_id, "scope": token.scope })).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenParam {
+    token: String,
+}
+
+/// Token introspection (RFC 7662); the caller has to be logged in or present a valid token of its own
+pub async fn introspect(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Form(request): Form<TokenParam>,
+) -> Result<Response, OAuthError> {
+    let mut conn = state.pool.acquire().await?;
+
+    if auth.user.is_none() {
+        let caller = match access_token(&headers, None) {
+            Some(token) => get_active_access_token(&token, &mut conn).await?,
+            None => None,
+        };
+
+        if caller.is_none() {
+            return Err(OAuthError::new("unauthorized", "introspection requires authorization"));
+        }
+    }
+
+    let Some(token) = get_active_access_token(&request.token, &mut conn).await? else {
+        return Ok(Json(json!({ "active": false })).into_response());
+    };
+
+    Ok(Json(json!({
+        "active": true,
+        "me": token.me,
+        "client_id": token.client_id,
+        "scope": token.scope,
+        "iat": token.inserted_at.and_utc().timestamp(),
+        "exp": token.expires_at.and_utc().timestamp(),
+    }))
+    .into_response())
+}
+
+/// Token revocation (RFC 7009); answers with 200 regardless of whether the token was valid
+pub async fn revoke(State(state): State<AppState>, Form(request): Form<TokenParam>) -> Result<Response, OAuthError> {
+    let mut conn = state.pool.acquire().await?;
+    revoke_access_token(&request.token, &mut conn).await?;
+
+    Ok(StatusCode::OK.into_response())
+}
@@ -1,6 +1,6 @@
 use std::vec::Vec;
 
-use sqlx::{PgConnection, query_as, query_scalar};
+use sqlx::{Connection, PgConnection, query, query_as, query_scalar};
 use validator::Validate;
 
 use crate::models::{Like, NewLike};
@@ -62,13 +62,15 @@ pub async fn create_like(
     } else {
         let like = query_as!(
             Like,
-            "INSERT INTO likes (in_reply_to, posse, show_in_index, inserted_at, updated_at) VALUES ($1, $2, $3, $4, \
-             $5) RETURNING *",
+            "INSERT INTO likes (author_id, in_reply_to, posse, show_in_index, inserted_at, updated_at, posse_targets) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+            data.author_id,
             data.in_reply_to,
             data.posse,
             data.show_in_index,
             now,
-            now
+            now,
+            &data.posse_targets
         )
         .fetch_one(conn)
         .await?;
@@ -88,12 +90,13 @@ pub async fn update_like(
         let now = chrono::Utc::now().naive_utc();
         let like = query_as!(
             Like,
-            "UPDATE likes SET in_reply_to = $1, posse = $2, show_in_index = $3, updated_at = $4 WHERE id = $5 \
-             RETURNING *",
+            "UPDATE likes SET in_reply_to = $1, posse = $2, show_in_index = $3, updated_at = $4, posse_targets = $5 \
+             WHERE id = $6 RETURNING *",
             data.in_reply_to,
             data.posse,
             data.show_in_index,
             now,
+            &data.posse_targets,
             like_id
         )
         .fetch_one(conn)
@@ -104,7 +107,16 @@ pub async fn update_like(
 }
 
 pub async fn delete_like(like_id: i32, conn: &mut PgConnection) -> Result<Like, sqlx::Error> {
-    query_as!(Like, "DELETE FROM likes WHERE id = $1 RETURNING *", like_id)
-        .fetch_one(&mut *conn)
-        .await
+    let mut tx = conn.begin().await?;
+
+    query!("DELETE FROM mentions WHERE like_id = $1", like_id)
+        .execute(&mut *tx)
+        .await?;
+    let like = query_as!(Like, "DELETE FROM likes WHERE id = $1 RETURNING *", like_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(like)
 }
@@ -1,12 +1,16 @@
 use askama::Template;
-use axum::extract::{Form, Path, State};
+use axum::extract::{Path, State};
 use axum::http::StatusCode;
 use axum::response::{Html, IntoResponse, Redirect, Response};
+use axum_extra::extract::Form;
 
 use super::actions;
 use crate::errors::AppError;
 use crate::models::{Like, NewLike};
+use crate::posse::syndicate::syndicate_like;
+use crate::posse::targets_added;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
 use crate::webmentions::send::send_mentions;
 use crate::{AppState, AuthSession};
 
@@ -44,6 +48,7 @@ pub async fn edit(State(state): State<AppState>, Path(id): Path<i32>) -> Result<
             show_in_index: like.show_in_index,
             inserted_at: None,
             updated_at: None,
+            posse_targets: like.posse_targets.clone(),
         },
         like,
         error: None,
@@ -69,14 +74,15 @@ pub async fn update(
     form.author_id = Some(user.id);
 
     match actions::update_like(like.id, &form, &mut conn).await {
-        Ok(like) => {
-            state.like_cache.insert(like.id, like.clone()).await;
-            let uri = like_uri(&like);
-
-            tokio::task::spawn_blocking(move || {
-                let uri = like_uri(&like);
-                let _ = send_mentions(&uri);
-            });
+        Ok(updated_like) => {
+            state.like_cache.insert(updated_like.id, updated_like.clone()).await;
+            let uri = like_uri(&updated_like);
+
+            if targets_added(updated_like.posse, &updated_like.posse_targets, like.posse, &like.posse_targets) {
+                tokio::task::spawn(syndicate_like(updated_like, state.pool.clone()));
+            }
+
+            tokio::task::spawn(send_mentions(uri.clone(), state.pool.clone()));
 
             Ok(Redirect::to(&uri).into_response())
         }
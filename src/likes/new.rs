@@ -1,12 +1,15 @@
 use askama::Template;
-use axum::extract::{Form, State};
+use axum::extract::State;
 use axum::http::StatusCode;
 use axum::response::{Html, IntoResponse, Redirect, Response};
+use axum_extra::extract::Form;
 
 use super::actions;
 use crate::errors::AppError;
 use crate::models::NewLike;
+use crate::posse::syndicate::syndicate_like;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
 use crate::webmentions::send::send_mentions;
 use crate::{AppState, AuthSession};
 
@@ -59,10 +62,11 @@ pub async fn create(
     if let Ok(like) = res {
         let uri = like_uri(&like);
 
-        tokio::task::spawn_blocking(move || {
-            let uri = like_uri(&like);
-            let _ = send_mentions(&uri);
-        });
+        if like.posse {
+            tokio::task::spawn(syndicate_like(like, state.pool.clone()));
+        }
+
+        tokio::task::spawn(send_mentions(uri.clone(), state.pool.clone()));
 
         Ok(Redirect::to(&uri).into_response())
     } else {
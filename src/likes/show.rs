@@ -5,8 +5,11 @@ use sqlx::PgConnection;
 
 use super::actions;
 use crate::errors::AppError;
-use crate::models::Like;
+use crate::models::{GroupedMentions, Like, Syndication};
+use crate::posse::actions::list_syndications;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
+use crate::webmentions::actions::{ObjectType, list_mentions};
 use crate::{AppState, AuthSession, utils as filters};
 
 #[derive(Template)]
@@ -22,6 +25,8 @@ pub struct Show<'a> {
     like: Like,
     index: bool,
     atom: bool,
+    mentions: Option<GroupedMentions>,
+    syndications: Vec<Syndication>,
 }
 
 pub async fn show(
@@ -32,6 +37,9 @@ pub async fn show(
     let mut conn = state.pool.acquire().await?;
     let like = get_like(id, &state, &mut conn).await?;
 
+    let mentions = list_mentions(ObjectType::Like, like.id, &mut conn).await?;
+    let syndications = list_syndications(ObjectType::Like, like.id, &mut conn).await?;
+
     let html = Show {
         lang: "en",
         title: Some(format!("♥  {}", like.in_reply_to)),
@@ -42,6 +50,8 @@ pub async fn show(
         like,
         index: false,
         atom: false,
+        mentions: Some(mentions.into()),
+        syndications,
     }
     .render()?;
 
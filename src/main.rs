@@ -1,20 +1,30 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::Duration;
 
-use axum::middleware::map_response_with_state;
+use axum::middleware::{from_fn, from_fn_with_state, map_response_with_state};
 use axum::{Router, ServiceExt};
 use axum_login::AuthManagerLayerBuilder;
-use axum_login::tower_sessions::{Expiry, MemoryStore, SessionManagerLayer};
+use axum_login::tower_sessions::{Expiry, SessionManagerLayer};
 use moka::future::Cache;
 use sqlx::PgPool;
 use sqlx::postgres::PgPoolOptions;
 use tower::Layer;
 use tower_http::services::{ServeDir, ServeFile};
+use webauthn_rs::prelude::Webauthn;
 
+use crate::utils::rate_limit::RateLimiter;
+
+mod account;
 mod articles;
+mod audit;
 mod deafies;
 mod errors;
+mod indieauth;
 mod likes;
+mod media;
+mod mentions;
+mod micropub;
 mod middleware;
 mod models;
 mod notes;
@@ -36,6 +46,12 @@ pub struct AppState {
     pub picture_cache: Cache<i32, models::Picture>,
     pub like_cache: Cache<i32, models::Like>,
     pub deafie_cache: Cache<String, models::Deafie>,
+
+    pub webmention_ip_limiter: RateLimiter,
+    pub webmention_domain_limiter: RateLimiter,
+    pub login_ip_limiter: RateLimiter,
+
+    pub webauthn: Arc<Webauthn>,
 }
 type AppRouter = Router<AppState>;
 pub type AuthSession = axum_login::AuthSession<store::Store>;
@@ -53,12 +69,6 @@ async fn main() {
 
     tracing_subscriber::fmt::init();
 
-    let session_store = MemoryStore::default();
-
-    let session_layer = SessionManagerLayer::new(session_store)
-        .with_secure(SECURE)
-        .with_expiry(Expiry::OnInactivity(axum_login::tower_sessions::cookie::time::Duration::days(14)));
-
     let database_url =
         std::env::var("DATABASE_URL").unwrap_or_else(|_| "postgres://localhost/termitool_dev".to_owned());
 
@@ -78,16 +88,30 @@ async fn main() {
         .await
         .expect("Failed to connect to database");
 
-    let user_store = store::Store::new(pool.clone());
+    let webauthn = Arc::new(utils::webauthn::build_webauthn().expect("Failed to set up WebAuthn"));
+    let user_store = store::Store::new(pool.clone(), webauthn.clone());
 
     sqlx::migrate!("./migrations")
         .run(&pool)
         .await
         .expect("Failed to run migrations");
 
-    posse::mastodon::verify_or_register()
+    let session_store = session::store::PgSessionStore::new(pool.clone());
+    tokio::spawn(session_store.clone().run_cleanup());
+
+    let session_layer = SessionManagerLayer::new(session_store)
+        .with_secure(SECURE)
+        .with_expiry(Expiry::OnInactivity(axum_login::tower_sessions::cookie::time::Duration::days(14)));
+
+    posse::configure()
         .await
-        .expect("Error verifying Mastodon credentials");
+        .expect("Error configuring the syndication targets");
+
+    tokio::spawn(webmentions::worker::run(pool.clone()));
+    tokio::spawn(webmentions::outbox::run(pool.clone()));
+    if let Some(mastodon) = posse::mastodon_client() {
+        tokio::spawn(posse::backfeed::run(mastodon.clone(), pool.clone()));
+    }
 
     let static_path = utils::static_path();
     let serve_dir = ServeDir::new(static_path);
@@ -99,6 +123,10 @@ async fn main() {
         picture_cache: Cache::new(1000),
         like_cache: Cache::new(1000),
         deafie_cache: Cache::new(1000),
+        webmention_ip_limiter: RateLimiter::new(30, Duration::from_secs(60 * 60)),
+        webmention_domain_limiter: RateLimiter::new(60, Duration::from_secs(60 * 60)),
+        login_ip_limiter: RateLimiter::new(20, Duration::from_secs(15 * 60)),
+        webauthn,
     };
 
     let mut app: AppRouter = Router::new();
@@ -106,10 +134,15 @@ async fn main() {
     app = articles::configure(app);
     app = notes::configure(app);
     app = likes::configure(app);
+    app = media::configure(app);
     app = pictures::configure(app);
     app = deafies::configure(app);
     app = session::configure(app);
+    app = account::configure(app);
     app = webmentions::configure(app);
+    app = mentions::configure(app);
+    app = micropub::configure(app);
+    app = indieauth::configure(app);
 
     let addr = SocketAddr::from(([127, 0, 0, 1], 8000));
 
@@ -127,12 +160,18 @@ async fn main() {
 
     let app = app
         .merge(static_router)
+        .layer(from_fn_with_state(state.clone(), middleware::gone_middleware))
+        .layer(from_fn_with_state(state.clone(), middleware::audit_middleware))
+        .layer(from_fn(middleware::csrf_middleware))
+        .layer(from_fn(middleware::session_info_middleware))
         .with_state(state)
         .layer(AuthManagerLayerBuilder::new(user_store, session_layer).build())
         .layer(axum::middleware::map_response(middleware::webmention_middleware));
 
     let middleware = tower::util::MapRequestLayer::new(middleware::rewrite_request_uri);
-    let app = middleware.layer(app).into_make_service();
+    let app = middleware
+        .layer(app)
+        .into_make_service_with_connect_info::<SocketAddr>();
 
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
     axum::serve(listener, app).await.unwrap();
@@ -0,0 +1,18 @@
+use axum::Router;
+use axum::middleware::map_response_with_state;
+use axum::routing::get;
+use chrono::Duration;
+
+use crate::AppRouter;
+use crate::middleware::caching_middleware;
+
+pub mod actions;
+pub mod show;
+
+pub fn configure(app: AppRouter) -> AppRouter {
+    let caching_router: AppRouter = Router::new()
+        .route("/media/{id}/{file_name}", get(show::show))
+        .layer(map_response_with_state(Duration::days(30), caching_middleware));
+
+    app.merge(caching_router)
+}
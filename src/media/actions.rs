@@ -0,0 +1,55 @@
+use sqlx::{Connection, PgConnection, query_as};
+use tokio::fs::File;
+use tokio::io::AsyncSeekExt;
+
+use crate::models::MediaFile;
+use crate::utils::media_base_path;
+
+pub async fn get_media_file(media_file_id: i32, conn: &mut PgConnection) -> Result<MediaFile, sqlx::Error> {
+    query_as!(MediaFile, "SELECT * FROM media_files WHERE id = $1", media_file_id)
+        .fetch_one(conn)
+        .await
+}
+
+/// Creates the database entry and stores the file in the same layout as pictures:
+/// `{media_base_path}/{id}/original/{file_name}`
+pub async fn create_media_file(
+    author_id: i32,
+    file_name: &str,
+    content_type: &str,
+    mut file: File,
+    conn: &mut PgConnection,
+) -> Result<MediaFile, Box<dyn std::error::Error + Send + Sync>> {
+    let now = chrono::Utc::now().naive_utc();
+    let file_size = i32::try_from(file.metadata().await?.len())?;
+    let mut tx = conn.begin().await?;
+
+    let media_file = query_as!(
+        MediaFile,
+        "INSERT INTO media_files (author_id, file_name, content_type, file_size, inserted_at, updated_at) \
+         VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+        author_id,
+        file_name,
+        content_type,
+        file_size,
+        now,
+        now
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    for version in ["original", "large", "thumbnail"] {
+        let path = format!("{}/{}/{}", media_base_path(), media_file.id, version);
+        std::fs::create_dir_all(path)?;
+    }
+
+    let path = format!("{}/{}/original/{}", media_base_path(), media_file.id, media_file.file_name);
+
+    let mut target_file = File::create(path).await?;
+    file.rewind().await?;
+    tokio::io::copy(&mut file, &mut target_file).await?;
+
+    tx.commit().await?;
+
+    Ok(media_file)
+}
@@ -0,0 +1,46 @@
+use std::path::Path;
+
+use axum::body::Body;
+use axum::extract::{Path as EPath, Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use tokio_util::io::ReaderStream;
+
+use super::actions;
+use crate::AppState;
+use crate::errors::AppError;
+use crate::pictures::{ImageTypes, TypeParams};
+use crate::utils::media_base_path;
+
+pub async fn show(
+    State(state): State<AppState>,
+    EPath((id, file_name)): EPath<(i32, String)>,
+    pic_type: Query<TypeParams>,
+) -> Result<Response, AppError> {
+    let mut conn = state.pool.acquire().await?;
+    let media_file = actions::get_media_file(id, &mut conn).await?;
+
+    if media_file.file_name != file_name {
+        return Err(AppError::NotFound("File not found".to_owned()));
+    }
+
+    let path_part = match pic_type.pic_type {
+        Some(ImageTypes::Large) if media_file.is_image() => "large",
+        Some(ImageTypes::Thumbnail) if media_file.is_image() => "thumbnail",
+        _ => "original",
+    };
+
+    let mut path = format!("{}/{}/{}/{}", media_base_path(), media_file.id, path_part, media_file.file_name);
+
+    if !Path::new(&path).exists() {
+        path = format!("{}/{}/original/{}", media_base_path(), media_file.id, media_file.file_name);
+    }
+
+    let file = match tokio::fs::File::open(path).await {
+        Ok(file) => file,
+        Err(err) => return Err(AppError::NotFound(format!("File not found: {}", err))),
+    };
+    let stream = ReaderStream::new(file);
+
+    Ok(([(header::CONTENT_TYPE, media_file.content_type)], Body::from_stream(stream)).into_response())
+}
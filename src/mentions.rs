@@ -0,0 +1,31 @@
+use axum::Router;
+use axum::middleware::from_fn;
+use axum::routing::{get, post};
+use axum_login::login_required;
+
+use crate::AppRouter;
+use crate::middleware::two_factor_middleware;
+use crate::store::Store;
+
+pub mod actions;
+
+pub mod domains;
+pub mod index;
+pub mod moderate;
+
+static PER_PAGE: i64 = 50;
+
+pub fn configure(app: AppRouter) -> AppRouter {
+    let authed_router: AppRouter = Router::new()
+        .route("/admin/mentions", get(index::index))
+        .route("/admin/mentions/{id}/approve", post(moderate::approve))
+        .route("/admin/mentions/{id}/reject", post(moderate::reject))
+        .route("/admin/mentions/{id}/delete", post(moderate::delete))
+        .route("/admin/mentions/domains", get(domains::index))
+        .route("/admin/mentions/domains", post(domains::create))
+        .route("/admin/mentions/domains/{id}/delete", post(domains::delete))
+        .route_layer(from_fn(two_factor_middleware))
+        .route_layer(login_required!(Store, login_url = "/login"));
+
+    app.merge(authed_router)
+}
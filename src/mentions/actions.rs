@@ -0,0 +1,135 @@
+use sqlx::{PgConnection, query, query_as, query_scalar};
+use validator::Validate;
+
+use crate::models::{Mention, MentionDomain, NewMentionDomain};
+
+pub async fn list_mentions(
+    status: &str,
+    object_type: Option<&str>,
+    limit: i64,
+    offset: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<Mention>, sqlx::Error> {
+    query_as!(
+        Mention,
+        r#"
+        SELECT * FROM mentions
+        WHERE status = $1
+          AND ($2::text IS NULL
+               OR ($2 = 'articles' AND article_id IS NOT NULL)
+               OR ($2 = 'notes' AND note_id IS NOT NULL)
+               OR ($2 = 'pictures' AND picture_id IS NOT NULL)
+               OR ($2 = 'likes' AND like_id IS NOT NULL)
+               OR ($2 = 'deafies' AND deafie_id IS NOT NULL))
+        ORDER BY inserted_at DESC, id DESC
+        LIMIT $3 OFFSET $4
+        "#,
+        status,
+        object_type,
+        limit,
+        offset
+    )
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn count_mentions(
+    status: &str,
+    object_type: Option<&str>,
+    conn: &mut PgConnection,
+) -> Result<i64, sqlx::Error> {
+    query_scalar(
+        r#"
+        SELECT COUNT(*) FROM mentions
+        WHERE status = $1
+          AND ($2::text IS NULL
+               OR ($2 = 'articles' AND article_id IS NOT NULL)
+               OR ($2 = 'notes' AND note_id IS NOT NULL)
+               OR ($2 = 'pictures' AND picture_id IS NOT NULL)
+               OR ($2 = 'likes' AND like_id IS NOT NULL)
+               OR ($2 = 'deafies' AND deafie_id IS NOT NULL))
+        "#,
+    )
+    .bind(status)
+    .bind(object_type)
+    .fetch_one(conn)
+    .await
+}
+
+pub async fn set_mention_status(
+    mention_id: i32,
+    status: &str,
+    conn: &mut PgConnection,
+) -> Result<Mention, sqlx::Error> {
+    let now = chrono::Utc::now().naive_utc();
+
+    query_as!(
+        Mention,
+        "UPDATE mentions SET status = $1, updated_at = $2 WHERE id = $3 RETURNING *",
+        status,
+        now,
+        mention_id
+    )
+    .fetch_one(conn)
+    .await
+}
+
+pub async fn delete_mention(mention_id: i32, conn: &mut PgConnection) -> Result<Mention, sqlx::Error> {
+    query_as!(Mention, "DELETE FROM mentions WHERE id = $1 RETURNING *", mention_id)
+        .fetch_one(conn)
+        .await
+}
+
+pub async fn list_mention_domains(conn: &mut PgConnection) -> Result<Vec<MentionDomain>, sqlx::Error> {
+    query_as!(MentionDomain, "SELECT * FROM mention_domains ORDER BY rule, domain")
+        .fetch_all(conn)
+        .await
+}
+
+pub async fn create_mention_domain(
+    data: &NewMentionDomain,
+    conn: &mut PgConnection,
+) -> Result<MentionDomain, Box<dyn std::error::Error + Send + Sync>> {
+    let mut data = data.clone();
+    data.domain = data.domain.trim().trim_start_matches('.').to_lowercase();
+
+    data.validate()?;
+
+    let now = chrono::Utc::now().naive_utc();
+    let domain = query_as!(
+        MentionDomain,
+        r#"
+        INSERT INTO mention_domains (domain, rule, inserted_at, updated_at)
+        VALUES ($1, $2, $3, $3)
+        ON CONFLICT (domain) DO UPDATE SET rule = EXCLUDED.rule, updated_at = EXCLUDED.updated_at
+        RETURNING *
+        "#,
+        data.domain,
+        data.rule,
+        now
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(domain)
+}
+
+pub async fn delete_mention_domain(domain_id: i32, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    query!("DELETE FROM mention_domains WHERE id = $1", domain_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Returns the rule (`approve` or `block`) for a host; rules for a domain apply to its subdomains
+/// as well, the most specific one wins
+pub async fn domain_rule(host: &str, conn: &mut PgConnection) -> Result<Option<String>, sqlx::Error> {
+    query_scalar!(
+        "SELECT rule FROM mention_domains WHERE $1 = domain OR $1 LIKE '%.' || domain ORDER BY length(domain) DESC \
+         LIMIT 1",
+        host
+    )
+    .fetch_optional(conn)
+    .await
+}
@@ -0,0 +1,83 @@
+use askama::Template;
+use axum::extract::{Form, Path, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Redirect, Response};
+
+use super::actions;
+use crate::AppState;
+use crate::errors::AppError;
+use crate::models::{MentionDomain, NewMentionDomain};
+use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
+
+#[derive(Template)]
+#[template(path = "mentions/domains.html.j2")]
+pub struct Index<'a> {
+    lang: &'a str,
+    title: Option<&'a str>,
+    page_type: Option<&'a str>,
+    page_image: Option<&'a str>,
+    body_id: Option<&'a str>,
+    logged_in: bool,
+
+    domains: Vec<MentionDomain>,
+    form_data: NewMentionDomain,
+    error: Option<String>,
+}
+
+pub async fn index(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let mut conn = state.pool.acquire().await?;
+    let domains = actions::list_mention_domains(&mut conn).await?;
+
+    let html = Index {
+        lang: "en",
+        title: Some("Mention domains"),
+        page_type: None,
+        page_image: None,
+        body_id: None,
+        logged_in: true,
+        domains,
+        form_data: NewMentionDomain {
+            rule: "approve".to_owned(),
+            ..Default::default()
+        },
+        error: None,
+    }
+    .render()?;
+
+    Ok(Html(html))
+}
+
+pub async fn create(State(state): State<AppState>, Form(form): Form<NewMentionDomain>) -> Result<Response, AppError> {
+    let mut conn = state.pool.acquire().await?;
+
+    match actions::create_mention_domain(&form, &mut conn).await {
+        Ok(_) => Ok(Redirect::to(&mention_domains_uri()).into_response()),
+
+        Err(error) => {
+            let domains = actions::list_mention_domains(&mut conn).await?;
+
+            let html = Index {
+                lang: "en",
+                title: Some("Mention domains"),
+                page_type: None,
+                page_image: None,
+                body_id: None,
+                logged_in: true,
+                domains,
+                form_data: form,
+                error: Some(error.to_string()),
+            }
+            .render()?;
+
+            Ok((StatusCode::UNPROCESSABLE_ENTITY, Html(html)).into_response())
+        }
+    }
+}
+
+pub async fn delete(State(state): State<AppState>, Path(id): Path<i32>) -> Result<impl IntoResponse, AppError> {
+    let mut conn = state.pool.acquire().await?;
+    actions::delete_mention_domain(id, &mut conn).await?;
+
+    Ok(Redirect::to(&mention_domains_uri()))
+}
@@ -0,0 +1,78 @@
+use std::str::FromStr;
+
+use askama::Template;
+use axum::extract::{Query, State};
+use axum::response::{Html, IntoResponse};
+use serde::Deserialize;
+
+use super::{PER_PAGE, actions};
+use crate::errors::AppError;
+use crate::models::Mention;
+use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
+use crate::utils::paging::{PageParams, Paging, get_page, get_paging};
+use crate::webmentions::actions::ObjectType;
+use crate::{AppState, utils as filters};
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct MentionFilter {
+    pub status: Option<String>,
+    pub object_type: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "mentions/index.html.j2")]
+pub struct Index<'a> {
+    lang: &'a str,
+    title: Option<&'a str>,
+    page_type: Option<&'a str>,
+    page_image: Option<&'a str>,
+    body_id: Option<&'a str>,
+    logged_in: bool,
+
+    mentions: Vec<Mention>,
+    paging: Paging,
+    status: String,
+    object_type: String,
+}
+
+pub async fn index(
+    State(state): State<AppState>,
+    page: Query<PageParams>,
+    Query(filter): Query<MentionFilter>,
+) -> Result<impl IntoResponse, AppError> {
+    let p = get_page(&page);
+
+    let status = match filter.status.as_deref() {
+        Some("approved") => "approved",
+        Some("rejected") => "rejected",
+        Some("deleted") => "deleted",
+        _ => "pending",
+    };
+
+    let object_type = filter
+        .object_type
+        .filter(|object_type| ObjectType::from_str(object_type).is_ok());
+
+    let mut conn = state.pool.acquire().await?;
+    let mentions = actions::list_mentions(status, object_type.as_deref(), PER_PAGE, p * PER_PAGE, &mut conn).await?;
+    let count = actions::count_mentions(status, object_type.as_deref(), &mut conn).await?;
+
+    let paging = get_paging(count, p, PER_PAGE);
+
+    let html = Index {
+        lang: "en",
+        title: Some("Mentions"),
+        page_type: None,
+        page_image: None,
+        body_id: None,
+        logged_in: true,
+        mentions,
+        paging,
+        status: status.to_owned(),
+        object_type: object_type.unwrap_or_default(),
+    }
+    .render()?;
+
+    Ok(Html(html))
+}
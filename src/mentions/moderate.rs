@@ -0,0 +1,28 @@
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Redirect};
+
+use super::actions;
+use crate::AppState;
+use crate::errors::AppError;
+use crate::uri_helpers::*;
+
+pub async fn approve(State(state): State<AppState>, Path(id): Path<i32>) -> Result<impl IntoResponse, AppError> {
+    let mut conn = state.pool.acquire().await?;
+    actions::set_mention_status(id, "approved", &mut conn).await?;
+
+    Ok(Redirect::to(&admin_mentions_uri(None)))
+}
+
+pub async fn reject(State(state): State<AppState>, Path(id): Path<i32>) -> Result<impl IntoResponse, AppError> {
+    let mut conn = state.pool.acquire().await?;
+    actions::set_mention_status(id, "rejected", &mut conn).await?;
+
+    Ok(Redirect::to(&admin_mentions_uri(None)))
+}
+
+pub async fn delete(State(state): State<AppState>, Path(id): Path<i32>) -> Result<impl IntoResponse, AppError> {
+    let mut conn = state.pool.acquire().await?;
+    let mention = actions::delete_mention(id, &mut conn).await?;
+
+    Ok(Redirect::to(&admin_mentions_uri(Some(&mention.status))))
+}
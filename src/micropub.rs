@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+
+use axum::Json;
+use axum::extract::DefaultBodyLimit;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use serde_json::{Value, json};
+
+use crate::{AppRouter, MAX_UPLOAD_SIZE};
+
+pub mod actions;
+pub mod auth;
+pub mod create;
+pub mod delete;
+pub mod endpoint;
+pub mod media;
+pub mod properties;
+pub mod query;
+pub mod update;
+
+/// microformats2 properties, property name to list of values
+pub type Properties = BTreeMap<String, Vec<Value>>;
+
+pub fn configure(app: AppRouter) -> AppRouter {
+    app.route(
+        "/micropub",
+        get(query::query)
+            .post(endpoint::post)
+            .layer(DefaultBodyLimit::max(MAX_UPLOAD_SIZE)),
+    )
+    .route("/micropub/media", post(media::upload).layer(DefaultBodyLimit::max(MAX_UPLOAD_SIZE)))
+}
+
+pub enum MicropubError {
+    InvalidRequest(String),
+    Unauthorized,
+    Forbidden(String),
+    InsufficientScope(String),
+    InternalError(String),
+}
+
+impl IntoResponse for MicropubError {
+    fn into_response(self) -> Response {
+        let (code, error, description) = match &self {
+            MicropubError::InvalidRequest(s) => (StatusCode::BAD_REQUEST, "invalid_request", s.clone()),
+            MicropubError::Unauthorized => {
+                (StatusCode::UNAUTHORIZED, "unauthorized", "no access token given".to_owned())
+            }
+            MicropubError::Forbidden(s) => (StatusCode::FORBIDDEN, "forbidden", s.clone()),
+            MicropubError::InsufficientScope(scope) => {
+                (StatusCode::FORBIDDEN, "insufficient_scope", format!("the access token lacks the scope {}", scope))
+            }
+            MicropubError::InternalError(s) => (StatusCode::INTERNAL_SERVER_ERROR, "server_error", s.clone()),
+        };
+
+        let mut body = json!({ "error": error, "error_description": description });
+        if let MicropubError::InsufficientScope(scope) = self {
+            body["scope"] = Value::String(scope);
+        }
+
+        (code, Json(body)).into_response()
+    }
+}
+
+impl From<sqlx::Error> for MicropubError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::RowNotFound => MicropubError::InvalidRequest("post could not be found".to_owned()),
+            e => MicropubError::InternalError(format!("db error: {}", e)),
+        }
+    }
+}
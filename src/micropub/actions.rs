@@ -0,0 +1,254 @@
+use serde_json::Value;
+use sqlx::PgConnection;
+use url::Url;
+
+use super::properties::{all_texts, first_alt, first_text, set_text, title_from_text};
+use super::{MicropubError, Properties};
+use crate::models::{Article, Like, NewArticle, NewLike, NewNote, NewPicture, Note, Picture};
+use crate::session::actions::get_author_by_id;
+use crate::uri_helpers::*;
+use crate::webmentions::actions::ObjectType;
+use crate::{articles, likes, notes, pictures, posse};
+
+/// the posts which can be managed via micropub
+pub enum Post {
+    Article(Article),
+    Note(Note),
+    Picture(Picture),
+    Like(Like),
+}
+
+impl Post {
+    pub fn uri(&self) -> String {
+        match self {
+            Post::Article(article) => article_uri(article),
+            Post::Note(note) => note_uri(note),
+            Post::Picture(picture) => picture_uri(picture),
+            Post::Like(like) => like_uri(like),
+        }
+    }
+
+    pub fn author_id(&self) -> i32 {
+        match self {
+            Post::Article(article) => article.author_id,
+            Post::Note(note) => note.author_id,
+            Post::Picture(picture) => picture.author_id,
+            Post::Like(like) => like.author_id,
+        }
+    }
+
+    pub fn object_type_and_id(&self) -> (ObjectType, i32) {
+        match self {
+            Post::Article(article) => (ObjectType::Article, article.id),
+            Post::Note(note) => (ObjectType::Note, note.id),
+            Post::Picture(picture) => (ObjectType::Picture, picture.id),
+            Post::Like(like) => (ObjectType::Like, like.id),
+        }
+    }
+}
+
+/// Looks up the post a URL of this site points to
+pub async fn find_post(url: &str, conn: &mut PgConnection) -> Result<Post, MicropubError> {
+    let root_url =
+        Url::parse(&root_uri()).map_err(|e| MicropubError::InternalError(format!("BASE_URI is invalid: {}", e)))?;
+    let url = Url::parse(url).map_err(|_| MicropubError::InvalidRequest("url invalid".to_owned()))?;
+
+    if url.host_str() != root_url.host_str() {
+        return Err(MicropubError::InvalidRequest("url does not belong to this site".to_owned()));
+    }
+
+    let path = url.path().strip_prefix(root_url.path()).unwrap_or_default();
+    let pieces = path.split('/').collect::<Vec<_>>();
+    let not_a_post = || MicropubError::InvalidRequest("url does not point to a post".to_owned());
+
+    let post = match pieces.as_slice() {
+        ["articles", _, _, _] => {
+            let article = articles::actions::get_article_by_slug(path.trim_start_matches("articles/"), false, conn)
+                .await?
+                .ok_or_else(not_a_post)?;
+            Post::Article(article)
+        }
+        ["notes", id] => Post::Note(notes::actions::get_note(id.parse().map_err(|_| not_a_post())?, conn).await?),
+        ["pictures", id] => {
+            Post::Picture(pictures::actions::get_picture(id.parse().map_err(|_| not_a_post())?, conn).await?)
+        }
+        ["likes", id] => Post::Like(likes::actions::get_like(id.parse().map_err(|_| not_a_post())?, conn).await?),
+        _ => return Err(not_a_post()),
+    };
+
+    Ok(post)
+}
+
+/// Looks up a post the author may change: one of their own or, for admins, any post
+pub async fn find_own_post(url: &str, author_id: i32, conn: &mut PgConnection) -> Result<Post, MicropubError> {
+    let post = find_post(url, conn).await?;
+
+    if post.author_id() != author_id && !get_author_by_id(author_id, conn).await?.is_admin {
+        return Err(MicropubError::Forbidden("the post belongs to another author".to_owned()));
+    }
+
+    Ok(post)
+}
+
+/// The syndication targets we offer: the UID and the name of each configured service
+pub fn syndication_targets() -> Vec<(String, String)> {
+    posse::syndicators()
+        .iter()
+        .map(|syndicator| (syndicator.uid(), syndicator.name().to_owned()))
+        .collect()
+}
+
+/// Returns POSSE and the services the post selected in `mp-syndicate-to`
+fn syndicate(properties: &Properties) -> Option<(bool, Vec<String>)> {
+    let targets = all_texts(properties, "mp-syndicate-to");
+    if targets.is_empty() {
+        return None;
+    }
+
+    let selected = posse::syndicators()
+        .iter()
+        .filter(|syndicator| targets.contains(&syndicator.uid()))
+        .map(|syndicator| syndicator.service().to_owned())
+        .collect::<Vec<_>>();
+
+    Some((!selected.is_empty(), selected))
+}
+
+/// Returns the post as microformats2 properties, as used for `q=source` and as the base for updates
+pub fn post_properties(post: &Post) -> Properties {
+    let mut properties = Properties::new();
+
+    let (inserted_at, lang) = match post {
+        Post::Article(article) => {
+            set_text(&mut properties, "name", Some(&article.title));
+            set_text(&mut properties, "content", Some(&article.body));
+            set_text(&mut properties, "summary", article.excerpt.as_deref());
+            set_text(&mut properties, "in-reply-to", article.in_reply_to.as_deref());
+            set_text(&mut properties, "post-status", Some(if article.published { "published" } else { "draft" }));
+            set_text(&mut properties, "visibility", Some(&article.posse_visibility));
+            (article.inserted_at, Some(&article.lang))
+        }
+
+        Post::Note(note) => {
+            set_text(&mut properties, "name", Some(&note.title));
+            set_text(&mut properties, "content", Some(&note.content));
+            let property = if note.note_type == "repost" {
+                "repost-of"
+            } else {
+                "in-reply-to"
+            };
+            set_text(&mut properties, property, note.in_reply_to.as_deref());
+            set_text(&mut properties, "visibility", Some(&note.posse_visibility));
+            (note.inserted_at, Some(&note.lang))
+        }
+
+        Post::Picture(picture) => {
+            let photo = serde_json::json!({
+                "value": picture_img_uri(picture, None),
+                "alt": picture.alt,
+            });
+
+            properties.insert("photo".to_owned(), vec![photo]);
+            set_text(&mut properties, "name", Some(&picture.title));
+            set_text(&mut properties, "content", Some(&picture.content));
+            set_text(&mut properties, "in-reply-to", picture.in_reply_to.as_deref());
+            set_text(&mut properties, "visibility", Some(&picture.posse_visibility));
+            (picture.inserted_at, Some(&picture.lang))
+        }
+
+        Post::Like(like) => {
+            set_text(&mut properties, "like-of", Some(&like.in_reply_to));
+            (like.inserted_at, None)
+        }
+    };
+
+    set_text(&mut properties, "lang", lang.map(|lang| lang.as_str()));
+    set_text(&mut properties, "url", Some(&post.uri()));
+    set_text(&mut properties, "published", Some(&inserted_at.format("%Y-%m-%dT%H:%M:%SZ").to_string()));
+
+    properties
+}
+
+pub fn note_values(properties: &Properties, base: NewNote) -> NewNote {
+    let content = first_text(properties, "content");
+    let repost_of = first_text(properties, "repost-of");
+    let in_reply_to = first_text(properties, "in-reply-to");
+
+    let note_type = if repost_of.is_some() {
+        "repost"
+    } else if in_reply_to.is_some() {
+        "reply"
+    } else {
+        "note"
+    };
+
+    let (posse, posse_targets) = syndicate(properties).unwrap_or((base.posse, base.posse_targets.clone()));
+
+    NewNote {
+        title: first_text(properties, "name")
+            .or_else(|| content.as_deref().map(title_from_text))
+            .unwrap_or_default(),
+        note_type: note_type.to_owned(),
+        in_reply_to: repost_of.or(in_reply_to),
+        lang: first_text(properties, "lang").unwrap_or(base.lang),
+        posse,
+        posse_targets,
+        posse_visibility: first_text(properties, "visibility").unwrap_or(base.posse_visibility),
+        content,
+        ..base
+    }
+}
+
+pub fn article_values(properties: &Properties, base: NewArticle) -> NewArticle {
+    let (posse, posse_targets) = syndicate(properties).unwrap_or((base.posse, base.posse_targets.clone()));
+
+    NewArticle {
+        title: first_text(properties, "name").unwrap_or_default(),
+        body: first_text(properties, "content").unwrap_or_default(),
+        excerpt: first_text(properties, "summary"),
+        in_reply_to: first_text(properties, "in-reply-to"),
+        published: first_text(properties, "post-status").is_none_or(|status| status != "draft"),
+        lang: first_text(properties, "lang").unwrap_or(base.lang),
+        posse,
+        posse_targets,
+        posse_visibility: first_text(properties, "visibility").unwrap_or(base.posse_visibility),
+        ..base
+    }
+}
+
+pub fn picture_values(properties: &Properties, base: NewPicture) -> NewPicture {
+    let content = first_text(properties, "content");
+    let (posse, posse_targets) = syndicate(properties).unwrap_or((base.posse, base.posse_targets.clone()));
+
+    NewPicture {
+        title: first_text(properties, "name")
+            .or_else(|| content.as_deref().map(title_from_text))
+            .unwrap_or_default(),
+        alt: first_alt(properties, "photo").or(base.alt),
+        in_reply_to: first_text(properties, "in-reply-to"),
+        lang: first_text(properties, "lang").unwrap_or(base.lang),
+        posse,
+        posse_targets,
+        posse_visibility: first_text(properties, "visibility").unwrap_or(base.posse_visibility),
+        content,
+        ..base
+    }
+}
+
+pub fn like_values(properties: &Properties, base: NewLike) -> NewLike {
+    let (posse, posse_targets) = syndicate(properties).unwrap_or((base.posse, base.posse_targets.clone()));
+
+    NewLike {
+        in_reply_to: first_text(properties, "like-of").unwrap_or_default(),
+        posse,
+        posse_targets,
+        ..base
+    }
+}
+
+/// Returns `true` if one of the values of `name` is a URL or an object with a URL
+pub fn has_value(properties: &Properties, name: &str) -> bool {
+    properties
+        .get(name)
+        .is_some_and(|values| values.iter().any(|value| !matches!(value, Value::Null)))
+}
@@ -0,0 +1,99 @@
+use std::env;
+
+use axum::http::{HeaderMap, header};
+
+use sqlx::PgConnection;
+
+use super::MicropubError;
+use crate::AuthSession;
+use crate::indieauth::actions::use_access_token;
+use crate::models::AccessToken;
+use crate::utils::tokens::constant_time_eq;
+use crate::utils::two_factor_required;
+
+/// Returns the access token of a request, given either as bearer token or as `access_token` parameter
+pub fn access_token(headers: &HeaderMap, param: Option<&str>) -> Option<String> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or(param)
+        .map(|token| token.trim().to_owned())
+        .filter(|token| !token.is_empty())
+}
+
+/// The author a micropub request acts for and, for access tokens issued by our IndieAuth server, the scopes
+/// it may act in
+pub struct Authorization {
+    pub author_id: i32,
+    access_token: Option<AccessToken>,
+}
+
+impl Authorization {
+    /// Returns the author ID if the request may act in `scope`
+    pub fn require(&self, scope: Option<&str>) -> Result<i32, MicropubError> {
+        match (scope, &self.access_token) {
+            (Some(scope), Some(access_token)) if !access_token.has_scope(scope) => {
+                Err(MicropubError::InsufficientScope(scope.to_owned()))
+            }
+            _ => Ok(self.author_id),
+        }
+    }
+}
+
+/// Returns the author a micropub request acts for: the logged in author, the author an access token issued
+/// by our IndieAuth server belongs to or, for scripts, the author `MICROPUB_AUTHOR_ID` when the request
+/// carries the `MICROPUB_TOKEN`.
+pub async fn authenticate(
+    auth: &AuthSession,
+    token: Option<&str>,
+    conn: &mut PgConnection,
+) -> Result<Authorization, MicropubError> {
+    if let Some(user) = &auth.user {
+        if two_factor_required() && !user.has_two_factor() {
+            return Err(MicropubError::Forbidden("two-factor authentication has to be enabled".to_owned()));
+        }
+
+        return Ok(Authorization {
+            author_id: user.id,
+            access_token: None,
+        });
+    }
+
+    let Some(token) = token else {
+        return Err(MicropubError::Unauthorized);
+    };
+
+    let configured_token = env::var("MICROPUB_TOKEN").ok().filter(|t| !t.is_empty());
+    let author_id = env::var("MICROPUB_AUTHOR_ID")
+        .ok()
+        .and_then(|id| id.parse::<i32>().ok());
+
+    if let (Some(configured_token), Some(author_id)) = (configured_token, author_id) {
+        if constant_time_eq(token, &configured_token) {
+            return Ok(Authorization {
+                author_id,
+                access_token: None,
+            });
+        }
+    }
+
+    let Some(access_token) = use_access_token(token, conn).await? else {
+        return Err(MicropubError::Forbidden("the access token is invalid".to_owned()));
+    };
+
+    Ok(Authorization {
+        author_id: access_token.author_id,
+        access_token: Some(access_token),
+    })
+}
+
+/// Returns the id of the author a micropub request acts for if it may act in `scope`, see `authenticate()`
+pub async fn authorize(
+    auth: &AuthSession,
+    token: Option<&str>,
+    scope: Option<&str>,
+    conn: &mut PgConnection,
+) -> Result<i32, MicropubError> {
+    authenticate(auth, token, conn).await?.require(scope)
+}
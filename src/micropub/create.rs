@@ -0,0 +1,279 @@
+use std::io::Write;
+use std::time::Duration;
+
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use sqlx::PgConnection;
+use tempfile::NamedTempFile;
+use url::Url;
+
+use super::actions::{article_values, has_value, like_values, note_values, picture_values};
+use super::endpoint::{MicropubRequest, UploadedFile};
+use super::properties::{first_text, slugify};
+use super::{MicropubError, Properties};
+use crate::models::{MediaFile, NewArticle, NewLike, NewNote, NewPicture, generate_pictures};
+use crate::posse::syndicate::{syndicate_article, syndicate_like, syndicate_note, syndicate_picture};
+use crate::uri_helpers::*;
+use crate::utils::media_base_path;
+use crate::webmentions::fetch::{build_client, is_public_url};
+use crate::webmentions::send::send_mentions;
+use crate::{AppState, MAX_UPLOAD_SIZE, articles, likes, media, notes, pictures};
+
+const PHOTO_FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Creates a post; the post type is derived from the properties: a `like-of` makes a like, a
+/// `photo` a picture, a `name` an article and everything else a note.
+pub async fn create(mut request: MicropubRequest, author_id: i32, state: &AppState) -> Result<Response, MicropubError> {
+    if request.entry_type != "entry" {
+        return Err(MicropubError::InvalidRequest("only h-entry posts are supported".to_owned()));
+    }
+
+    let properties = &request.properties;
+    let mut conn = state.pool.acquire().await?;
+
+    let uri = if has_value(properties, "like-of") {
+        create_like(properties, author_id, state, &mut conn).await?
+    } else if !request.photos.is_empty() || has_value(properties, "photo") {
+        let photo = match request.photos.pop() {
+            Some(photo) => photo,
+            None => fetch_photo(&first_text(properties, "photo").unwrap_or_default(), &mut conn).await?,
+        };
+
+        create_picture(properties, photo, author_id, state, &mut conn).await?
+    } else if has_value(properties, "name") {
+        create_article(properties, author_id, state, &mut conn).await?
+    } else {
+        create_note(properties, author_id, state, &mut conn).await?
+    };
+
+    Ok((StatusCode::CREATED, [(header::LOCATION, uri)]).into_response())
+}
+
+fn invalid(error: Box<dyn std::error::Error + Send + Sync>) -> MicropubError {
+    MicropubError::InvalidRequest(error.to_string())
+}
+
+async fn create_note(
+    properties: &Properties,
+    author_id: i32,
+    state: &AppState,
+    conn: &mut PgConnection,
+) -> Result<String, MicropubError> {
+    let base = NewNote {
+        author_id: Some(author_id),
+        lang: "en".to_owned(),
+        show_in_index: true,
+        posse_visibility: "public".to_owned(),
+        ..Default::default()
+    };
+
+    let values = note_values(properties, base);
+    let note = notes::actions::create_note(&values, conn).await.map_err(invalid)?;
+    let uri = note_uri(&note);
+
+    if note.posse {
+        tokio::task::spawn(syndicate_note(note.clone(), state.pool.clone()));
+    }
+
+    tokio::task::spawn(send_mentions(uri.clone(), state.pool.clone()));
+
+    Ok(uri)
+}
+
+async fn create_article(
+    properties: &Properties,
+    author_id: i32,
+    state: &AppState,
+    conn: &mut PgConnection,
+) -> Result<String, MicropubError> {
+    let slug = first_text(properties, "mp-slug")
+        .or_else(|| first_text(properties, "name"))
+        .map(|slug| slugify(&slug))
+        .unwrap_or_default();
+
+    let base = NewArticle {
+        author_id: Some(author_id),
+        slug,
+        lang: "en".to_owned(),
+        posse_visibility: "public".to_owned(),
+        ..Default::default()
+    };
+
+    let values = article_values(properties, base);
+    let article = articles::actions::create_article(&values, conn)
+        .await
+        .map_err(invalid)?;
+    let uri = article_uri(&article);
+
+    if article.published {
+        if article.posse {
+            tokio::task::spawn(syndicate_article(article.clone(), state.pool.clone()));
+        }
+
+        tokio::task::spawn(send_mentions(uri.clone(), state.pool.clone()));
+    }
+
+    Ok(uri)
+}
+
+async fn create_picture(
+    properties: &Properties,
+    photo: UploadedFile,
+    author_id: i32,
+    state: &AppState,
+    conn: &mut PgConnection,
+) -> Result<String, MicropubError> {
+    let content_type = new_mime_guess::from_path(&photo.file_name)
+        .first_raw()
+        .unwrap_or("image/jpeg")
+        .to_owned();
+
+    let base = NewPicture {
+        author_id: Some(author_id),
+        lang: "en".to_owned(),
+        show_in_index: true,
+        posse_visibility: "public".to_owned(),
+        image_file_name: Some(photo.file_name.clone()),
+        image_content_type: Some(content_type),
+        ..Default::default()
+    };
+
+    let values = picture_values(properties, base);
+
+    let f = tokio::fs::File::from_std(
+        photo
+            .file
+            .reopen()
+            .map_err(|e| MicropubError::InternalError(format!("could not open uploaded photo: {}", e)))?,
+    );
+
+    let picture = pictures::actions::create_picture(&values, Some(f), conn)
+        .await
+        .map_err(invalid)?;
+    let uri = picture_uri(&picture);
+
+    let pool = state.pool.clone();
+    tokio::task::spawn_blocking(move || {
+        let _ = generate_pictures(&picture);
+        tokio::task::spawn(send_mentions(picture_uri(&picture), pool.clone()));
+
+        if picture.posse {
+            tokio::task::spawn(syndicate_picture(picture, pool));
+        }
+    });
+
+    Ok(uri)
+}
+
+async fn create_like(
+    properties: &Properties,
+    author_id: i32,
+    state: &AppState,
+    conn: &mut PgConnection,
+) -> Result<String, MicropubError> {
+    let base = NewLike {
+        author_id: Some(author_id),
+        show_in_index: true,
+        ..Default::default()
+    };
+
+    let values = like_values(properties, base);
+    let like = likes::actions::create_like(&values, conn).await.map_err(invalid)?;
+    let uri = like_uri(&like);
+
+    if like.posse {
+        tokio::task::spawn(syndicate_like(like, state.pool.clone()));
+    }
+
+    tokio::task::spawn(send_mentions(uri.clone(), state.pool.clone()));
+
+    Ok(uri)
+}
+
+/// Fetches a photo given by URL instead of a file upload; files uploaded to our media endpoint are copied
+/// from disk.
+async fn fetch_photo(url: &str, conn: &mut PgConnection) -> Result<UploadedFile, MicropubError> {
+    let url = Url::parse(url).map_err(|_| MicropubError::InvalidRequest("photo url invalid".to_owned()))?;
+
+    if let Some(media_file) = local_media_file(&url, conn).await? {
+        let path = format!("{}/{}/original/{}", media_base_path(), media_file.id, media_file.file_name);
+        let mut file = NamedTempFile::new()
+            .map_err(|e| MicropubError::InternalError(format!("could not create temp file: {}", e)))?;
+        let mut source = std::fs::File::open(path)
+            .map_err(|e| MicropubError::InternalError(format!("could not open media file: {}", e)))?;
+        std::io::copy(&mut source, &mut file)
+            .map_err(|e| MicropubError::InternalError(format!("could not copy media file: {}", e)))?;
+
+        return Ok(UploadedFile {
+            file_name: media_file.file_name,
+            file,
+        });
+    }
+
+    if !is_public_url(&url) {
+        return Err(MicropubError::InvalidRequest("photo url invalid".to_owned()));
+    }
+
+    let client = build_client(PHOTO_FETCH_TIMEOUT)
+        .map_err(|e| MicropubError::InternalError(format!("could not build HTTP client: {}", e)))?;
+
+    let mut rsp = client
+        .get(url.as_str())
+        .send()
+        .await
+        .and_then(|rsp| rsp.error_for_status())
+        .map_err(|e| MicropubError::InvalidRequest(format!("could not fetch photo: {}", e)))?;
+
+    if rsp.content_length().is_some_and(|len| len > MAX_UPLOAD_SIZE as u64) {
+        return Err(MicropubError::InvalidRequest("photo is too large".to_owned()));
+    }
+
+    let mut file =
+        NamedTempFile::new().map_err(|e| MicropubError::InternalError(format!("could not create temp file: {}", e)))?;
+    let mut size = 0;
+
+    // the photo is written as it comes in, giving up once it exceeds `MAX_UPLOAD_SIZE`
+    while let Some(chunk) = rsp
+        .chunk()
+        .await
+        .map_err(|e| MicropubError::InvalidRequest(format!("could not fetch photo: {}", e)))?
+    {
+        size += chunk.len();
+        if size > MAX_UPLOAD_SIZE {
+            return Err(MicropubError::InvalidRequest("photo is too large".to_owned()));
+        }
+
+        file.write_all(&chunk)
+            .map_err(|e| MicropubError::InternalError(format!("could not write temp file: {}", e)))?;
+    }
+
+    let file_name = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("photo.jpg")
+        .to_owned();
+
+    Ok(UploadedFile { file_name, file })
+}
+
+async fn local_media_file(url: &Url, conn: &mut PgConnection) -> Result<Option<MediaFile>, MicropubError> {
+    let Some(path) = url.as_str().strip_prefix(&root_uri()) else {
+        return Ok(None);
+    };
+
+    let pieces = path.trim_start_matches('/').split('/').collect::<Vec<_>>();
+    let ["media", id, file_name] = pieces.as_slice() else {
+        return Ok(None);
+    };
+
+    let Ok(id) = id.parse() else {
+        return Ok(None);
+    };
+
+    match media::actions::get_media_file(id, conn).await {
+        Ok(media_file) if media_file.file_name == *file_name => Ok(Some(media_file)),
+        Ok(_) | Err(sqlx::Error::RowNotFound) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,48 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+
+use super::MicropubError;
+use super::actions::{Post, find_own_post};
+use super::endpoint::MicropubRequest;
+use crate::posse::syndicate::delete_syndicated;
+use crate::webmentions::actions::create_tombstone;
+use crate::webmentions::send::send_mentions_for_deleted;
+use crate::{AppState, articles, likes, notes, pictures};
+
+/// Deletes a post; like the admin interface this removes it for good and leaves a tombstone
+pub async fn delete(request: MicropubRequest, author_id: i32, state: &AppState) -> Result<Response, MicropubError> {
+    let Some(url) = request.url.as_deref() else {
+        return Err(MicropubError::InvalidRequest("url is missing".to_owned()));
+    };
+
+    let mut conn = state.pool.acquire().await?;
+    let post = find_own_post(url, author_id, &mut conn).await?;
+    let uri = post.uri();
+
+    match &post {
+        Post::Article(article) => {
+            articles::actions::delete_article(article.id, &mut conn).await?;
+            state.article_cache.remove(&article.slug).await;
+        }
+        Post::Note(note) => {
+            notes::actions::delete_note(note.id, &mut conn).await?;
+            state.note_cache.remove(&note.id).await;
+        }
+        Post::Picture(picture) => {
+            pictures::actions::delete_picture(picture, &mut conn).await?;
+            state.picture_cache.remove(&picture.id).await;
+        }
+        Post::Like(like) => {
+            likes::actions::delete_like(like.id, &mut conn).await?;
+            state.like_cache.remove(&like.id).await;
+        }
+    }
+
+    create_tombstone(&uri, &mut conn).await?;
+    tokio::task::spawn(send_mentions_for_deleted(uri, state.pool.clone()));
+
+    let (object_type, id) = post.object_type_and_id();
+    tokio::task::spawn(delete_syndicated(object_type, id, state.pool.clone()));
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
@@ -0,0 +1,249 @@
+use std::io::Write;
+use std::net::SocketAddr;
+
+use axum::body::Bytes;
+use axum::extract::{ConnectInfo, FromRequest, Multipart, Request, State};
+use axum::http::{HeaderMap, header};
+use axum::response::Response;
+use serde_json::Value;
+use tempfile::NamedTempFile;
+
+use super::auth::{access_token, authenticate};
+use super::{MicropubError, Properties, create, delete, update};
+use crate::models::NewAuditLogEntry;
+use crate::utils::rate_limit::client_ip;
+use crate::{AppState, AuthSession, audit};
+
+/// a file uploaded in a multipart request
+pub struct UploadedFile {
+    pub file_name: String,
+    pub file: NamedTempFile,
+}
+
+/// A micropub request, normalized from its form-encoded, multipart or JSON representation
+pub struct MicropubRequest {
+    pub action: String,
+    pub url: Option<String>,
+    pub entry_type: String,
+    pub properties: Properties,
+
+    pub replace: Properties,
+    pub add: Properties,
+    pub delete_properties: Vec<String>,
+    pub delete_values: Properties,
+
+    pub photos: Vec<UploadedFile>,
+}
+
+impl Default for MicropubRequest {
+    fn default() -> Self {
+        Self {
+            action: "create".to_owned(),
+            url: None,
+            entry_type: "entry".to_owned(),
+            properties: Properties::new(),
+            replace: Properties::new(),
+            add: Properties::new(),
+            delete_properties: Vec::new(),
+            delete_values: Properties::new(),
+            photos: Vec::new(),
+        }
+    }
+}
+
+pub async fn post(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    req: Request,
+) -> Result<Response, MicropubError> {
+    let headers = req.headers().clone();
+
+    // the body of unknown clients isn't read at all, so the token has to be in the Authorization header
+    let authorization = {
+        let mut conn = state.pool.acquire().await?;
+        authenticate(&auth, access_token(&headers, None).as_deref(), &mut conn).await?
+    };
+
+    let request = parse_request(req, &state).await?;
+
+    let scope = match request.action.as_str() {
+        "update" => "update",
+        "delete" => "delete",
+        _ => "create",
+    };
+    let author_id = authorization.require(Some(scope))?;
+
+    let action = request.action.clone();
+    let url = request.url.clone();
+
+    let response = match action.as_str() {
+        "create" => create::create(request, author_id, &state).await?,
+        "update" => update::update(request, author_id, &state).await?,
+        "delete" => delete::delete(request, author_id, &state).await?,
+        action => return Err(MicropubError::InvalidRequest(format!("unsupported action: {}", action))),
+    };
+
+    let location = response
+        .headers()
+        .get(header::LOCATION)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_owned());
+
+    let entry = NewAuditLogEntry {
+        author_id: Some(author_id),
+        action,
+        path: location.or(url).unwrap_or_else(|| "/micropub".to_owned()),
+        email: None,
+        ip: Some(client_ip(&headers, peer).to_string()),
+    };
+
+    let mut conn = state.pool.acquire().await?;
+    audit::record(&entry, &mut conn).await?;
+
+    Ok(response)
+}
+
+fn content_type(headers: &HeaderMap) -> String {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+async fn parse_request(req: Request, state: &AppState) -> Result<MicropubRequest, MicropubError> {
+    let content_type = content_type(req.headers());
+
+    if content_type.starts_with("multipart/form-data") {
+        let multipart = Multipart::from_request(req, state)
+            .await
+            .map_err(|e| MicropubError::InvalidRequest(e.body_text()))?;
+
+        return parse_multipart(multipart).await;
+    }
+
+    let body = Bytes::from_request(req, state)
+        .await
+        .map_err(|e| MicropubError::InvalidRequest(e.body_text()))?;
+
+    if content_type.starts_with("application/json") {
+        let value = serde_json::from_slice::<Value>(&body)
+            .map_err(|e| MicropubError::InvalidRequest(format!("invalid JSON: {}", e)))?;
+        parse_json(value)
+    } else {
+        let mut request = MicropubRequest::default();
+
+        for (key, value) in url::form_urlencoded::parse(&body) {
+            push_field(&mut request, &key, value.into_owned());
+        }
+
+        Ok(request)
+    }
+}
+
+fn push_field(request: &mut MicropubRequest, key: &str, value: String) {
+    match key {
+        "h" => request.entry_type = value,
+        "action" => request.action = value,
+        "url" => request.url = Some(value),
+        // only accepted in the Authorization header, see `post()`
+        "access_token" => {}
+        key => request
+            .properties
+            .entry(key.trim_end_matches("[]").to_owned())
+            .or_default()
+            .push(Value::String(value)),
+    }
+}
+
+async fn parse_multipart(mut multipart: Multipart) -> Result<MicropubRequest, MicropubError> {
+    let mut request = MicropubRequest::default();
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| MicropubError::InvalidRequest(e.body_text()))?
+    {
+        let name = field.name().unwrap_or_default().to_owned();
+
+        let Some(file_name) = field.file_name().map(|file_name| file_name.to_owned()) else {
+            let value = field
+                .text()
+                .await
+                .map_err(|e| MicropubError::InvalidRequest(e.body_text()))?;
+            push_field(&mut request, &name, value);
+            continue;
+        };
+
+        if name.trim_end_matches("[]") != "photo" {
+            return Err(MicropubError::InvalidRequest(format!("unsupported file upload: {}", name)));
+        }
+
+        let bytes = field
+            .bytes()
+            .await
+            .map_err(|e| MicropubError::InvalidRequest(e.body_text()))?;
+
+        let mut file = NamedTempFile::new()
+            .map_err(|e| MicropubError::InternalError(format!("could not create temp file: {}", e)))?;
+        file.write_all(&bytes)
+            .map_err(|e| MicropubError::InternalError(format!("could not write temp file: {}", e)))?;
+
+        request.photos.push(UploadedFile { file_name, file });
+    }
+
+    Ok(request)
+}
+
+fn parse_json(value: Value) -> Result<MicropubRequest, MicropubError> {
+    let Value::Object(obj) = value else {
+        return Err(MicropubError::InvalidRequest("expected a JSON object".to_owned()));
+    };
+
+    let text = |key: &str| obj.get(key).and_then(|value| value.as_str()).map(|s| s.to_owned());
+    let mut request = MicropubRequest::default();
+
+    if let Some(action) = text("action") {
+        request.action = action;
+    }
+
+    if let Some(entry_type) = obj.get("type").and_then(|types| types.get(0)).and_then(|t| t.as_str()) {
+        request.entry_type = entry_type.trim_start_matches("h-").to_owned();
+    }
+
+    request.url = text("url");
+    request.properties = json_properties(obj.get("properties"))?;
+    request.replace = json_properties(obj.get("replace"))?;
+    request.add = json_properties(obj.get("add"))?;
+
+    match obj.get("delete") {
+        Some(Value::Array(names)) => {
+            request.delete_properties = names
+                .iter()
+                .filter_map(|name| name.as_str().map(|s| s.to_owned()))
+                .collect()
+        }
+        Some(value @ Value::Object(_)) => request.delete_values = json_properties(Some(value))?,
+        Some(_) => return Err(MicropubError::InvalidRequest("delete must be an array or an object".to_owned())),
+        None => {}
+    }
+
+    Ok(request)
+}
+
+fn json_properties(value: Option<&Value>) -> Result<Properties, MicropubError> {
+    let properties = match value {
+        None => return Ok(Properties::new()),
+        Some(Value::Object(properties)) => properties,
+        Some(_) => return Err(MicropubError::InvalidRequest("properties must be an object".to_owned())),
+    };
+
+    properties
+        .iter()
+        .map(|(name, values)| match values {
+            Value::Array(values) => Ok((name.clone(), values.clone())),
+            _ => Err(MicropubError::InvalidRequest(format!("values of {} must be an array", name))),
+        })
+        .collect()
+}
@@ -0,0 +1,128 @@
+use std::io::Write;
+use std::net::SocketAddr;
+
+use axum::extract::{ConnectInfo, Multipart, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use tempfile::NamedTempFile;
+
+use super::MicropubError;
+use super::auth::{access_token, authorize};
+use crate::models::{NewAuditLogEntry, generate_media_versions};
+use crate::uri_helpers::*;
+use crate::utils::rate_limit::client_ip;
+use crate::{AppState, AuthSession, audit, media};
+
+/// The micropub media endpoint: stores the uploaded `file` and answers with its URL in the `Location` header
+pub async fn upload(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    mut multipart: Multipart,
+) -> Result<Response, MicropubError> {
+    let mut token = None;
+    let mut upload = None;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| MicropubError::InvalidRequest(e.body_text()))?
+    {
+        match field.name() {
+            Some("access_token") => {
+                token = Some(
+                    field
+                        .text()
+                        .await
+                        .map_err(|e| MicropubError::InvalidRequest(e.body_text()))?,
+                )
+            }
+
+            Some("file") => {
+                let file_name = sanitize_file_name(field.file_name().unwrap_or_default());
+                let content_type = field
+                    .content_type()
+                    .filter(|content_type| *content_type != "application/octet-stream")
+                    .map(|content_type| content_type.to_owned())
+                    .or_else(|| {
+                        new_mime_guess::from_path(&file_name)
+                            .first_raw()
+                            .map(|ct| ct.to_owned())
+                    })
+                    .unwrap_or_else(|| "application/octet-stream".to_owned());
+
+                let bytes = field
+                    .bytes()
+                    .await
+                    .map_err(|e| MicropubError::InvalidRequest(e.body_text()))?;
+
+                let mut file = NamedTempFile::new()
+                    .map_err(|e| MicropubError::InternalError(format!("could not create temp file: {}", e)))?;
+                file.write_all(&bytes)
+                    .map_err(|e| MicropubError::InternalError(format!("could not write temp file: {}", e)))?;
+
+                upload = Some((file_name, content_type, file));
+            }
+
+            _ => {}
+        }
+    }
+
+    let mut conn = state.pool.acquire().await?;
+    let token = access_token(&headers, token.as_deref());
+    let author_id = authorize(&auth, token.as_deref(), Some("media"), &mut conn).await?;
+
+    let Some((file_name, content_type, file)) = upload else {
+        return Err(MicropubError::InvalidRequest("file is missing".to_owned()));
+    };
+
+    let f = tokio::fs::File::from_std(
+        file.reopen()
+            .map_err(|e| MicropubError::InternalError(format!("could not open uploaded file: {}", e)))?,
+    );
+
+    let media_file = media::actions::create_media_file(author_id, &file_name, &content_type, f, &mut conn)
+        .await
+        .map_err(|e| MicropubError::InternalError(e.to_string()))?;
+    let uri = media_file_uri(&media_file);
+
+    let entry = NewAuditLogEntry {
+        author_id: Some(author_id),
+        action: "create".to_owned(),
+        path: uri.clone(),
+        email: None,
+        ip: Some(client_ip(&headers, peer).to_string()),
+    };
+    audit::record(&entry, &mut conn).await?;
+
+    if media_file.is_image() {
+        tokio::task::spawn_blocking(move || {
+            let _ = generate_media_versions(&media_file);
+        });
+    }
+
+    Ok((StatusCode::CREATED, [(header::LOCATION, uri)]).into_response())
+}
+
+/// Reduces a client supplied file name to something safe to use as a path segment
+fn sanitize_file_name(file_name: &str) -> String {
+    let file_name = file_name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let file_name = file_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    let file_name = file_name.trim_start_matches('.');
+
+    if file_name.is_empty() {
+        "upload".to_owned()
+    } else {
+        file_name.to_owned()
+    }
+}
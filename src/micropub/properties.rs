@@ -0,0 +1,100 @@
+use serde_json::Value;
+
+use super::Properties;
+
+/// Returns the first value of `name` as text; HTML content (`{"html": …}`) and embedded objects
+/// (`{"value": …}`) are unwrapped.
+pub fn first_text(properties: &Properties, name: &str) -> Option<String> {
+    all_texts(properties, name).into_iter().next()
+}
+
+pub fn all_texts(properties: &Properties, name: &str) -> Vec<String> {
+    properties
+        .get(name)
+        .map(|values| values.iter().filter_map(value_text).collect())
+        .unwrap_or_default()
+}
+
+fn value_text(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        Value::Object(obj) => obj
+            .get("html")
+            .or_else(|| obj.get("value"))
+            .and_then(|v| v.as_str())?
+            .to_owned(),
+        _ => return None,
+    };
+
+    let text = text.trim();
+    if text.is_empty() { None } else { Some(text.to_owned()) }
+}
+
+/// Returns the `alt` text of the first value of `name`, e.g. of `{"value": "…/img.jpg", "alt": "…"}`
+pub fn first_alt(properties: &Properties, name: &str) -> Option<String> {
+    properties
+        .get(name)?
+        .first()?
+        .get("alt")?
+        .as_str()
+        .map(|alt| alt.to_owned())
+}
+
+pub fn set_text(properties: &mut Properties, name: &str, value: Option<&str>) {
+    if let Some(value) = value {
+        properties.insert(name.to_owned(), vec![Value::String(value.to_owned())]);
+    }
+}
+
+/// Applies the `replace`, `add` and `delete` operations of an update request to `properties`.
+pub fn apply_update(
+    properties: &mut Properties,
+    replace: &Properties,
+    add: &Properties,
+    delete_properties: &[String],
+    delete_values: &Properties,
+) {
+    for (name, values) in replace {
+        properties.insert(name.clone(), values.clone());
+    }
+
+    for (name, values) in add {
+        properties
+            .entry(name.clone())
+            .or_default()
+            .extend(values.iter().cloned());
+    }
+
+    for name in delete_properties {
+        properties.remove(name);
+    }
+
+    for (name, values) in delete_values {
+        if let Some(existing) = properties.get_mut(name) {
+            existing.retain(|value| !values.contains(value));
+        }
+    }
+}
+
+/// Makes a URL slug out of `text`: lower case ASCII letters and digits separated by dashes
+pub fn slugify(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Derives a title from the first line of `text` for post types which have to have one
+pub fn title_from_text(text: &str) -> String {
+    const MAX_LEN: usize = 100;
+
+    let line = text.lines().next().unwrap_or_default().trim();
+    if line.chars().count() <= MAX_LEN {
+        return line.to_owned();
+    }
+
+    let mut title = line.chars().take(MAX_LEN - 1).collect::<String>();
+    title.push('…');
+    title
+}
@@ -0,0 +1,93 @@
+use axum::Json;
+use axum::extract::{RawQuery, State};
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
+use serde_json::{Value, json};
+
+use super::MicropubError;
+use super::actions::{find_post, post_properties, syndication_targets};
+use super::auth::{access_token, authorize};
+use crate::models::Syndication;
+use crate::posse::actions::list_syndications;
+use crate::uri_helpers::*;
+use crate::{AppState, AuthSession};
+
+/// Answers the `q=config`, `q=syndicate-to` and `q=source` queries
+pub async fn query(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Result<Response, MicropubError> {
+    let params = url::form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+        .into_owned()
+        .collect::<Vec<(String, String)>>();
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    let token = access_token(&headers, param("access_token"));
+    let mut conn = state.pool.acquire().await?;
+    authorize(&auth, token.as_deref(), None, &mut conn).await?;
+
+    let syndicate_to = syndication_targets()
+        .into_iter()
+        .map(|(uid, name)| json!({ "uid": uid, "name": name }))
+        .collect::<Vec<Value>>();
+
+    match param("q") {
+        Some("config") => Ok(Json(json!({
+            "media-endpoint": micropub_media_endpoint_uri(),
+            "syndicate-to": syndicate_to,
+            "q": ["config", "syndicate-to", "source"],
+            "post-types": [
+                { "type": "note", "name": "Note" },
+                { "type": "article", "name": "Article" },
+                { "type": "photo", "name": "Picture" },
+                { "type": "like", "name": "Like" },
+            ],
+        }))
+        .into_response()),
+
+        Some("syndicate-to") => Ok(Json(json!({ "syndicate-to": syndicate_to })).into_response()),
+
+        Some("source") => {
+            let Some(url) = param("url") else {
+                return Err(MicropubError::InvalidRequest("url is missing".to_owned()));
+            };
+
+            let post = find_post(url, &mut conn).await?;
+            let mut properties = post_properties(&post);
+
+            let (object_type, id) = post.object_type_and_id();
+            let urls = list_syndications(object_type, id, &mut conn)
+                .await?
+                .into_iter()
+                .filter(Syndication::is_copy)
+                .map(|syndication| json!(syndication.url))
+                .collect::<Vec<_>>();
+            if !urls.is_empty() {
+                properties.insert("syndication".to_owned(), urls);
+            }
+
+            let wanted = params
+                .iter()
+                .filter(|(key, _)| key == "properties[]" || key == "properties")
+                .map(|(_, value)| value.as_str())
+                .collect::<Vec<_>>();
+
+            if !wanted.is_empty() {
+                properties.retain(|name, _| wanted.contains(&name.as_str()));
+                return Ok(Json(json!({ "properties": properties })).into_response());
+            }
+
+            Ok(Json(json!({ "type": ["h-entry"], "properties": properties })).into_response())
+        }
+
+        Some(q) => Err(MicropubError::InvalidRequest(format!("unsupported query: {}", q))),
+        None => Err(MicropubError::InvalidRequest("q is missing".to_owned())),
+    }
+}
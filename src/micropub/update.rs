@@ -0,0 +1,169 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+
+use super::MicropubError;
+use super::actions::{Post, article_values, find_own_post, like_values, note_values, picture_values, post_properties};
+use super::endpoint::MicropubRequest;
+use super::properties::apply_update;
+use crate::models::{NewArticle, NewLike, NewNote, NewPicture, generate_pictures};
+use crate::posse::syndicate::{
+    syndicate_article, syndicate_like, syndicate_note, syndicate_picture, update_syndicated_article,
+    update_syndicated_note, update_syndicated_picture,
+};
+use crate::posse::targets_added;
+use crate::uri_helpers::*;
+use crate::webmentions::send::send_mentions;
+use crate::{AppState, articles, likes, notes, pictures};
+
+/// Updates a post by applying the `replace`, `add` and `delete` operations to its current properties
+pub async fn update(request: MicropubRequest, author_id: i32, state: &AppState) -> Result<Response, MicropubError> {
+    let Some(url) = request.url.as_deref() else {
+        return Err(MicropubError::InvalidRequest("url is missing".to_owned()));
+    };
+
+    let mut conn = state.pool.acquire().await?;
+    let post = find_own_post(url, author_id, &mut conn).await?;
+
+    let mut properties = post_properties(&post);
+    apply_update(&mut properties, &request.replace, &request.add, &request.delete_properties, &request.delete_values);
+
+    let invalid = |e: Box<dyn std::error::Error + Send + Sync>| MicropubError::InvalidRequest(e.to_string());
+
+    match post {
+        Post::Article(article) => {
+            let base = NewArticle {
+                author_id: Some(article.author_id),
+                slug: article.slug.clone(),
+                guid: Some(article.guid.clone()),
+                article_format: Some(article.article_format.clone()),
+                posse: article.posse,
+                lang: article.lang.clone(),
+                posse_visibility: article.posse_visibility.clone(),
+                content_warning: article.content_warning.clone(),
+                posse_targets: article.posse_targets.clone(),
+                ..Default::default()
+            };
+
+            let values = article_values(&properties, base);
+            let updated_article = articles::actions::update_article(article.id, &values, &mut conn)
+                .await
+                .map_err(invalid)?;
+
+            state.article_cache.remove(&article.slug).await;
+            state
+                .article_cache
+                .insert(updated_article.slug.clone(), updated_article.clone())
+                .await;
+
+            if updated_article.published {
+                let added = targets_added(
+                    updated_article.posse,
+                    &updated_article.posse_targets,
+                    article.posse,
+                    &article.posse_targets,
+                );
+                if !article.published || added {
+                    tokio::task::spawn(syndicate_article(updated_article.clone(), state.pool.clone()));
+                }
+
+                tokio::task::spawn(update_syndicated_article(updated_article.clone(), state.pool.clone()));
+
+                tokio::task::spawn(send_mentions(article_uri(&updated_article), state.pool.clone()));
+            }
+        }
+
+        Post::Note(note) => {
+            let base = NewNote {
+                author_id: Some(note.author_id),
+                lang: note.lang.clone(),
+                posse: note.posse,
+                show_in_index: note.show_in_index,
+                posse_visibility: note.posse_visibility.clone(),
+                content_warning: note.content_warning.clone(),
+                posse_targets: note.posse_targets.clone(),
+                ..Default::default()
+            };
+
+            let values = note_values(&properties, base);
+            let updated_note = notes::actions::update_note(note.id, &values, &mut conn)
+                .await
+                .map_err(invalid)?;
+
+            state.note_cache.insert(updated_note.id, updated_note.clone()).await;
+            tokio::task::spawn(send_mentions(note_uri(&updated_note), state.pool.clone()));
+
+            if targets_added(updated_note.posse, &updated_note.posse_targets, note.posse, &note.posse_targets) {
+                tokio::task::spawn(syndicate_note(updated_note.clone(), state.pool.clone()));
+            }
+
+            tokio::task::spawn(update_syndicated_note(updated_note, state.pool.clone()));
+        }
+
+        Post::Picture(picture) => {
+            let base = NewPicture {
+                author_id: Some(picture.author_id),
+                alt: picture.alt.clone(),
+                lang: picture.lang.clone(),
+                posse: picture.posse,
+                show_in_index: picture.show_in_index,
+                posse_visibility: picture.posse_visibility.clone(),
+                content_warning: picture.content_warning.clone(),
+                posse_targets: picture.posse_targets.clone(),
+                ..Default::default()
+            };
+
+            let values = picture_values(&properties, base);
+            let updated_picture = pictures::actions::update_picture(&picture, &values, None, &mut conn)
+                .await
+                .map_err(invalid)?;
+
+            state
+                .picture_cache
+                .insert(updated_picture.id, updated_picture.clone())
+                .await;
+            let added = targets_added(
+                updated_picture.posse,
+                &updated_picture.posse_targets,
+                picture.posse,
+                &picture.posse_targets,
+            );
+
+            let pool = state.pool.clone();
+            tokio::task::spawn_blocking(move || {
+                let _ = generate_pictures(&updated_picture);
+                tokio::task::spawn(send_mentions(picture_uri(&updated_picture), pool.clone()));
+
+                if added {
+                    tokio::task::spawn(syndicate_picture(updated_picture.clone(), pool.clone()));
+                }
+
+                tokio::task::spawn(update_syndicated_picture(updated_picture, pool));
+            });
+        }
+
+        Post::Like(like) => {
+            let base = NewLike {
+                author_id: Some(like.author_id),
+                posse: like.posse,
+                show_in_index: like.show_in_index,
+                posse_targets: like.posse_targets.clone(),
+                ..Default::default()
+            };
+
+            let values = like_values(&properties, base);
+            let updated_like = likes::actions::update_like(like.id, &values, &mut conn)
+                .await
+                .map_err(invalid)?;
+
+            state.like_cache.insert(updated_like.id, updated_like.clone()).await;
+
+            if targets_added(updated_like.posse, &updated_like.posse_targets, like.posse, &like.posse_targets) {
+                tokio::task::spawn(syndicate_like(updated_like.clone(), state.pool.clone()));
+            }
+
+            tokio::task::spawn(send_mentions(like_uri(&updated_like), state.pool.clone()));
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
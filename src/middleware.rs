@@ -1,11 +1,23 @@
-use axum::extract::{Request, State};
-use axum::http::{self, Uri, header};
-use axum::response::Response;
-use chrono::Duration;
-#[cfg(not(debug_assertions))]
-use chrono::Utc;
+use std::net::SocketAddr;
 
-use crate::uri_helpers::webmentions_endpoint_uri;
+use askama::Template;
+use axum::body::Body;
+use axum::extract::{ConnectInfo, FromRequest, MatchedPath, Multipart, Request, State};
+use axum::http::{self, Method, StatusCode, Uri, header};
+use axum::middleware::Next;
+use axum::response::{Html, IntoResponse, Redirect, Response};
+use chrono::{Duration, Utc};
+
+use crate::audit;
+use crate::models::{NewAuditLogEntry, SessionInfo};
+use crate::session::store::SESSION_INFO_KEY;
+use crate::uri_helpers::{root_uri, two_factor_uri, webmentions_endpoint_uri};
+use crate::utils::csrf::{self, CsrfError};
+use crate::utils::rate_limit::client_ip;
+use crate::utils::tokens::constant_time_eq;
+use crate::utils::two_factor_required;
+use crate::webmentions::actions::tombstone_exists;
+use crate::{AppState, AuthSession, MAX_UPLOAD_SIZE};
 
 pub async fn webmention_middleware<B>(mut response: Response<B>) -> Response<B> {
     if let Ok(value) = http::HeaderValue::from_str(&format!("<{}>; rel=\"webmention\"", webmentions_endpoint_uri())) {
@@ -15,6 +27,241 @@ pub async fn webmention_middleware<B>(mut response: Response<B>) -> Response<B>
     response
 }
 
+/// Turns a `404 Not Found` into a `410 Gone` when the requested post has been deleted.
+pub async fn gone_middleware(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let url = format!("{}{}", root_uri().trim_end_matches('/'), req.uri().path());
+    let response = next.run(req).await;
+
+    if response.status() != StatusCode::NOT_FOUND {
+        return response;
+    }
+
+    let Ok(mut conn) = state.pool.acquire().await else {
+        return response;
+    };
+
+    match tombstone_exists(&url, &mut conn).await {
+        Ok(true) => (StatusCode::GONE, "gone: this post has been deleted").into_response(),
+        _ => response,
+    }
+}
+
+/// Sends logged in authors to the two-factor enrollment when two-factor authentication is required and they
+/// haven't enabled it yet.
+pub async fn two_factor_middleware(auth: AuthSession, req: Request, next: Next) -> Response {
+    match auth.user {
+        Some(user) if two_factor_required() && !user.has_two_factor() => {
+            Redirect::to(&two_factor_uri()).into_response()
+        }
+        _ => next.run(req).await,
+    }
+}
+
+/// Protects logged in authors against cross-site request forgery: every request changing something has to
+/// carry the CSRF token of the session, either as `csrf_token` form field or as `X-CSRF-Token` header.
+/// Requests with an `Authorization` header (micropub clients) can't be forged by other sites and aren't checked.
+pub async fn csrf_middleware(auth: AuthSession, req: Request, next: Next) -> Response {
+    if auth.user.is_none() {
+        return next.run(req).await;
+    }
+
+    let token = match csrf::session_token(&auth).await {
+        Ok(token) => token,
+        Err(e) => {
+            tracing::error!("could not read CSRF token: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "internal error: could not read session").into_response();
+        }
+    };
+
+    let safe_method = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    if safe_method || req.headers().contains_key(header::AUTHORIZATION) {
+        return csrf::with_token(token, next.run(req)).await;
+    }
+
+    let (req, submitted) = submitted_csrf_token(req).await;
+    let valid = submitted.is_some_and(|submitted| constant_time_eq(&submitted, &token));
+
+    if !valid {
+        let html = csrf::with_token(token, async { CsrfError::new().render() }).await;
+
+        return match html {
+            Ok(html) => (StatusCode::FORBIDDEN, Html(html)).into_response(),
+            Err(_) => (StatusCode::FORBIDDEN, "forbidden: invalid CSRF token").into_response(),
+        };
+    }
+
+    csrf::with_token(token, next.run(req)).await
+}
+
+/// Looks for the CSRF token in the header or in the form data; the body is buffered and put back into the
+/// request for the handler.
+async fn submitted_csrf_token(req: Request) -> (Request, Option<String>) {
+    if let Some(token) = req
+        .headers()
+        .get(csrf::HEADER_NAME)
+        .and_then(|value| value.to_str().ok())
+    {
+        let token = token.to_owned();
+        return (req, Some(token));
+    }
+
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let (parts, body) = req.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, MAX_UPLOAD_SIZE).await else {
+        return (Request::from_parts(parts, Body::empty()), None);
+    };
+
+    let token = if content_type.starts_with("application/x-www-form-urlencoded") {
+        url::form_urlencoded::parse(&bytes)
+            .find(|(key, _)| key == csrf::FIELD_NAME)
+            .map(|(_, value)| value.into_owned())
+    } else if content_type.starts_with("multipart/form-data") {
+        multipart_csrf_token(Request::from_parts(parts.clone(), Body::from(bytes.clone()))).await
+    } else {
+        None
+    };
+
+    (Request::from_parts(parts, Body::from(bytes)), token)
+}
+
+async fn multipart_csrf_token(req: Request<Body>) -> Option<String> {
+    let mut multipart = Multipart::from_request(req, &()).await.ok()?;
+
+    while let Ok(Some(field)) = multipart.next_field().await {
+        if field.name() == Some(csrf::FIELD_NAME) {
+            return field.text().await.ok();
+        }
+    }
+
+    None
+}
+
+/// how often the last seen time of a session is refreshed
+const SESSION_SEEN_INTERVAL: Duration = Duration::minutes(5);
+
+/// Keeps the `SessionInfo` of logged in authors up to date, for the list of their sessions: the session is
+/// only written when the IP address or user agent changed or the last seen time is too old.
+pub async fn session_info_middleware(
+    auth: AuthSession,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(user) = &auth.user else {
+        return next.run(req).await;
+    };
+
+    let ip = client_ip(req.headers(), peer).to_string();
+    let user_agent = req
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(|user_agent| user_agent.chars().take(255).collect::<String>());
+    let now = Utc::now().naive_utc();
+
+    let info = match auth.session.get::<SessionInfo>(SESSION_INFO_KEY).await {
+        Ok(Some(info)) if info.author_id == user.id => {
+            if info.ip == ip && info.user_agent == user_agent && now - info.last_seen_at < SESSION_SEEN_INTERVAL {
+                None
+            } else {
+                Some(SessionInfo {
+                    ip,
+                    user_agent,
+                    last_seen_at: now,
+                    ..info
+                })
+            }
+        }
+
+        _ => Some(SessionInfo {
+            author_id: user.id,
+            ip,
+            user_agent,
+            created_at: now,
+            last_seen_at: now,
+        }),
+    };
+
+    if let Some(info) = info {
+        if let Err(e) = auth.session.insert(SESSION_INFO_KEY, &info).await {
+            tracing::error!("could not write session info: {}", e);
+        }
+    }
+
+    next.run(req).await
+}
+
+/// Records the changes logged in authors make in the audit log. The action is derived from the route:
+/// `…/delete` and `…/disable` routes delete, other routes with a parameter update and the rest creates.
+/// Logins, logouts, password resets and micropub requests are recorded by their handlers.
+pub async fn audit_middleware(
+    State(state): State<AppState>,
+    auth: AuthSession,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    matched_path: Option<MatchedPath>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let path = req.uri().path().to_owned();
+    let excluded = ["/login", "/logout", "/password-reset", "/micropub"]
+        .iter()
+        .any(|prefix| path.starts_with(prefix));
+
+    let author_id = match &auth.user {
+        Some(user) if req.method() == Method::POST && !excluded => user.id,
+        _ => return next.run(req).await,
+    };
+
+    let ip = client_ip(req.headers(), peer);
+    let route = matched_path
+        .as_ref()
+        .map_or(path.as_str(), |matched_path| matched_path.as_str());
+    let action = if route.ends_with("/delete") || route.ends_with("/disable") {
+        "delete"
+    } else if route.contains('{') {
+        "update"
+    } else {
+        "create"
+    };
+
+    let response = next.run(req).await;
+    if response.status().is_client_error() || response.status().is_server_error() {
+        return response;
+    }
+
+    // for creates the redirect leads to the new post
+    let location = response
+        .headers()
+        .get(header::LOCATION)
+        .and_then(|value| value.to_str().ok())
+        .filter(|_| action == "create");
+
+    let entry = NewAuditLogEntry {
+        author_id: Some(author_id),
+        action: action.to_owned(),
+        path: location.map_or(path, |location| location.to_owned()),
+        email: None,
+        ip: Some(ip.to_string()),
+    };
+
+    match state.pool.acquire().await {
+        Ok(mut conn) => {
+            if let Err(e) = audit::record(&entry, &mut conn).await {
+                tracing::error!("could not write audit log: {}", e);
+            }
+        }
+        Err(e) => tracing::error!("could not write audit log: {}", e),
+    }
+
+    response
+}
+
 #[allow(unused_mut, unused_variables)]
 pub async fn caching_middleware<B>(State(duration): State<Duration>, mut response: Response<B>) -> Response<B> {
     #[cfg(not(debug_assertions))]
@@ -1,15 +1,35 @@
+pub mod access_token;
+pub mod active_session;
 pub mod articles;
+pub mod audit_log;
 pub mod author;
 pub mod deafie;
 pub mod like;
+pub mod media_file;
 pub mod mention;
+pub mod mention_domain;
 pub mod note;
+pub mod outgoing_webmention;
+pub mod passkey;
+pub mod password_token;
 pub mod picture;
+pub mod queued_webmention;
+pub mod syndication;
 
+pub use access_token::*;
+pub use active_session::*;
 pub use articles::*;
+pub use audit_log::*;
 pub use author::*;
 pub use deafie::*;
 pub use like::*;
+pub use media_file::*;
 pub use mention::*;
+pub use mention_domain::*;
 pub use note::*;
+pub use outgoing_webmention::*;
+pub use passkey::*;
+pub use password_token::*;
 pub use picture::*;
+pub use queued_webmention::*;
+pub use syndication::*;
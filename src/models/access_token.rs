@@ -0,0 +1,40 @@
+use chrono::naive::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// An access token issued by our IndieAuth token endpoint; the token itself is only stored hashed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessToken {
+    pub id: i32,
+    pub token_hash: String,
+    pub author_id: i32,
+    pub client_id: String,
+    pub scope: String,
+    pub me: String,
+
+    pub last_used_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+    pub expires_at: NaiveDateTime,
+
+    pub inserted_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl AccessToken {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scope.split_whitespace().any(|s| s == scope)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorizationCode {
+    pub id: i32,
+    pub code_hash: String,
+    pub author_id: i32,
+    pub client_id: String,
+    pub redirect_uri: String,
+    pub scope: String,
+    pub code_challenge: String,
+    pub me: String,
+    pub expires_at: NaiveDateTime,
+    pub inserted_at: NaiveDateTime,
+}
@@ -0,0 +1,26 @@
+use chrono::naive::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Who a session belongs to and where it is used from; kept in the session data and mirrored into the
+/// columns of the `sessions` table, so that authors can see and revoke their sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub author_id: i32,
+    pub ip: String,
+    pub user_agent: Option<String>,
+    pub created_at: NaiveDateTime,
+    /// refreshed every few minutes only, so that not every request has to write the session
+    pub last_seen_at: NaiveDateTime,
+}
+
+/// A session of an author as listed on the sessions page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveSession {
+    /// the SHA-256 hash of the session ID; the ID itself is as good as the session cookie
+    pub key: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub inserted_at: Option<NaiveDateTime>,
+    pub last_seen_at: Option<NaiveDateTime>,
+    pub expiry_date: NaiveDateTime,
+}
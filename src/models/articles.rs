@@ -26,6 +26,8 @@ pub struct Article {
 
     pub posse_visibility: String,
     pub content_warning: Option<String>,
+    /// the services the post is syndicated to, all configured services when empty
+    pub posse_targets: Vec<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Validate, Default)]
@@ -57,4 +59,6 @@ pub struct NewArticle {
 
     pub posse_visibility: String,
     pub content_warning: Option<String>,
+    #[serde(default)]
+    pub posse_targets: Vec<String>,
 }
@@ -0,0 +1,11 @@
+#[derive(Debug, Clone, Default)]
+pub struct NewAuditLogEntry {
+    pub author_id: Option<i32>,
+    /// `login`, `login_failed`, `logout`, `create`, `update` or `delete`
+    pub action: String,
+    /// the path of the request or the URL of the post acted on
+    pub path: String,
+    /// the email address a failed login was tried with
+    pub email: Option<String>,
+    pub ip: Option<String>,
+}
@@ -1,6 +1,7 @@
 use axum_login::AuthUser;
 use chrono::naive::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use validator::Validate;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Author {
@@ -12,6 +13,54 @@ pub struct Author {
     pub remember_created_at: Option<NaiveDateTime>,
     pub inserted_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    /// the personal URL the author signs in with via IndieAuth
+    pub url: Option<String>,
+    /// base32 encoded TOTP secret; only used for logging in once `totp_enabled_at` is set
+    #[serde(skip_serializing)]
+    pub totp_secret: Option<String>,
+    pub totp_enabled_at: Option<NaiveDateTime>,
+    /// the last TOTP time step a code has been accepted for, so that codes can't be replayed
+    pub totp_last_step: Option<i64>,
+    /// consecutive failed logins; the account is locked for a while when there are too many
+    pub failed_login_attempts: i32,
+    pub locked_until: Option<NaiveDateTime>,
+    /// admins may invite and disable other authors
+    pub is_admin: bool,
+    pub disabled_at: Option<NaiveDateTime>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, Validate)]
+pub struct AuthorProfile {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    #[validate(email, length(max = 255))]
+    pub email: String,
+    #[validate(length(max = 255))]
+    pub avatar: String,
+}
+
+impl Author {
+    pub fn has_two_factor(&self) -> bool {
+        self.totp_enabled_at.is_some() && self.totp_secret.is_some()
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked_until
+            .is_some_and(|locked_until| locked_until > chrono::Utc::now().naive_utc())
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.disabled_at.is_some()
+    }
+
+    /// invited authors can't log in until they have chosen a password
+    pub fn is_invited(&self) -> bool {
+        self.encrypted_password.is_empty()
+    }
+
+    pub fn may_log_in(&self) -> bool {
+        !self.is_locked() && !self.is_disabled()
+    }
 }
 
 impl AuthUser for Author {
@@ -23,6 +23,8 @@ pub struct Deafie {
     pub updated_at: NaiveDateTime,
     pub posse_visibility: String,
     pub content_warning: Option<String>,
+    /// the services the post is syndicated to, all configured services when empty
+    pub posse_targets: Vec<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Validate, Default)]
@@ -47,6 +49,8 @@ pub struct NewDeafie {
 
     pub posse_visibility: String,
     pub content_warning: Option<String>,
+    #[serde(default)]
+    pub posse_targets: Vec<String>,
 }
 
 const THUMB_ASPEC_RATIO: f32 = 1.0;
@@ -15,6 +15,9 @@ pub struct Like {
     pub updated_at: NaiveDateTime,
 
     pub show_in_index: bool,
+
+    /// the services the post is syndicated to, all configured services when empty
+    pub posse_targets: Vec<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Validate, Default)]
@@ -28,4 +31,6 @@ pub struct NewLike {
     pub show_in_index: bool,
     pub inserted_at: Option<NaiveDateTime>,
     pub updated_at: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub posse_targets: Vec<String>,
 }
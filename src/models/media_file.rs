@@ -0,0 +1,30 @@
+use anyhow::Result;
+use chrono::naive::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::img::generate_versions;
+use crate::utils::media_base_path;
+
+/// A file uploaded via the micropub media endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaFile {
+    pub id: i32,
+    pub author_id: i32,
+
+    pub file_name: String,
+    pub content_type: String,
+    pub file_size: i32,
+
+    pub inserted_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl MediaFile {
+    pub fn is_image(&self) -> bool {
+        self.content_type.starts_with("image/") && self.content_type != "image/svg+xml"
+    }
+}
+
+pub fn generate_media_versions(media_file: &MediaFile) -> Result<()> {
+    generate_versions(&format!("{}/{}", media_base_path(), media_file.id), &media_file.file_name)
+}
@@ -1,5 +1,6 @@
 use chrono::naive::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use url::Url;
 use validator::Validate;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +16,7 @@ pub struct Mention {
     pub author_avatar: Option<String>,
 
     pub mention_type: String,
+    pub status: String,
 
     pub note_id: Option<i32>,
     pub picture_id: Option<i32>,
@@ -23,6 +25,10 @@ pub struct Mention {
     pub article_id: Option<i32>,
     pub articles_id: Option<i32>,
     pub deafie_id: Option<i32>,
+    pub like_id: Option<i32>,
+
+    /// the ID of a mention backfed from a syndicated copy, unique per service
+    pub remote_id: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Validate, Default)]
@@ -32,15 +38,76 @@ pub struct NewMention {
     #[validate(url, length(min = 3))]
     pub target_url: String,
 
-    pub title: String,
+    pub title: Option<String>,
+    pub excerpt: Option<String>,
     pub author: String,
+    pub author_url: Option<String>,
+    pub author_avatar: Option<String>,
     pub mention_type: String,
+    pub status: String,
 
     pub note_id: Option<i32>,
     pub picture_id: Option<i32>,
     pub article_id: Option<i32>,
     pub deafie_id: Option<i32>,
+    pub like_id: Option<i32>,
+
+    pub remote_id: Option<String>,
 
     pub inserted_at: Option<NaiveDateTime>,
     pub updated_at: Option<NaiveDateTime>,
 }
+
+impl Mention {
+    /// The author URL if it is safe to link to; it comes from the source document
+    pub fn author_link(&self) -> Option<&str> {
+        self.author_url.as_deref().filter(|url| is_web_url(url))
+    }
+
+    /// The avatar URL if it is safe to show; it comes from the source document
+    pub fn author_photo(&self) -> Option<&str> {
+        self.author_avatar.as_deref().filter(|url| is_web_url(url))
+    }
+}
+
+fn is_web_url(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+/// Mentions of a post, grouped the way the show pages render them
+#[derive(Debug, Clone, Default)]
+pub struct GroupedMentions {
+    pub likes: Vec<Mention>,
+    pub reposts: Vec<Mention>,
+    pub bookmarks: Vec<Mention>,
+    pub replies: Vec<Mention>,
+    pub mentions: Vec<Mention>,
+}
+
+impl GroupedMentions {
+    pub fn is_empty(&self) -> bool {
+        self.likes.is_empty()
+            && self.reposts.is_empty()
+            && self.bookmarks.is_empty()
+            && self.replies.is_empty()
+            && self.mentions.is_empty()
+    }
+}
+
+impl From<Vec<Mention>> for GroupedMentions {
+    fn from(mentions: Vec<Mention>) -> Self {
+        let mut grouped = GroupedMentions::default();
+
+        for mention in mentions {
+            match mention.mention_type.as_str() {
+                "like" => grouped.likes.push(mention),
+                "repost" => grouped.reposts.push(mention),
+                "bookmark" => grouped.bookmarks.push(mention),
+                "reply" => grouped.replies.push(mention),
+                _ => grouped.mentions.push(mention),
+            }
+        }
+
+        grouped
+    }
+}
@@ -0,0 +1,28 @@
+use chrono::naive::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MentionDomain {
+    pub id: i32,
+    pub domain: String,
+    pub rule: String,
+    pub inserted_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Validate, Default)]
+pub struct NewMentionDomain {
+    #[validate(length(min = 3, max = 255))]
+    pub domain: String,
+    #[validate(custom(function = "validate_rule"))]
+    pub rule: String,
+}
+
+fn validate_rule(rule: &str) -> Result<(), ValidationError> {
+    if rule != "approve" && rule != "block" {
+        return Err(ValidationError::new("rule is invalid"));
+    }
+
+    Ok(())
+}
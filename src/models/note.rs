@@ -18,6 +18,8 @@ pub struct Note {
     pub note_type: String,
     pub posse_visibility: String,
     pub content_warning: Option<String>,
+    /// the services the post is syndicated to, all configured services when empty
+    pub posse_targets: Vec<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Default, Validate)]
@@ -41,6 +43,8 @@ pub struct NewNote {
     pub updated_at: Option<NaiveDateTime>,
     pub posse_visibility: String,
     pub content_warning: Option<String>,
+    #[serde(default)]
+    pub posse_targets: Vec<String>,
 }
 
 fn validate_note_type(note_type: &str) -> Result<(), ValidationError> {
@@ -0,0 +1,19 @@
+use chrono::naive::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutgoingWebmention {
+    pub id: i32,
+    pub source_url: String,
+    pub target_url: String,
+    pub endpoint: Option<String>,
+
+    pub status: String,
+    pub response_code: Option<i32>,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_retry_at: NaiveDateTime,
+
+    pub inserted_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
@@ -0,0 +1,25 @@
+use chrono::naive::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// A WebAuthn credential an author can log in with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Passkey {
+    pub id: i32,
+    pub author_id: i32,
+    pub name: String,
+    /// base64url encoded credential ID
+    pub credential_id: String,
+    /// the serialized credential: public key, algorithm and signature counter
+    pub passkey: serde_json::Value,
+
+    pub last_used_at: Option<NaiveDateTime>,
+
+    pub inserted_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl Passkey {
+    pub fn credential(&self) -> Result<webauthn_rs::prelude::Passkey, serde_json::Error> {
+        serde_json::from_value(self.passkey.clone())
+    }
+}
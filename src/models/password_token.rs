@@ -0,0 +1,15 @@
+use chrono::naive::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// A single use token sent by email to choose a new password
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordToken {
+    pub id: i32,
+    pub author_id: i32,
+    pub token_hash: String,
+    /// `reset` for a forgotten password, `invitation` for a new author
+    pub purpose: String,
+    pub expires_at: NaiveDateTime,
+    pub used_at: Option<NaiveDateTime>,
+    pub inserted_at: NaiveDateTime,
+}
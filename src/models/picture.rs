@@ -1,11 +1,10 @@
 use anyhow::Result;
 use chrono::naive::NaiveDateTime;
-use image::{GenericImageView, imageops};
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
 use crate::utils::image_base_path;
-use crate::utils::img::{correct_orientation, get_orientation, read_exif};
+use crate::utils::img::generate_versions;
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Picture {
@@ -35,6 +34,8 @@ pub struct Picture {
 
     pub posse_visibility: String,
     pub content_warning: Option<String>,
+    /// the services the post is syndicated to, all configured services when empty
+    pub posse_targets: Vec<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Validate, Default)]
@@ -65,8 +66,11 @@ pub struct NewPicture {
 
     pub posse_visibility: String,
     pub content_warning: Option<String>,
+    #[serde(default)]
+    pub posse_targets: Vec<String>,
 }
 
+#[allow(dead_code)]
 #[derive(Deserialize, Serialize, Debug)]
 pub struct NewJsonPicture {
     #[serde(flatten)]
@@ -74,40 +78,6 @@ pub struct NewJsonPicture {
     pub picture: Option<String>,
 }
 
-const THUMB_ASPEC_RATIO: f32 = 1.0;
-
 pub fn generate_pictures(picture: &Picture) -> Result<()> {
-    let path = format!("{}/{}/original/{}", image_base_path(), picture.id, picture.image_file_name);
-    let exif = read_exif(&path)?;
-
-    let orientation = get_orientation(&exif);
-
-    let mut img = image::open(path)?;
-    img = correct_orientation(img, orientation);
-
-    let path = format!("{}/{}/large/{}", image_base_path(), picture.id, picture.image_file_name);
-    let new_img = img.resize(800, 600, imageops::FilterType::CatmullRom);
-    new_img.save(path)?;
-
-    let path = format!("{}/{}/thumbnail/{}", image_base_path(), picture.id, picture.image_file_name);
-    let (width, height) = img.dimensions();
-    let aspect_ratio = width as f32 / height as f32;
-
-    let img = if aspect_ratio != THUMB_ASPEC_RATIO {
-        let mid_x = width / 2;
-        let mid_y = height / 2;
-
-        if width > height {
-            img.crop(mid_x - (height / 2), mid_y - (height / 2), height, height)
-        } else {
-            img.crop(mid_x - (width / 2), mid_y - (width / 2), width, width)
-        }
-    } else {
-        img
-    };
-
-    let new_img = img.resize_exact(600, 600, imageops::FilterType::CatmullRom);
-    new_img.save(path)?;
-
-    Ok(())
+    generate_versions(&format!("{}/{}", image_base_path(), picture.id), &picture.image_file_name)
 }
@@ -0,0 +1,21 @@
+use chrono::naive::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedWebmention {
+    pub id: i32,
+    pub source_url: String,
+    pub target_url: String,
+    /// the random part of the public status URL, the ID would allow reading all queued webmentions
+    pub status_token: String,
+
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_try_at: NaiveDateTime,
+
+    pub mention_id: Option<i32>,
+
+    pub inserted_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
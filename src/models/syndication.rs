@@ -0,0 +1,40 @@
+use chrono::naive::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// A copy of a post on another service, e.g. the Mastodon status created by POSSE
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Syndication {
+    pub id: i32,
+    /// the path segment of the post type: `notes`, `articles`, `pictures`, `deafies` or `likes`
+    pub object_type: String,
+    pub object_id: i32,
+    pub service: String,
+    /// `post` for a copy of the post; `like` and `repost` for a favourite or reblog of the status the post
+    /// refers to, the URL points to that status then
+    pub syndication_type: String,
+    pub url: String,
+    /// the ID of the copy at the service, if it has one
+    pub remote_id: Option<String>,
+
+    /// why the last attempt to update or delete the copy failed
+    pub last_error: Option<String>,
+    pub failed_at: Option<NaiveDateTime>,
+
+    pub inserted_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl Syndication {
+    pub fn service_name(&self) -> &str {
+        match self.service.as_str() {
+            "mastodon" => "Mastodon",
+            "bluesky" => "Bluesky",
+            service => service,
+        }
+    }
+
+    /// `false` for favourites and reblogs, which aren't copies of the post
+    pub fn is_copy(&self) -> bool {
+        self.syndication_type == "post"
+    }
+}
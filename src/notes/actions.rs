@@ -74,8 +74,8 @@ pub async fn create_note(
         let note = query_as!(
             Note,
             r#"
-            INSERT INTO notes (author_id, title, note_type, in_reply_to, lang, posse, show_in_index, content, inserted_at, updated_at, posse_visibility, content_warning)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            INSERT INTO notes (author_id, title, note_type, in_reply_to, lang, posse, show_in_index, content, inserted_at, updated_at, posse_visibility, content_warning, posse_targets)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
             RETURNING *
             "#,
             data.author_id,
@@ -89,7 +89,8 @@ pub async fn create_note(
             data.inserted_at,
             data.updated_at,
             data.posse_visibility,
-            data.content_warning
+            data.content_warning,
+            &data.posse_targets
         )
         .fetch_one(conn)
         .await?;
@@ -122,8 +123,9 @@ pub async fn update_note(
             Note,
             r#"
             UPDATE notes
-            SET title = $1, lang = $2, in_reply_to = $3, posse = $4, show_in_index = $5, content = $6, updated_at = $7
-            WHERE id = $8
+            SET title = $1, lang = $2, in_reply_to = $3, posse = $4, show_in_index = $5, content = $6, updated_at = $7,
+                posse_targets = $8
+            WHERE id = $9
             RETURNING *
             "#,
             data.title,
@@ -133,6 +135,7 @@ pub async fn update_note(
             data.show_in_index,
             data.content,
             now,
+            &data.posse_targets,
             note_id
         )
         .fetch_one(conn)
@@ -1,12 +1,16 @@
 use askama::Template;
-use axum::extract::{Form, Path, State};
+use axum::extract::{Path, State};
 use axum::http::StatusCode;
 use axum::response::{Html, IntoResponse, Redirect, Response};
+use axum_extra::extract::Form;
 
 use super::actions;
 use crate::errors::AppError;
 use crate::models::{NewNote, Note};
+use crate::posse::syndicate::{syndicate_note, update_syndicated_note};
+use crate::posse::targets_added;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
 use crate::webmentions::send::send_mentions;
 use crate::{AppState, AuthSession, utils as filters};
 
@@ -50,6 +54,7 @@ pub async fn edit(State(state): State<AppState>, Path(id): Path<i32>) -> Result<
             updated_at: None,
             posse_visibility: note.posse_visibility.clone(),
             content_warning: note.content_warning.clone(),
+            posse_targets: note.posse_targets.clone(),
         },
 
         note,
@@ -77,14 +82,17 @@ pub async fn update(
     data.author_id = Some(user.id);
 
     match actions::update_note(note.id, &data, &mut conn).await {
-        Ok(note) => {
-            state.note_cache.insert(note.id, note.clone()).await;
-            let uri = note_uri(&note);
-
-            tokio::task::spawn_blocking(move || {
-                let uri = note_uri(&note);
-                let _ = send_mentions(&uri);
-            });
+        Ok(updated_note) => {
+            state.note_cache.insert(updated_note.id, updated_note.clone()).await;
+            let uri = note_uri(&updated_note);
+
+            tokio::task::spawn(send_mentions(uri.clone(), state.pool.clone()));
+
+            if targets_added(updated_note.posse, &updated_note.posse_targets, note.posse, &note.posse_targets) {
+                tokio::task::spawn(syndicate_note(updated_note.clone(), state.pool.clone()));
+            }
+
+            tokio::task::spawn(update_syndicated_note(updated_note, state.pool.clone()));
 
             Ok(Redirect::to(&uri).into_response())
         }
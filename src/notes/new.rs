@@ -1,13 +1,15 @@
 use askama::Template;
-use axum::extract::{Form, State};
+use axum::extract::State;
 use axum::http::StatusCode;
 use axum::response::{Html, IntoResponse, Redirect, Response};
+use axum_extra::extract::Form;
 
 use super::actions;
 use crate::errors::AppError;
 use crate::models::NewNote;
-use crate::posse::mastodon::post_note;
+use crate::posse::syndicate::syndicate_note;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
 use crate::webmentions::send::send_mentions;
 use crate::{AppState, AuthSession, utils as filters};
 
@@ -64,16 +66,10 @@ pub async fn create(
             let uri = note_uri(&note);
 
             if note.posse {
-                let note_ = note.clone();
-                tokio::task::spawn(async move {
-                    let _ = post_note(&note_).await;
-                });
+                tokio::task::spawn(syndicate_note(note.clone(), state.pool.clone()));
             }
 
-            tokio::task::spawn_blocking(move || {
-                let uri = note_uri(&note);
-                let _ = send_mentions(&uri);
-            });
+            tokio::task::spawn(send_mentions(uri.clone(), state.pool.clone()));
 
             Ok(Redirect::to(&uri).into_response())
         }
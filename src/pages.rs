@@ -8,6 +8,7 @@ use chrono::Duration;
 use crate::errors::AppError;
 use crate::middleware::caching_middleware;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
 use crate::{AppRouter, AuthSession};
 
 pub mod actions;
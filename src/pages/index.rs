@@ -12,6 +12,7 @@ use crate::deafies::actions as deafie_actions;
 use crate::errors::AppError;
 use crate::models::{Article, Deafie};
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
 use crate::{AppState, AuthSession, utils as filters};
 
 #[derive(Template)]
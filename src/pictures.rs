@@ -1,6 +1,6 @@
 use axum::Router;
 use axum::extract::DefaultBodyLimit;
-use axum::middleware::map_response_with_state;
+use axum::middleware::{from_fn, map_response_with_state};
 use axum::routing::{get, post};
 use axum_login::login_required;
 use axum_typed_multipart::{FieldData, TryFromMultipart};
@@ -8,7 +8,7 @@ use chrono::Duration;
 use serde::{Deserialize, Serialize};
 use tempfile::NamedTempFile;
 
-use crate::middleware::caching_middleware;
+use crate::middleware::{caching_middleware, two_factor_middleware};
 use crate::store::Store;
 use crate::{AppRouter, MAX_UPLOAD_SIZE};
 
@@ -49,6 +49,7 @@ pub struct PictureData {
     #[form_data(default)]
     pub posse: bool,
     pub posse_visibility: String,
+    pub posse_targets: Vec<String>,
 
     #[form_data(default)]
     pub show_in_index: bool,
@@ -67,6 +68,7 @@ pub fn configure(app: AppRouter) -> AppRouter {
         .route("/pictures/{id}", post(edit::update))
         .route("/pictures/{id}/delete", post(delete::delete))
         .layer(DefaultBodyLimit::max(MAX_UPLOAD_SIZE))
+        .route_layer(from_fn(two_factor_middleware))
         .route_layer(login_required!(Store, login_url = "/login"));
 
     let caching_router: AppRouter = Router::new()
@@ -90,8 +90,8 @@ pub async fn create_picture(
             INSERT INTO pictures
                 (author_id, in_reply_to, image_file_name, image_content_type, image_file_size, image_updated_at,
                  inserted_at, updated_at, title, posse, show_in_index, content, lang, alt, posse_visibility,
-                 content_warning)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+                 content_warning, posse_targets)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
             RETURNING *
         "#,
         data.author_id,
@@ -109,7 +109,8 @@ pub async fn create_picture(
         data.lang,
         data.alt,
         data.posse_visibility,
-        data.content_warning
+        data.content_warning,
+        &data.posse_targets
     )
     .fetch_one(&mut *tx)
     .await?;
@@ -176,8 +177,9 @@ pub async fn update_picture(
               lang = $10,
               alt = $11,
               posse_visibility = $12,
-              content_warning = $13
-            WHERE id = $14
+              content_warning = $13,
+              posse_targets = $14
+            WHERE id = $15
             RETURNING *
         "#,
         data.in_reply_to.or(picture.in_reply_to),
@@ -193,6 +195,7 @@ pub async fn update_picture(
         data.alt,
         data.posse_visibility,
         data.content_warning.or(picture.content_warning),
+        &data.posse_targets,
         picture.id
     )
     .fetch_one(conn)
@@ -4,7 +4,10 @@ use axum::response::{IntoResponse, Redirect};
 use super::actions;
 use crate::AppState;
 use crate::errors::AppError;
+use crate::posse::syndicate::delete_syndicated;
 use crate::uri_helpers::*;
+use crate::webmentions::actions::{ObjectType, create_tombstone};
+use crate::webmentions::send::send_mentions_for_deleted;
 
 pub async fn delete(State(state): State<AppState>, Path(id): Path<i32>) -> Result<impl IntoResponse, AppError> {
     let mut conn = state.pool.acquire().await?;
@@ -13,5 +16,10 @@ pub async fn delete(State(state): State<AppState>, Path(id): Path<i32>) -> Resul
     actions::delete_picture(&picture, &mut conn).await?;
     state.picture_cache.remove(&picture.id).await;
 
+    let uri = picture_uri(&picture);
+    create_tombstone(&uri, &mut conn).await?;
+    tokio::task::spawn(send_mentions_for_deleted(uri, state.pool.clone()));
+    tokio::task::spawn(delete_syndicated(ObjectType::Picture, picture.id, state.pool.clone()));
+
     Ok(Redirect::to(&pictures_uri()))
 }
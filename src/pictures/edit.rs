@@ -7,7 +7,10 @@ use axum_typed_multipart::TypedMultipart;
 use super::{PictureData, actions};
 use crate::errors::AppError;
 use crate::models::{NewPicture, Picture, generate_pictures};
+use crate::posse::syndicate::{syndicate_picture, update_syndicated_picture};
+use crate::posse::targets_added;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
 use crate::webmentions::send::send_mentions;
 use crate::{AppState, AuthSession, utils as filters};
 
@@ -50,6 +53,7 @@ pub async fn edit(State(state): State<AppState>, Path(id): Path<i32>) -> Result<
             content: Some(picture.content.clone()),
             posse_visibility: picture.posse_visibility.clone(),
             content_warning: picture.content_warning.clone(),
+            posse_targets: picture.posse_targets.clone(),
             ..Default::default()
         },
 
@@ -117,18 +121,34 @@ pub async fn update(
 
         posse_visibility: data.posse_visibility,
         content_warning: data.content_warning,
+        posse_targets: data.posse_targets,
         ..Default::default()
     };
 
     match actions::update_picture(&picture, &values, f, &mut conn).await {
-        Ok(picture) => {
-            state.picture_cache.insert(picture.id, picture.clone()).await;
-            let uri = picture_uri(&picture);
-
+        Ok(updated_picture) => {
+            state
+                .picture_cache
+                .insert(updated_picture.id, updated_picture.clone())
+                .await;
+            let uri = picture_uri(&updated_picture);
+            let added = targets_added(
+                updated_picture.posse,
+                &updated_picture.posse_targets,
+                picture.posse,
+                &picture.posse_targets,
+            );
+
+            let pool = state.pool.clone();
             tokio::task::spawn_blocking(move || {
-                let uri = picture_uri(&picture);
-                let _ = generate_pictures(&picture);
-                let _ = send_mentions(&uri);
+                let _ = generate_pictures(&updated_picture);
+                tokio::task::spawn(send_mentions(picture_uri(&updated_picture), pool.clone()));
+
+                if added {
+                    tokio::task::spawn(syndicate_picture(updated_picture.clone(), pool.clone()));
+                }
+
+                tokio::task::spawn(update_syndicated_picture(updated_picture, pool));
             });
 
             Ok(Redirect::to(&uri).into_response())
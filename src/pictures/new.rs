@@ -7,8 +7,9 @@ use axum_typed_multipart::TypedMultipart;
 use super::{PictureData, actions};
 use crate::errors::AppError;
 use crate::models::{NewPicture, generate_pictures};
-use crate::posse::mastodon::post_picture;
+use crate::posse::syndicate::syndicate_picture;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
 use crate::webmentions::send::send_mentions;
 use crate::{AppState, AuthSession, utils as filters};
 
@@ -78,6 +79,7 @@ pub async fn create(
 
         posse_visibility: data.posse_visibility,
         content_warning: data.content_warning,
+        posse_targets: data.posse_targets,
         ..Default::default()
     };
 
@@ -93,15 +95,13 @@ pub async fn create(
         Ok(picture) => {
             let uri = picture_uri(&picture);
 
+            let pool = state.pool.clone();
             tokio::task::spawn_blocking(move || {
-                let uri = picture_uri(&picture);
                 let _ = generate_pictures(&picture);
-                let _ = send_mentions(&uri);
+                tokio::task::spawn(send_mentions(picture_uri(&picture), pool.clone()));
 
                 if picture.posse {
-                    tokio::task::spawn(async move {
-                        let _ = post_picture(&picture).await;
-                    });
+                    tokio::task::spawn(syndicate_picture(picture, pool));
                 }
             });
 
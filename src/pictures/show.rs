@@ -10,9 +10,12 @@ use tokio_util::io::ReaderStream;
 
 use super::{ImageTypes, TypeParams, actions};
 use crate::errors::AppError;
-use crate::models::Picture;
+use crate::models::{GroupedMentions, Picture, Syndication};
+use crate::posse::actions::list_syndications;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
 use crate::utils::image_base_path;
+use crate::webmentions::actions::{ObjectType, list_mentions};
 use crate::{AppState, AuthSession, utils as filters};
 
 #[derive(Template)]
@@ -28,6 +31,8 @@ pub struct Show<'a> {
     picture: Picture,
     index: bool,
     atom: bool,
+    mentions: Option<GroupedMentions>,
+    syndications: Vec<Syndication>,
     home: bool,
     picture_type: &'a str,
 }
@@ -58,6 +63,9 @@ pub async fn show_post(state: AppState, id: i32, logged_in: bool) -> Result<Resp
     let mut conn = state.pool.acquire().await?;
     let picture = get_image(id, &state, &mut conn).await?;
 
+    let mentions = list_mentions(ObjectType::Picture, picture.id, &mut conn).await?;
+    let syndications = list_syndications(ObjectType::Picture, picture.id, &mut conn).await?;
+
     let html = Show {
         lang: "en",
         title: Some(picture.title.clone()),
@@ -68,6 +76,8 @@ pub async fn show_post(state: AppState, id: i32, logged_in: bool) -> Result<Resp
         picture,
         index: false,
         atom: false,
+        mentions: Some(mentions.into()),
+        syndications,
         home: false,
         picture_type: "large",
     }
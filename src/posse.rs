@@ -1 +1,93 @@
+use std::env;
+use std::sync::OnceLock;
+
+use anyhow::{Result, anyhow};
+use mastodon_async::Mastodon;
+
+use self::bluesky::BlueskySyndicator;
+use self::mastodon::MastodonSyndicator;
+use self::syndicator::Syndicator;
+
+pub mod actions;
+pub mod backfeed;
+pub mod bluesky;
 pub mod mastodon;
+#[cfg(test)]
+mod mock_bluesky;
+#[cfg(test)]
+mod mock_mastodon;
+pub mod syndicate;
+pub mod syndicator;
+
+static SYNDICATORS: OnceLock<Vec<Box<dyn Syndicator>>> = OnceLock::new();
+/// the client of the Mastodon syndicator, shared with the backfeed
+static MASTODON_CLIENT: OnceLock<Mastodon> = OnceLock::new();
+
+/// Sets up the services configured in the environment: Mastodon when `MASTODON_URL` is set, Bluesky when
+/// `BLUESKY_IDENTIFIER` is set. Fails when the credentials of a configured service don't work.
+pub async fn configure() -> Result<()> {
+    let mut syndicators: Vec<Box<dyn Syndicator>> = Vec::new();
+
+    if env::var("MASTODON_URL").is_ok() {
+        let syndicator = MastodonSyndicator::configure().await?;
+        let _ = MASTODON_CLIENT.set(syndicator.client().clone());
+        syndicators.push(Box::new(syndicator));
+    }
+
+    if env::var("BLUESKY_IDENTIFIER").is_ok() {
+        syndicators.push(Box::new(BlueskySyndicator::configure().await?));
+    }
+
+    let _ = SYNDICATORS.set(syndicators);
+
+    Ok(())
+}
+
+/// The verified client of the Mastodon syndicator, `None` when Mastodon isn't configured
+pub fn mastodon_client() -> Option<&'static Mastodon> {
+    MASTODON_CLIENT.get()
+}
+
+/// An environment variable a configured service can't do without
+fn required_env(name: &str) -> Result<String> {
+    env::var(name).map_err(|_| anyhow!("env variable {} not set", name))
+}
+
+pub fn syndicators() -> &'static [Box<dyn Syndicator>] {
+    SYNDICATORS.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+pub fn syndicator(service: &str) -> Option<&'static dyn Syndicator> {
+    syndicators()
+        .iter()
+        .find(|syndicator| syndicator.service() == service)
+        .map(Box::as_ref)
+}
+
+/// Whether a post with the `posse_targets` selects the service; a post without a selection selects all of them
+pub fn is_target(posse_targets: &[String], service: &str) -> bool {
+    posse_targets.is_empty() || posse_targets.iter().any(|target| target == service)
+}
+
+/// The services a post is syndicated to: none without POSSE, the configured services the post selected otherwise
+pub fn targets(posse: bool, posse_targets: &[String]) -> Vec<&'static dyn Syndicator> {
+    if !posse {
+        return Vec::new();
+    }
+
+    syndicators()
+        .iter()
+        .map(Box::as_ref)
+        .filter(|syndicator| is_target(posse_targets, syndicator.service()))
+        .collect()
+}
+
+/// Whether an edit added services to syndicate the post to; the post isn't syndicated again to the services
+/// it was syndicated to before
+pub fn targets_added(posse: bool, posse_targets: &[String], previous_posse: bool, previous_targets: &[String]) -> bool {
+    let previous = targets(previous_posse, previous_targets);
+
+    targets(posse, posse_targets)
+        .iter()
+        .any(|syndicator| !previous.iter().any(|other| other.service() == syndicator.service()))
+}
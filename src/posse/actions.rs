@@ -0,0 +1,93 @@
+use chrono::{NaiveDateTime, Utc};
+use sqlx::{PgConnection, query, query_as};
+
+use crate::models::Syndication;
+use crate::webmentions::actions::ObjectType;
+
+/// Records the copy of a post on a service; a post has at most one copy per service, so a newer copy
+/// replaces the older one
+pub async fn upsert_syndication(
+    object_type: ObjectType,
+    object_id: i32,
+    service: &str,
+    syndication_type: &str,
+    url: &str,
+    remote_id: Option<&str>,
+    conn: &mut PgConnection,
+) -> Result<Syndication, sqlx::Error> {
+    let now = Utc::now().naive_utc();
+
+    query_as!(
+        Syndication,
+        r#"
+            INSERT INTO syndications (object_type, object_id, service, syndication_type, url, remote_id, inserted_at,
+              updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (object_type, object_id, service)
+              DO UPDATE SET syndication_type = EXCLUDED.syndication_type, url = EXCLUDED.url,
+                remote_id = EXCLUDED.remote_id, last_error = NULL, failed_at = NULL, updated_at = EXCLUDED.updated_at
+            RETURNING *
+        "#,
+        object_type.as_str(),
+        object_id,
+        service,
+        syndication_type,
+        url,
+        remote_id,
+        now,
+        now
+    )
+    .fetch_one(conn)
+    .await
+}
+
+pub async fn list_syndications(
+    object_type: ObjectType,
+    object_id: i32,
+    conn: &mut PgConnection,
+) -> Result<Vec<Syndication>, sqlx::Error> {
+    query_as!(
+        Syndication,
+        "SELECT * FROM syndications WHERE object_type = $1 AND object_id = $2 ORDER BY service",
+        object_type.as_str(),
+        object_id
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Keeps the reason why the copy couldn't be updated or deleted, so it can be shown and fixed by hand
+pub async fn record_syndication_error(id: i32, error: &str, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    query!("UPDATE syndications SET last_error = $1, failed_at = $2 WHERE id = $3", error, Utc::now().naive_utc(), id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_syndication(id: i32, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    query!("DELETE FROM syndications WHERE id = $1", id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Lists the copies of posts on a service created since `since`, the ones still worth polling for interactions
+pub async fn list_recent_syndications(
+    service: &str,
+    since: NaiveDateTime,
+    conn: &mut PgConnection,
+) -> Result<Vec<Syndication>, sqlx::Error> {
+    query_as!(
+        Syndication,
+        r#"
+            SELECT * FROM syndications
+            WHERE service = $1 AND syndication_type = 'post' AND inserted_at >= $2
+            ORDER BY inserted_at DESC
+        "#,
+        service,
+        since
+    )
+    .fetch_all(conn)
+    .await
+}
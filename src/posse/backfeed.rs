@@ -0,0 +1,454 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::Utc;
+use mastodon_async::entities::prelude::Account;
+use mastodon_async::page::Page;
+use mastodon_async::prelude::*;
+use sqlx::{PgConnection, PgPool};
+use url::Url;
+use visdom::Vis;
+
+use super::actions::list_recent_syndications;
+use super::mastodon::SERVICE;
+use crate::mentions::actions::{domain_rule, set_mention_status};
+use crate::models::{Mention, Syndication};
+use crate::uri_helpers::*;
+use crate::webmentions::actions::{
+    ObjectType, create_mention, get_mention_by_remote_id, list_backfed_mentions, update_mention,
+};
+use crate::webmentions::mail_sender;
+use crate::webmentions::mf2::{SourceData, truncate};
+use crate::{articles, deafies, likes, notes, pictures};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// copies older than this aren't polled any more; nearly all interactions happen in the first days
+const MAX_AGE_DAYS: i64 = 30;
+
+/// An interaction with a copy, the counterpart of a received webmention
+struct Interaction {
+    source_url: String,
+    source: SourceData,
+}
+
+/// Polls the Mastodon copies of our posts for favourites, reblogs and replies and stores them as mentions
+/// of the original post
+pub async fn run(mastodon: Mastodon, pool: PgPool) {
+    loop {
+        let result = match pool.acquire().await {
+            Ok(mut conn) => backfeed(&mastodon, &mut conn).await,
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = result {
+            tracing::error!("error backfeeding mastodon interactions: {}", e);
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn backfeed(mastodon: &Mastodon, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    let since = Utc::now().naive_utc() - chrono::Duration::days(MAX_AGE_DAYS);
+
+    for syndication in list_recent_syndications(SERVICE, since, conn).await? {
+        backfeed_copy(mastodon, &syndication, conn).await?;
+    }
+
+    Ok(())
+}
+
+/// Stores the interactions with a copy; the ones which have been withdrawn since, by unfavouriting,
+/// unreblogging or deleting the reply, are removed like the mentions of a deleted source
+async fn backfeed_copy(
+    mastodon: &Mastodon,
+    syndication: &Syndication,
+    conn: &mut PgConnection,
+) -> Result<(), sqlx::Error> {
+    let Some(remote_id) = &syndication.remote_id else {
+        return Ok(());
+    };
+    let Ok(object_type) = ObjectType::from_str(&syndication.object_type) else {
+        return Ok(());
+    };
+
+    // the copy of a post which is gone is left alone
+    let target_url = match target_uri(&object_type, syndication.object_id, conn).await {
+        Ok(url) => url,
+        Err(sqlx::Error::RowNotFound) => return Ok(()),
+        Err(e) => return Err(e),
+    };
+
+    let interactions = match fetch_interactions(mastodon, &StatusId::new(remote_id)).await {
+        Ok(interactions) => interactions,
+        Err(e) => {
+            tracing::warn!("could not fetch the interactions with {}: {}", syndication.url, e);
+            return Ok(());
+        }
+    };
+
+    let remote_ids = interactions
+        .iter()
+        .filter_map(|interaction| interaction.source.remote_id.clone())
+        .collect::<Vec<_>>();
+
+    for interaction in interactions {
+        store_interaction(syndication, &object_type, &target_url, interaction, conn).await?;
+    }
+
+    for mention in list_backfed_mentions(&object_type, syndication.object_id, SERVICE, conn).await? {
+        if mention.remote_id.as_ref().is_some_and(|id| !remote_ids.contains(id)) {
+            set_mention_status(mention.id, "deleted", conn).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn target_uri(object_type: &ObjectType, id: i32, conn: &mut PgConnection) -> Result<String, sqlx::Error> {
+    let uri = match object_type {
+        ObjectType::Article => article_uri(&articles::actions::get_article(id, false, conn).await?),
+        ObjectType::Note => note_uri(&notes::actions::get_note(id, conn).await?),
+        ObjectType::Picture => picture_uri(&pictures::actions::get_picture(id, conn).await?),
+        ObjectType::Like => like_uri(&likes::actions::get_like(id, conn).await?),
+        ObjectType::Deafie => deafie_uri(&deafies::actions::get_deafie(id, false, conn).await?),
+    };
+
+    Ok(uri)
+}
+
+async fn fetch_interactions(mastodon: &Mastodon, id: &StatusId) -> mastodon_async::Result<Vec<Interaction>> {
+    let status = mastodon.get_status(id).await?;
+    let own_account = &status.account.id;
+    let mut interactions = Vec::new();
+
+    for account in all_accounts(mastodon.favourited_by(id.as_ref()).await?).await? {
+        if &account.id != own_account {
+            interactions.push(account_interaction(&status, account, "like", "favourited"));
+        }
+    }
+
+    for account in all_accounts(mastodon.reblogged_by(id.as_ref()).await?).await? {
+        if &account.id != own_account {
+            interactions.push(account_interaction(&status, account, "repost", "reblogged"));
+        }
+    }
+
+    // replies to replies are part of the conversation as well
+    for reply in mastodon.get_context(id).await?.descendants {
+        if &reply.account.id != own_account {
+            interactions.push(reply_interaction(reply));
+        }
+    }
+
+    Ok(interactions)
+}
+
+async fn all_accounts(mut page: Page<Account>) -> mastodon_async::Result<Vec<Account>> {
+    let mut accounts = std::mem::take(&mut page.initial_items);
+
+    while let Some(items) = page.next_page().await? {
+        accounts.extend(items);
+    }
+
+    Ok(accounts)
+}
+
+/// A favourite or reblog has no URL of its own, so the source URL is made unique with a fragment
+fn account_interaction(status: &Status, account: Account, mention_type: &str, verb: &str) -> Interaction {
+    let remote_id = format!("{}:{}:{}:{}", SERVICE, verb, status.id.as_ref(), account.id.as_ref());
+
+    Interaction {
+        source_url: format!("{}#{}-{}", account.url, verb, status.id.as_ref()),
+        source: SourceData {
+            mention_type: mention_type.to_owned(),
+            remote_id: Some(remote_id),
+            ..author_data(&account)
+        },
+    }
+}
+
+fn reply_interaction(reply: Status) -> Interaction {
+    let excerpt = plain_text(&reply.content);
+
+    Interaction {
+        source_url: reply.url.clone().unwrap_or_else(|| reply.uri.clone()),
+        source: SourceData {
+            mention_type: "reply".to_owned(),
+            excerpt: Some(truncate(&excerpt)).filter(|excerpt| !excerpt.is_empty()),
+            remote_id: Some(format!("{}:status:{}", SERVICE, reply.id.as_ref())),
+            ..author_data(&reply.account)
+        },
+    }
+}
+
+/// Mastodon delivers the content of a status as HTML paragraphs
+fn plain_text(html: &str) -> String {
+    let html = html.replace("<br", " <br");
+    let Ok(tree) = Vis::load(&html) else {
+        return String::new();
+    };
+
+    let text = tree.children("").map(|_, paragraph| paragraph.text()).join(" ");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn author_data(account: &Account) -> SourceData {
+    let name = if account.display_name.is_empty() {
+        &account.acct
+    } else {
+        &account.display_name
+    };
+
+    SourceData {
+        author: truncate(name),
+        author_url: Some(account.url.clone()).filter(|url| url.len() <= 255),
+        author_avatar: Some(account.avatar.clone()).filter(|url| !url.is_empty() && url.len() <= 255),
+        ..Default::default()
+    }
+}
+
+/// Creates the mention for an interaction seen for the first time; known interactions are only updated when
+/// the author changed their profile or the reply has been edited, keeping the moderation decision. An
+/// interaction which had been withdrawn and is back is moderated like a new one.
+async fn store_interaction(
+    syndication: &Syndication,
+    object_type: &ObjectType,
+    target_url: &str,
+    interaction: Interaction,
+    conn: &mut PgConnection,
+) -> Result<(), sqlx::Error> {
+    let Interaction { source_url, source } = interaction;
+    let remote_id = source.remote_id.clone().unwrap_or_default();
+
+    let existing = get_mention_by_remote_id(&remote_id, conn).await?;
+
+    if let Some(existing) = existing.as_ref().filter(|existing| existing.status != "deleted") {
+        if changed(existing, &source) {
+            update_mention(existing.id, source, &existing.status, conn).await?;
+        }
+
+        return Ok(());
+    }
+
+    if source_url.len() > 255 {
+        return Ok(());
+    }
+
+    let host = Url::parse(&source_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_owned))
+        .unwrap_or_default();
+
+    let status = match domain_rule(&host, conn).await?.as_deref() {
+        Some("block") => return Ok(()),
+        Some("approve") => "approved",
+        _ => "pending",
+    };
+
+    if let Some(existing) = existing {
+        update_mention(existing.id, source, status, conn).await?;
+        return Ok(());
+    }
+
+    let mention = create_mention(
+        source_url,
+        target_url.to_owned(),
+        object_type.clone(),
+        syndication.object_id,
+        source,
+        status,
+        conn,
+    )
+    .await?;
+
+    // favourites and reblogs would flood the inbox, replies are worth a mail
+    if mention.mention_type == "reply" {
+        tokio::task::spawn_blocking(move || mail_sender::send_mail(mention));
+    }
+
+    Ok(())
+}
+
+fn changed(mention: &Mention, source: &SourceData) -> bool {
+    mention.excerpt != source.excerpt
+        || mention.author != source.author
+        || mention.author_url != source.author_url
+        || mention.author_avatar != source.author_avatar
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::Connection;
+
+    use super::*;
+    use crate::posse::actions::upsert_syndication;
+    use crate::posse::mock_mastodon::{MockMastodon, account, status};
+
+    /// A copy, status 100 of ck, favourited by alice and ck, reblogged by bob and replied to by carol and ck
+    async fn mock_with_copy() -> MockMastodon {
+        let mock = MockMastodon::start().await;
+        let ck = account("1", "ck");
+        mock.add_status("100", ck.clone(), "A note (https://wwwtech.example/notes/1)");
+
+        let mut state = mock.state.lock().unwrap();
+        state
+            .favourited_by
+            .insert("100".to_owned(), vec![account("2", "alice"), ck.clone()]);
+        state.reblogged_by.insert("100".to_owned(), vec![account("3", "bob")]);
+        state.descendants.insert(
+            "100".to_owned(),
+            vec![
+                status("900", account("4", "carol"), "nice post!"),
+                status("901", ck, "thanks"),
+            ],
+        );
+        drop(state);
+
+        mock
+    }
+
+    #[tokio::test]
+    async fn fetches_favourites_reblogs_and_replies_of_others() {
+        let mock = mock_with_copy().await;
+
+        let interactions = fetch_interactions(&mock.mastodon, &StatusId::new("100")).await.unwrap();
+        let found = interactions
+            .iter()
+            .map(|interaction| {
+                (
+                    interaction.source.mention_type.as_str(),
+                    interaction.source.author.as_str(),
+                    interaction.source.remote_id.as_deref().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            found,
+            vec![
+                ("like", "alice", "mastodon:favourited:100:2"),
+                ("repost", "bob", "mastodon:reblogged:100:3"),
+                ("reply", "carol", "mastodon:status:900"),
+            ]
+        );
+
+        assert_eq!(interactions[0].source_url, "https://mastodon.example/@alice#favourited-100");
+        assert_eq!(interactions[2].source_url, "https://mastodon.example/@carol/900");
+        assert_eq!(interactions[2].source.excerpt.as_deref(), Some("nice post!"));
+    }
+
+    /// Creates a note with a copy in a transaction which is never committed
+    async fn note_with_copy(conn: &mut PgConnection) -> Syndication {
+        let author_id: i32 = sqlx::query_scalar(
+            "INSERT INTO authors (name, email, avatar, encrypted_password, inserted_at, updated_at) \
+             VALUES ('Backfeed', 'backfeed-test@wwwtech.example', '', '', now(), now()) RETURNING id",
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .unwrap();
+
+        let note_id: i32 = sqlx::query_scalar(
+            "INSERT INTO notes (author_id, content, title, lang, note_type, posse_visibility, inserted_at, updated_at) \
+             VALUES ($1, 'A note', 'A note', 'en', 'note', 'public', now(), now()) RETURNING id",
+        )
+        .bind(author_id)
+        .fetch_one(&mut *conn)
+        .await
+        .unwrap();
+
+        let url = "https://mastodon.example/@ck/100";
+        upsert_syndication(ObjectType::Note, note_id, SERVICE, "post", url, Some("100"), conn)
+            .await
+            .unwrap()
+    }
+
+    async fn backfed(syndication: &Syndication, conn: &mut PgConnection) -> Vec<(String, String)> {
+        list_backfed_mentions(&ObjectType::Note, syndication.object_id, SERVICE, conn)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|mention| (mention.remote_id.unwrap(), mention.status))
+            .collect()
+    }
+
+    /// Connects to the database of the environment the app would run in
+    async fn connect() -> PgConnection {
+        dotenvy::dotenv().ok();
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL has to point to a database with the schema");
+        PgConnection::connect(&url).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL and BASE_URI, pointing to a database with the schema"]
+    async fn stores_each_interaction_once() {
+        let mock = mock_with_copy().await;
+        let mut conn = connect().await;
+        let mut tx = conn.begin().await.unwrap();
+        let syndication = note_with_copy(&mut tx).await;
+
+        backfeed_copy(&mock.mastodon, &syndication, &mut tx).await.unwrap();
+        backfeed_copy(&mock.mastodon, &syndication, &mut tx).await.unwrap();
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM mentions WHERE note_id = $1")
+            .bind(syndication.object_id)
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(count, 3);
+
+        let reply = get_mention_by_remote_id("mastodon:status:900", &mut tx)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reply.mention_type, "reply");
+        assert_eq!(reply.source_url, "https://mastodon.example/@carol/900");
+        assert_eq!(reply.status, "pending");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL and BASE_URI, pointing to a database with the schema"]
+    async fn removes_withdrawn_interactions() {
+        let mock = mock_with_copy().await;
+        let mut conn = connect().await;
+        let mut tx = conn.begin().await.unwrap();
+        let syndication = note_with_copy(&mut tx).await;
+
+        backfeed_copy(&mock.mastodon, &syndication, &mut tx).await.unwrap();
+
+        {
+            let mut state = mock.state.lock().unwrap();
+            state.favourited_by.insert("100".to_owned(), vec![]);
+            state.descendants.insert("100".to_owned(), vec![]);
+        }
+
+        backfeed_copy(&mock.mastodon, &syndication, &mut tx).await.unwrap();
+        assert_eq!(
+            backfed(&syndication, &mut tx).await,
+            vec![("mastodon:reblogged:100:3".to_owned(), "pending".to_owned())]
+        );
+
+        let favourite = get_mention_by_remote_id("mastodon:favourited:100:2", &mut tx)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(favourite.status, "deleted");
+
+        // favouriting again brings the mention back
+        mock.state
+            .lock()
+            .unwrap()
+            .favourited_by
+            .insert("100".to_owned(), vec![account("2", "alice")]);
+
+        backfeed_copy(&mock.mastodon, &syndication, &mut tx).await.unwrap();
+        assert_eq!(
+            backfed(&syndication, &mut tx).await,
+            vec![
+                ("mastodon:favourited:100:2".to_owned(), "pending".to_owned()),
+                ("mastodon:reblogged:100:3".to_owned(), "pending".to_owned()),
+            ]
+        );
+    }
+}
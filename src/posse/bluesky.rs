@@ -0,0 +1,705 @@
+use std::env;
+
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{SecondsFormat, Utc};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+use url::Url;
+
+use super::required_env;
+use super::syndicator::{Capabilities, Content, Syndicated, Syndicator};
+use crate::models::Syndication;
+
+/// the service name syndications to Bluesky are recorded with
+pub const SERVICE: &str = "bluesky";
+
+const POST_COLLECTION: &str = "app.bsky.feed.post";
+
+/// seconds before its expiry an access token is renewed, so it doesn't expire during a request
+const EXPIRY_MARGIN: i64 = 60;
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Session {
+    access_jwt: String,
+    refresh_jwt: String,
+    did: String,
+    handle: String,
+}
+
+/// Posts to Bluesky via the XRPC API of the PDS of the account, `BLUESKY_PDS` (`https://bsky.social` by
+/// default). The account logs in with an app password once and renews the short-lived access token with the
+/// refresh token; it logs in again when the refresh token expired, too.
+pub struct BlueskySyndicator {
+    pds: String,
+    identifier: String,
+    password: String,
+    handle: String,
+    session: Mutex<Option<Session>>,
+    http: reqwest::Client,
+}
+
+impl BlueskySyndicator {
+    /// Verifies the credentials by logging in
+    pub async fn configure() -> Result<Self> {
+        let mut syndicator = Self {
+            pds: env::var("BLUESKY_PDS").unwrap_or_else(|_| "https://bsky.social".to_owned()),
+            identifier: required_env("BLUESKY_IDENTIFIER")?,
+            password: required_env("BLUESKY_APP_PASSWORD")?,
+            handle: String::new(),
+            session: Mutex::new(None),
+            http: reqwest::Client::new(),
+        };
+
+        syndicator.handle = syndicator.session().await?.handle;
+
+        Ok(syndicator)
+    }
+
+    fn xrpc(&self, method: &str) -> String {
+        format!("{}/xrpc/{}", self.pds.trim_end_matches('/'), method)
+    }
+
+    /// The cached session; renewed when its access token is about to expire
+    async fn session(&self) -> Result<Session> {
+        let mut cached = self.session.lock().await;
+
+        if let Some(session) = cached.as_ref() {
+            if !expires_soon(&session.access_jwt) {
+                return Ok(session.clone());
+            }
+
+            match self.refresh_session(session).await {
+                Ok(session) => {
+                    *cached = Some(session.clone());
+                    return Ok(session);
+                }
+                Err(err) => tracing::warn!("could not refresh the Bluesky session, logging in again: {}", err),
+            }
+        }
+
+        let session = self.create_session().await?;
+        *cached = Some(session.clone());
+
+        Ok(session)
+    }
+
+    async fn create_session(&self) -> Result<Session> {
+        let body = json!({ "identifier": self.identifier, "password": self.password });
+        let rsp = self
+            .http
+            .post(self.xrpc("com.atproto.server.createSession"))
+            .json(&body)
+            .send()
+            .await?;
+
+        Ok(check(rsp).await?.json::<Session>().await?)
+    }
+
+    async fn refresh_session(&self, session: &Session) -> Result<Session> {
+        let rsp = self
+            .http
+            .post(self.xrpc("com.atproto.server.refreshSession"))
+            .bearer_auth(&session.refresh_jwt)
+            .send()
+            .await?;
+
+        Ok(check(rsp).await?.json::<Session>().await?)
+    }
+
+    async fn procedure(&self, session: &Session, method: &str, body: Value) -> Result<Value> {
+        let rsp = self
+            .http
+            .post(self.xrpc(method))
+            .bearer_auth(&session.access_jwt)
+            .json(&body)
+            .send()
+            .await?;
+
+        Ok(check(rsp).await?.json::<Value>().await?)
+    }
+
+    async fn query(&self, session: &Session, method: &str, params: &[(&str, &str)]) -> Result<Value> {
+        let rsp = self
+            .http
+            .get(self.xrpc(method))
+            .bearer_auth(&session.access_jwt)
+            .query(params)
+            .send()
+            .await?;
+
+        Ok(check(rsp).await?.json::<Value>().await?)
+    }
+
+    async fn create_record(&self, session: &Session, collection: &str, record: Value) -> Result<String> {
+        let body = json!({ "repo": session.did, "collection": collection, "record": record });
+        let created = self.procedure(session, "com.atproto.repo.createRecord", body).await?;
+
+        created["uri"]
+            .as_str()
+            .map(str::to_owned)
+            .ok_or_else(|| anyhow!("the created record has no URI"))
+    }
+
+    async fn upload_blob(&self, session: &Session, path: &str, data: Vec<u8>) -> Result<Value> {
+        let mime = new_mime_guess::from_path(path).first_raw().unwrap_or("image/jpeg");
+
+        let rsp = self
+            .http
+            .post(self.xrpc("com.atproto.repo.uploadBlob"))
+            .bearer_auth(&session.access_jwt)
+            .header(reqwest::header::CONTENT_TYPE, mime)
+            .body(data)
+            .send()
+            .await?;
+
+        let uploaded = check(rsp).await?.json::<Value>().await?;
+        Ok(uploaded["blob"].clone())
+    }
+
+    /// Looks up the post a `https://bsky.app/profile/{handle}/post/{rkey}` URL points to; `None` for any
+    /// other URL
+    async fn resolve_post(&self, session: &Session, url: &str) -> Result<Option<Value>> {
+        let Ok(url) = Url::parse(url) else {
+            return Ok(None);
+        };
+        if url.host_str() != Some("bsky.app") {
+            return Ok(None);
+        }
+
+        let segments = url.path_segments().map(|segments| segments.collect::<Vec<_>>());
+        let Some(["profile", actor, "post", rkey]) = segments.as_deref() else {
+            return Ok(None);
+        };
+
+        let did = if actor.starts_with("did:") {
+            actor.to_string()
+        } else {
+            let resolved = self
+                .query(session, "com.atproto.identity.resolveHandle", &[("handle", actor)])
+                .await?;
+            resolved["did"].as_str().unwrap_or_default().to_owned()
+        };
+
+        let uri = format!("at://{}/{}/{}", did, POST_COLLECTION, rkey);
+        let found = self.query(session, "app.bsky.feed.getPosts", &[("uris", &uri)]).await?;
+
+        Ok(found["posts"].as_array().and_then(|posts| posts.first()).cloned())
+    }
+
+    fn post_text(&self, content: &Content) -> String {
+        // Bluesky has no content warnings; self-labels only cover adult content
+        let prefix = content
+            .content_warning
+            .as_ref()
+            .map(|cw| format!("CW: {}\n\n", cw))
+            .unwrap_or_default();
+
+        format!("{}{}", prefix, content.text(&self.capabilities(), prefix.chars().count()))
+    }
+
+    /// Builds the post record: the text with facets for its links, the reply references and the image. An
+    /// edited post keeps the creation time of the `current` record and its image blob if the image didn't
+    /// change; `current` is `Value::Null` for a new post.
+    async fn post_record(&self, session: &Session, content: &Content, current: &Value) -> Result<Value> {
+        let text = self.post_text(content);
+        let created_at = match current["createdAt"].as_str() {
+            Some(created_at) => created_at.to_owned(),
+            None => Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        };
+
+        let mut record = json!({
+            "$type": POST_COLLECTION,
+            "text": text,
+            "facets": link_facets(&text),
+            "createdAt": created_at,
+        });
+
+        if let Some(lang) = &content.lang {
+            record["langs"] = json!([lang]);
+        }
+
+        if let Some(url) = &content.in_reply_to {
+            if let Some(parent) = self.resolve_post(session, url).await? {
+                let parent_ref = json!({ "uri": parent["uri"], "cid": parent["cid"] });
+                let root_ref = match &parent["record"]["reply"]["root"] {
+                    Value::Null => parent_ref.clone(),
+                    root => root.clone(),
+                };
+
+                record["reply"] = json!({ "root": root_ref, "parent": parent_ref });
+            }
+        }
+
+        if let Some(embed) = self.image_embed(session, content, &current["embed"]).await? {
+            record["embed"] = embed;
+        }
+
+        Ok(record)
+    }
+
+    /// The embed with the image of a post; the image is only uploaded if it isn't the one of the `current`
+    /// embed already
+    async fn image_embed(&self, session: &Session, content: &Content, current: &Value) -> Result<Option<Value>> {
+        let Some(image) = &content.image else {
+            return Ok(None);
+        };
+        let Some(path) = image.path_for(&self.capabilities()) else {
+            return Ok(None);
+        };
+
+        let data = tokio::fs::read(path).await?;
+        let current_blob = &current["images"][0]["image"];
+
+        let blob = if current_blob["ref"]["$link"].as_str() == Some(&blob_cid(&data)) {
+            current_blob.clone()
+        } else {
+            self.upload_blob(session, path, data).await?
+        };
+
+        Ok(Some(json!({
+            "$type": "app.bsky.embed.images",
+            "images": [{ "alt": image.alt.as_deref().unwrap_or_default(), "image": blob }],
+        })))
+    }
+
+    async fn create_post(&self, session: &Session, content: &Content) -> Result<Syndicated> {
+        let record = self.post_record(session, content, &Value::Null).await?;
+        let uri = self.create_record(session, POST_COLLECTION, record).await?;
+
+        Ok(Syndicated {
+            syndication_type: "post".to_owned(),
+            url: post_url(&session.handle, &uri),
+            remote_id: Some(uri),
+        })
+    }
+
+    async fn delete_record(&self, session: &Session, uri: &str) -> Result<()> {
+        let Some((repo, collection, rkey)) = split_uri(uri) else {
+            return Err(anyhow!("invalid record URI {}", uri));
+        };
+
+        let body = json!({ "repo": repo, "collection": collection, "rkey": rkey });
+        self.procedure(session, "com.atproto.repo.deleteRecord", body).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Syndicator for BlueskySyndicator {
+    fn service(&self) -> &'static str {
+        SERVICE
+    }
+
+    fn name(&self) -> &'static str {
+        "Bluesky"
+    }
+
+    fn uid(&self) -> String {
+        format!("https://bsky.app/profile/{}", self.handle)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_length: 300,
+            link_length: None,
+            media: true,
+            max_image_size: Some(1_000_000),
+            private: false,
+        }
+    }
+
+    async fn post(&self, content: &Content) -> Result<Syndicated> {
+        let session = self.session().await?;
+        self.create_post(&session, content).await
+    }
+
+    /// Updates the post record in place, so replies, likes and reposts stay with the copy; the record is built
+    /// like for a new post and only written if any part of it changed
+    async fn edit(&self, syndication: &Syndication, content: &Content) -> Result<Syndicated> {
+        let session = self.session().await?;
+        let uri = syndication.remote_id.as_deref().unwrap_or_default();
+        let Some((repo, collection, rkey)) = split_uri(uri) else {
+            return Err(anyhow!("invalid record URI {}", uri));
+        };
+
+        let params = [("repo", repo), ("collection", collection), ("rkey", rkey)];
+        let current = self.query(&session, "com.atproto.repo.getRecord", &params).await?;
+        let record = self.post_record(&session, content, &current["value"]).await?;
+
+        if record != current["value"] {
+            let body = json!({
+                "repo": repo,
+                "collection": collection,
+                "rkey": rkey,
+                "record": record,
+                "swapRecord": current["cid"],
+            });
+            self.procedure(&session, "com.atproto.repo.putRecord", body).await?;
+        }
+
+        Ok(Syndicated {
+            syndication_type: syndication.syndication_type.clone(),
+            url: syndication.url.clone(),
+            remote_id: syndication.remote_id.clone(),
+        })
+    }
+
+    /// Deletes the post, like or repost record
+    async fn delete(&self, syndication: &Syndication) -> Result<()> {
+        let session = self.session().await?;
+        self.delete_record(&session, syndication.remote_id.as_deref().unwrap_or_default())
+            .await
+    }
+
+    async fn interact(&self, url: &str, syndication_type: &str) -> Result<Option<Syndicated>> {
+        let session = self.session().await?;
+        let Some(post) = self.resolve_post(&session, url).await? else {
+            return Ok(None);
+        };
+
+        let collection = if syndication_type == "like" {
+            "app.bsky.feed.like"
+        } else {
+            "app.bsky.feed.repost"
+        };
+
+        let record = json!({
+            "$type": collection,
+            "subject": { "uri": post["uri"], "cid": post["cid"] },
+            "createdAt": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        });
+        let uri = self.create_record(&session, collection, record).await?;
+
+        Ok(Some(Syndicated {
+            syndication_type: syndication_type.to_owned(),
+            url: url.to_owned(),
+            remote_id: Some(uri),
+        }))
+    }
+}
+
+/// XRPC errors come with a JSON body naming the error
+async fn check(rsp: reqwest::Response) -> Result<reqwest::Response> {
+    let status = rsp.status();
+    if status.is_success() {
+        return Ok(rsp);
+    }
+
+    let body = rsp.json::<Value>().await.unwrap_or_default();
+    let message = body["message"].as_str().or(body["error"].as_str()).unwrap_or_default();
+
+    Err(anyhow!("{} {}", status, message))
+}
+
+/// Whether a JWT expires within the next `EXPIRY_MARGIN` seconds; tokens without a readable expiry are
+/// treated as expired
+fn expires_soon(jwt: &str) -> bool {
+    let exp = jwt
+        .split('.')
+        .nth(1)
+        .and_then(|payload| URL_SAFE_NO_PAD.decode(payload).ok())
+        .and_then(|payload| serde_json::from_slice::<Value>(&payload).ok())
+        .and_then(|claims| claims["exp"].as_i64());
+
+    exp.is_none_or(|exp| exp - EXPIRY_MARGIN <= Utc::now().timestamp())
+}
+
+/// The CID a PDS stores a blob under: a CIDv1 of the raw bytes with a SHA-256 multihash, base32 encoded
+fn blob_cid(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+    let mut cid = vec![0x01, 0x55, 0x12, 0x20];
+    cid.extend_from_slice(&Sha256::digest(data));
+
+    let mut encoded = String::from("b");
+    let (mut buffer, mut bits) = (0u32, 0);
+    for byte in cid {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(char::from(ALPHABET[((buffer >> bits) & 31) as usize]));
+        }
+    }
+
+    if bits > 0 {
+        encoded.push(char::from(ALPHABET[((buffer << (5 - bits)) & 31) as usize]));
+    }
+
+    encoded
+}
+
+/// Splits an `at://{repo}/{collection}/{rkey}` URI
+fn split_uri(uri: &str) -> Option<(&str, &str, &str)> {
+    let mut parts = uri.strip_prefix("at://")?.splitn(3, '/');
+    Some((parts.next()?, parts.next()?, parts.next()?))
+}
+
+fn post_url(handle: &str, uri: &str) -> String {
+    let rkey = uri.rsplit('/').next().unwrap_or_default();
+    format!("https://bsky.app/profile/{}/post/{}", handle, rkey)
+}
+
+/// Links in a post are only clickable with a facet marking them; facets index the text by UTF-8 bytes
+fn link_facets(text: &str) -> Vec<Value> {
+    let mut facets = Vec::new();
+    let mut end = 0;
+
+    for (start, _) in text.match_indices("http") {
+        let candidate = &text[start..];
+        if start < end || !(candidate.starts_with("https://") || candidate.starts_with("http://")) {
+            continue;
+        }
+
+        let length = candidate
+            .find(|c: char| c.is_whitespace() || matches!(c, ')' | ']' | '>' | '"'))
+            .unwrap_or(candidate.len());
+        let link = candidate[..length].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        end = start + link.len();
+
+        facets.push(json!({
+            "index": { "byteStart": start, "byteEnd": end },
+            "features": [{ "$type": "app.bsky.richtext.facet#link", "uri": link }],
+        }));
+    }
+
+    facets
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::posse::mock_bluesky::{HANDLE, MockBluesky};
+    use crate::posse::syndicator::Image;
+
+    fn syndicator(mock: &MockBluesky) -> BlueskySyndicator {
+        BlueskySyndicator {
+            pds: mock.base_url.clone(),
+            identifier: HANDLE.to_owned(),
+            password: "app-password".to_owned(),
+            handle: HANDLE.to_owned(),
+            session: Mutex::new(None),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    fn content(title: &str, image: Option<Image>) -> Content {
+        let mut content = Content::new(title, "https://wwwtech.example/notes/1".to_owned(), "public", &None);
+        content.image = image;
+        content
+    }
+
+    fn image_file(data: &[u8]) -> tempfile::NamedTempFile {
+        let mut file = tempfile::Builder::new().suffix(".jpg").tempfile().unwrap();
+        file.write_all(data).unwrap();
+        file
+    }
+
+    fn image(file: &tempfile::NamedTempFile, alt: &str) -> Image {
+        Image {
+            path: file.path().to_str().unwrap().to_owned(),
+            large_path: None,
+            alt: Some(alt.to_owned()),
+        }
+    }
+
+    /// Adds the copy of a post with `text` and an image with `data` and `alt`
+    fn add_copy(mock: &MockBluesky, text: &str, image: Option<(&[u8], &str)>) -> Syndication {
+        let mut value = json!({
+            "$type": POST_COLLECTION,
+            "text": text,
+            "facets": link_facets(text),
+            "createdAt": "2026-10-01T12:00:00.000Z",
+        });
+
+        if let Some((data, alt)) = image {
+            value["embed"] = json!({
+                "$type": "app.bsky.embed.images",
+                "images": [{
+                    "alt": alt,
+                    "image": {
+                        "$type": "blob",
+                        "ref": { "$link": blob_cid(data) },
+                        "mimeType": "image/jpeg",
+                        "size": data.len(),
+                    },
+                }],
+            });
+        }
+
+        let uri = mock.add_post("3kabc", value);
+
+        Syndication {
+            id: 1,
+            object_type: "notes".to_owned(),
+            object_id: 1,
+            service: SERVICE.to_owned(),
+            syndication_type: "post".to_owned(),
+            url: post_url(HANDLE, &uri),
+            remote_id: Some(uri),
+            last_error: None,
+            failed_at: None,
+            inserted_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+        }
+    }
+
+    #[tokio::test]
+    async fn reuses_the_session() {
+        let mock = MockBluesky::start().await;
+        let syndicator = syndicator(&mock);
+
+        let first = syndicator.session().await.unwrap();
+        let second = syndicator.session().await.unwrap();
+
+        assert_eq!(first.access_jwt, second.access_jwt);
+        let state = mock.state.lock().unwrap();
+        assert_eq!((state.logins, state.refreshes), (1, 0));
+    }
+
+    #[tokio::test]
+    async fn refreshes_an_expiring_session() {
+        let mock = MockBluesky::start().await;
+        mock.state.lock().unwrap().access_lifetime = EXPIRY_MARGIN / 2;
+        let syndicator = syndicator(&mock);
+
+        let first = syndicator.session().await.unwrap();
+        let second = syndicator.session().await.unwrap();
+
+        assert_ne!(first.access_jwt, second.access_jwt);
+        let state = mock.state.lock().unwrap();
+        assert_eq!((state.logins, state.refreshes), (1, 1));
+        assert_eq!(second.access_jwt, state.access_jwt);
+    }
+
+    #[tokio::test]
+    async fn logs_in_again_when_the_refresh_fails() {
+        let mock = MockBluesky::start().await;
+        mock.state.lock().unwrap().access_lifetime = EXPIRY_MARGIN / 2;
+        let syndicator = syndicator(&mock);
+
+        syndicator.session().await.unwrap();
+        mock.state.lock().unwrap().reject_refresh = true;
+        let session = syndicator.session().await.unwrap();
+
+        let state = mock.state.lock().unwrap();
+        assert_eq!((state.logins, state.refreshes), (2, 0));
+        assert_eq!(session.access_jwt, state.access_jwt);
+    }
+
+    #[test]
+    fn unreadable_tokens_expire() {
+        assert!(expires_soon("not a jwt"));
+        assert!(expires_soon(&format!("x.{}.y", URL_SAFE_NO_PAD.encode(r#"{"sub":"did"}"#))));
+        assert!(!expires_soon(&format!(
+            "x.{}.y",
+            URL_SAFE_NO_PAD.encode(json!({ "exp": Utc::now().timestamp() + 3600 }).to_string())
+        )));
+    }
+
+    #[test]
+    fn computes_blob_cids() {
+        assert_eq!(blob_cid(b""), "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku");
+    }
+
+    #[tokio::test]
+    async fn leaves_unchanged_post_alone() {
+        let mock = MockBluesky::start().await;
+        let syndicator = syndicator(&mock);
+        let file = image_file(b"a picture");
+        let content = content("Hello", Some(image(&file, "a cat")));
+        let syndication = add_copy(&mock, &syndicator.post_text(&content), Some((b"a picture", "a cat")));
+
+        let syndicated = syndicator.edit(&syndication, &content).await.unwrap();
+
+        assert_eq!(syndicated.remote_id, syndication.remote_id);
+        let state = mock.state.lock().unwrap();
+        assert!(state.puts.is_empty());
+        assert!(state.uploads.is_empty());
+    }
+
+    #[tokio::test]
+    async fn updates_changed_text_in_place() {
+        let mock = MockBluesky::start().await;
+        let syndicator = syndicator(&mock);
+        let syndication = add_copy(&mock, "Hello (https://wwwtech.example/notes/1)", None);
+
+        let syndicated = syndicator
+            .edit(&syndication, &content("Hello again", None))
+            .await
+            .unwrap();
+
+        assert_eq!(syndicated.remote_id, syndication.remote_id);
+        assert_eq!(syndicated.url, syndication.url);
+
+        let state = mock.state.lock().unwrap();
+        assert_eq!(state.puts.len(), 1);
+        assert_eq!(state.puts[0]["swapRecord"], "cid-3kabc-0");
+        assert_eq!(state.puts[0]["rkey"], "3kabc");
+
+        let record = &state.records[syndication.remote_id.as_deref().unwrap()]["value"];
+        assert_eq!(record["text"], "Hello again (https://wwwtech.example/notes/1)");
+        assert_eq!(record["createdAt"], "2026-10-01T12:00:00.000Z");
+    }
+
+    #[tokio::test]
+    async fn updates_changed_language() {
+        let mock = MockBluesky::start().await;
+        let syndicator = syndicator(&mock);
+        let mut content = content("Hello", None);
+        let syndication = add_copy(&mock, &syndicator.post_text(&content), None);
+        content.lang = Some("de".to_owned());
+
+        syndicator.edit(&syndication, &content).await.unwrap();
+
+        let state = mock.state.lock().unwrap();
+        assert_eq!(state.puts.len(), 1);
+        assert_eq!(state.puts[0]["record"]["langs"], json!(["de"]));
+        assert_eq!(state.puts[0]["record"]["text"], syndicator.post_text(&content));
+    }
+
+    #[tokio::test]
+    async fn updates_changed_alt_text_without_uploading_the_image() {
+        let mock = MockBluesky::start().await;
+        let syndicator = syndicator(&mock);
+        let file = image_file(b"a picture");
+        let content = content("Hello", Some(image(&file, "a black cat")));
+        let syndication = add_copy(&mock, &syndicator.post_text(&content), Some((b"a picture", "a cat")));
+
+        syndicator.edit(&syndication, &content).await.unwrap();
+
+        let state = mock.state.lock().unwrap();
+        assert_eq!(state.puts.len(), 1);
+        assert!(state.uploads.is_empty());
+
+        let image = &state.puts[0]["record"]["embed"]["images"][0];
+        assert_eq!(image["alt"], "a black cat");
+        assert_eq!(image["image"]["ref"]["$link"], blob_cid(b"a picture"));
+    }
+
+    #[tokio::test]
+    async fn uploads_a_changed_image() {
+        let mock = MockBluesky::start().await;
+        let syndicator = syndicator(&mock);
+        let file = image_file(b"another picture");
+        let content = content("Hello", Some(image(&file, "a cat")));
+        let syndication = add_copy(&mock, &syndicator.post_text(&content), Some((b"a picture", "a cat")));
+
+        syndicator.edit(&syndication, &content).await.unwrap();
+
+        let state = mock.state.lock().unwrap();
+        assert_eq!(state.uploads, vec![b"another picture".len()]);
+        assert_eq!(state.puts.len(), 1);
+        assert_eq!(state.puts[0]["record"]["embed"]["images"][0]["image"]["ref"]["$link"], "uploaded-1");
+    }
+}
@@ -1,18 +1,19 @@
-use std::env;
-
-use mastodon_async::Result;
-use mastodon_async::entities::prelude::Account;
+use anyhow::Result as AResult;
+use async_trait::async_trait;
 use mastodon_async::helpers::{cli, toml};
 use mastodon_async::prelude::*;
+use mastodon_async::{Error, Result, Visibility};
+use serde::Deserialize;
+use serde_json::json;
 
-use crate::models::{Article, Deafie, Note, Picture};
-use crate::uri_helpers::{article_uri, deafie_uri, note_uri, picture_uri};
-use crate::utils::image_base_path;
+use super::required_env;
+use super::syndicator::{Capabilities, Content, Syndicated, Syndicator};
+use crate::models::Syndication;
 
-async fn register() -> Result<Mastodon> {
-    let server_url = env::var("MASTODON_URL").expect("env variable MASTODON_URL not set");
-    let toml_path = env::var("MASTODON_TOML").expect("env variable MASTODON_TOML not set");
+/// the service name syndications to Mastodon are recorded with
+pub const SERVICE: &str = "mastodon";
 
+async fn register(server_url: &str, toml_path: &str) -> Result<Mastodon> {
     let registration = Registration::new(server_url)
         .client_name("WWWTech.de")
         .scopes(Scopes::write_all() | Scopes::read_all())
@@ -26,129 +27,305 @@ async fn register() -> Result<Mastodon> {
     Ok(mastodon)
 }
 
-pub async fn verify_or_register() -> Result<Account> {
-    let toml_path = env::var("MASTODON_TOML").expect("env variable MASTODON_TOML not set");
-
-    let mastodon = if let Ok(data) = toml::from_file(toml_path) {
-        Mastodon::from(data)
-    } else {
-        register().await?
-    };
+/// The plain text of a status as it was posted; the status itself only has the rendered HTML
+#[derive(Deserialize)]
+struct StatusSource {
+    text: String,
+    spoiler_text: String,
+}
 
-    mastodon.verify_credentials().await
+pub struct MastodonSyndicator {
+    mastodon: Mastodon,
+    server_url: String,
+    /// for the requests mastodon-async doesn't implement
+    http: reqwest::Client,
 }
 
-pub async fn post_note(note: &Note) -> Result<()> {
-    let toml_path = env::var("MASTODON_TOML").expect("env variable MASTODON_TOML not set");
-    let mastodon = Mastodon::from(toml::from_file(toml_path).unwrap());
+impl MastodonSyndicator {
+    /// Registers the app on the first run and verifies the credentials
+    pub async fn configure() -> AResult<Self> {
+        let server_url = required_env("MASTODON_URL")?;
+        let toml_path = required_env("MASTODON_TOML")?;
+
+        let mastodon = if let Ok(data) = toml::from_file(&toml_path) {
+            Mastodon::from(data)
+        } else {
+            register(&server_url, &toml_path).await?
+        };
+
+        mastodon.verify_credentials().await?;
+
+        Ok(Self {
+            mastodon,
+            server_url,
+            http: reqwest::Client::new(),
+        })
+    }
+
+    pub fn client(&self) -> &Mastodon {
+        &self.mastodon
+    }
+
+    /// Looks up a status by its URL; Mastodon fetches statuses of other instances on the way. `None` if the URL
+    /// doesn't point to a status.
+    async fn resolve_status(&self, url: &str) -> Result<Option<Status>> {
+        let result = self.mastodon.search(url, true).await?;
+        Ok(result.statuses.into_iter().next())
+    }
+
+    /// Returns the text of the status and the status it replies to; a reply mentions the author of that
+    /// status, so that they get notified
+    async fn status_text(&self, content: &Content) -> Result<(String, Option<Status>)> {
+        let target = match &content.in_reply_to {
+            Some(url) => self.resolve_status(url).await?,
+            None => None,
+        };
+
+        let Some(target) = target else {
+            return Ok((content.text(&self.capabilities(), 0), None));
+        };
+
+        let mention = format!("@{}", target.account.acct);
+        let text = content.text(&self.capabilities(), mention.chars().count() + 1);
+        let text = if text.contains(&mention) {
+            text
+        } else {
+            format!("{} {}", mention, text)
+        };
+
+        Ok((text, Some(target)))
+    }
 
-    let content = format!("{} ({})", note.title, note_uri(note));
+    async fn post_status(&self, content: &Content) -> Result<Status> {
+        let (text, target) = self.status_text(content).await?;
+        let mut new_status = StatusBuilder::new();
 
-    let mut new_status = StatusBuilder::new();
+        new_status
+            .status(text)
+            .visibility(visibility_from_str(&content.visibility));
 
-    new_status
-        .status(content)
-        .visibility(visibility_from_str(&note.posse_visibility));
+        if let Some(target) = target {
+            new_status.in_reply_to(target.id.as_ref());
+        }
 
-    if let Some(cw) = &note.content_warning {
-        if !cw.is_empty() {
+        if let Some(image) = &content.image {
+            if let Some(path) = image.path_for(&self.capabilities()) {
+                let attachment = self.mastodon.media(path, image.alt.clone()).await?;
+                new_status.media_ids([attachment.id.as_ref()]);
+            }
+        }
+
+        if let Some(cw) = &content.content_warning {
             new_status.sensitive(true).spoiler_text(cw.clone());
         }
+
+        self.mastodon.new_status(new_status.build()?).await
     }
 
-    let new_status = new_status.build()?;
+    fn status_url(&self, id: &StatusId, path: &str) -> String {
+        format!("{}/api/v1/statuses/{}{}", self.mastodon.data.base, id.as_ref(), path)
+    }
 
-    mastodon.new_status(new_status).await?;
+    // mastodon-async implements neither reading the source of a status nor editing it
+    async fn status_source(&self, id: &StatusId) -> Result<StatusSource> {
+        let request_error = |e: reqwest::Error| Error::Other(format!("could not get status source: {}", e));
+
+        self.http
+            .get(self.status_url(id, "/source"))
+            .bearer_auth(self.mastodon.data.token.as_ref())
+            .send()
+            .await
+            .and_then(|rsp| rsp.error_for_status())
+            .map_err(request_error)?
+            .json::<StatusSource>()
+            .await
+            .map_err(request_error)
+    }
 
-    Ok(())
-}
+    /// Edits a status to match the post; the attachments are kept. The visibility of a status can't be edited,
+    /// so when it changed the status is replaced by a new one. A status which already matches is left alone,
+    /// every edit is shown as such and notifies those who boosted the status.
+    async fn update_status(&self, id: &StatusId, content: &Content) -> Result<Status> {
+        let current = self.mastodon.get_status(id).await?;
 
-pub async fn post_picture(picture: &Picture) -> Result<()> {
-    let toml_path = env::var("MASTODON_TOML").expect("env variable MASTODON_TOML not set");
-    let mastodon = Mastodon::from(toml::from_file(toml_path).unwrap());
+        if current.visibility != visibility_from_str(&content.visibility) {
+            let status = self.post_status(content).await?;
+            self.mastodon.delete_status(id).await?;
+            return Ok(status);
+        }
 
-    let path = format!("{}/{}/original/{}", image_base_path(), picture.id, picture.image_file_name);
+        let (text, _) = self.status_text(content).await?;
+        let spoiler_text = content.content_warning.as_deref().unwrap_or_default();
 
-    let attachment = mastodon.media(path, picture.alt.clone()).await?;
-    let mut new_status = StatusBuilder::new();
+        let source = self.status_source(id).await?;
+        if source.text == text && source.spoiler_text == spoiler_text {
+            return Ok(current);
+        }
 
-    new_status
-        .status(format!("{} ({})", picture.title, picture_uri(picture)))
-        .visibility(visibility_from_str(&picture.posse_visibility))
-        .media_ids([attachment.id.as_ref()]);
+        let media_ids = current
+            .media_attachments
+            .iter()
+            .map(|attachment| attachment.id.as_ref())
+            .collect::<Vec<_>>();
+
+        let body = json!({
+            "status": text,
+            "spoiler_text": spoiler_text,
+            "sensitive": content.content_warning.is_some(),
+            "media_ids": media_ids,
+        });
+
+        let request_error = |e: reqwest::Error| Error::Other(format!("could not edit status: {}", e));
+
+        self.http
+            .put(self.status_url(id, ""))
+            .bearer_auth(self.mastodon.data.token.as_ref())
+            .json(&body)
+            .send()
+            .await
+            .and_then(|rsp| rsp.error_for_status())
+            .map_err(request_error)?
+            .json::<Status>()
+            .await
+            .map_err(request_error)
+    }
+}
 
-    if let Some(cw) = &picture.content_warning {
-        if !cw.is_empty() {
-            new_status.sensitive(true).spoiler_text(cw.clone());
-        }
+/// A favourite or reblog is recorded with the status it refers to: unfavouriting and unreblogging take the ID
+/// of that status, not of the reblog
+fn syndicated(status: &Status, syndication_type: &str) -> Syndicated {
+    Syndicated {
+        syndication_type: syndication_type.to_owned(),
+        url: status.url.clone().unwrap_or_else(|| status.uri.clone()),
+        remote_id: Some(status.id.as_ref().to_owned()),
     }
+}
 
-    let new_status = new_status.build()?;
+#[async_trait]
+impl Syndicator for MastodonSyndicator {
+    fn service(&self) -> &'static str {
+        SERVICE
+    }
 
-    mastodon.new_status(new_status).await.map_err(|e| {
-        println!("Error posting status: {}", e);
-        e
-    })?;
+    fn name(&self) -> &'static str {
+        "Mastodon"
+    }
 
-    Ok(())
-}
+    fn uid(&self) -> String {
+        self.server_url.clone()
+    }
 
-pub async fn post_article(article: &Article) -> Result<()> {
-    let toml_path = env::var("MASTODON_TOML").expect("env variable MASTODON_TOML not set");
-    let mastodon = Mastodon::from(toml::from_file(toml_path).unwrap());
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_length: 500,
+            link_length: Some(23),
+            media: true,
+            max_image_size: Some(16 * 1024 * 1024),
+            private: true,
+        }
+    }
 
-    let content = format!("{} ({})", article.title, article_uri(article));
+    async fn post(&self, content: &Content) -> AResult<Syndicated> {
+        let status = self.post_status(content).await?;
+        Ok(syndicated(&status, "post"))
+    }
 
-    let mut new_status = StatusBuilder::new();
+    async fn edit(&self, syndication: &Syndication, content: &Content) -> AResult<Syndicated> {
+        let remote_id = syndication.remote_id.as_deref().unwrap_or_default();
+        let status = self.update_status(&StatusId::new(remote_id), content).await?;
+        Ok(syndicated(&status, "post"))
+    }
 
-    new_status
-        .status(content)
-        .visibility(visibility_from_str(&article.posse_visibility));
+    async fn delete(&self, syndication: &Syndication) -> AResult<()> {
+        let id = StatusId::new(syndication.remote_id.as_deref().unwrap_or_default());
 
-    if let Some(cw) = &article.content_warning {
-        if !cw.is_empty() {
-            new_status.sensitive(true).spoiler_text(cw.clone());
+        match syndication.syndication_type.as_str() {
+            "like" => self.mastodon.unfavourite(&id).await.map(|_| ())?,
+            "repost" => self.mastodon.unreblog(&id).await.map(|_| ())?,
+            _ => self.mastodon.delete_status(&id).await.map(|_| ())?,
         }
+
+        Ok(())
     }
 
-    let new_status = new_status.build()?;
+    async fn interact(&self, url: &str, syndication_type: &str) -> AResult<Option<Syndicated>> {
+        let Some(target) = self.resolve_status(url).await? else {
+            return Ok(None);
+        };
 
-    mastodon.new_status(new_status).await?;
+        let status = if syndication_type == "like" {
+            self.mastodon.favourite(&target.id).await?
+        } else {
+            let reblog = self.mastodon.reblog(&target.id).await?;
+            reblog.reblog.map(|status| *status).unwrap_or(target)
+        };
 
-    Ok(())
+        Ok(Some(syndicated(&status, syndication_type)))
+    }
 }
 
-pub async fn post_deafie(deafie: &Deafie) -> Result<()> {
-    let toml_path = env::var("MASTODON_TOML").expect("env variable MASTODON_TOML not set");
-    let mastodon = Mastodon::from(toml::from_file(toml_path).unwrap());
-
-    let content = format!("{} ({})", deafie.title, deafie_uri(deafie));
-
-    let mut new_status = StatusBuilder::new();
+fn visibility_from_str(visiblity: &str) -> Visibility {
+    match visiblity {
+        "public" => Visibility::Public,
+        "unlisted" => Visibility::Unlisted,
+        "private" => Visibility::Private,
+        "direct" => Visibility::Direct,
+        _ => Visibility::Direct,
+    }
+}
 
-    new_status
-        .status(content)
-        .visibility(visibility_from_str(&deafie.posse_visibility));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::posse::mock_mastodon::{MockMastodon, account};
 
-    if let Some(cw) = &deafie.content_warning {
-        if !cw.is_empty() {
-            new_status.sensitive(true).spoiler_text(cw.clone());
+    fn syndicator(mock: &MockMastodon) -> MastodonSyndicator {
+        MastodonSyndicator {
+            mastodon: mock.mastodon.clone(),
+            server_url: mock.base_url.clone(),
+            http: reqwest::Client::new(),
         }
     }
 
-    let new_status = new_status.build()?;
+    fn content(title: &str) -> Content {
+        Content::new(title, "https://wwwtech.example/notes/1".to_owned(), "public", &None)
+    }
 
-    mastodon.new_status(new_status).await?;
+    #[tokio::test]
+    async fn leaves_unchanged_status_alone() {
+        let mock = MockMastodon::start().await;
+        mock.add_status("100", account("1", "ck"), "Hello (https://wwwtech.example/notes/1)");
 
-    Ok(())
-}
+        let status = syndicator(&mock)
+            .update_status(&StatusId::new("100"), &content("Hello"))
+            .await
+            .unwrap();
 
-fn visibility_from_str(visiblity: &str) -> mastodon_async::Visibility {
-    match visiblity {
-        "public" => mastodon_async::Visibility::Public,
-        "unlisted" => mastodon_async::Visibility::Unlisted,
-        "private" => mastodon_async::Visibility::Private,
-        "direct" => mastodon_async::Visibility::Direct,
-        _ => mastodon_async::Visibility::Direct,
+        assert_eq!(status.id.as_ref(), "100");
+        assert!(mock.state.lock().unwrap().edits.is_empty());
+    }
+
+    #[tokio::test]
+    async fn edits_changed_status() {
+        let mock = MockMastodon::start().await;
+        mock.add_status("100", account("1", "ck"), "Hello (https://wwwtech.example/notes/1)");
+
+        let mut changed = content("Hello");
+        changed.content_warning = Some("politics".to_owned());
+        syndicator(&mock)
+            .update_status(&StatusId::new("100"), &changed)
+            .await
+            .unwrap();
+
+        syndicator(&mock)
+            .update_status(&StatusId::new("100"), &content("Hello again"))
+            .await
+            .unwrap();
+
+        let state = mock.state.lock().unwrap();
+        assert_eq!(state.edits.len(), 2);
+        assert_eq!(state.edits[0].1["spoiler_text"], "politics");
+        assert_eq!(state.edits[1].1["status"], "Hello again (https://wwwtech.example/notes/1)");
+        assert_eq!(state.edits[1].1["spoiler_text"], "");
     }
 }
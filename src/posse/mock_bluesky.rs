@@ -0,0 +1,213 @@
+//! A minimal XRPC API of a Bluesky PDS on a random local port, for testing the syndication
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::body::Bytes;
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::Utc;
+use serde_json::{Value, json};
+
+pub const DID: &str = "did:plc:wwwtech";
+pub const HANDLE: &str = "wwwtech.example";
+
+pub struct MockState {
+    /// seconds the issued access tokens are valid
+    pub access_lifetime: i64,
+    /// whether refreshing a session fails, as it does with an expired refresh token
+    pub reject_refresh: bool,
+    pub logins: usize,
+    pub refreshes: usize,
+    pub access_jwt: String,
+    pub refresh_jwt: String,
+    /// the records by their `at://` URI, with their `cid` and the `value`
+    pub records: HashMap<String, Value>,
+    /// the bodies of the record updates
+    pub puts: Vec<Value>,
+    /// the sizes of the uploaded blobs
+    pub uploads: Vec<usize>,
+}
+
+impl Default for MockState {
+    fn default() -> Self {
+        Self {
+            access_lifetime: 7200,
+            reject_refresh: false,
+            logins: 0,
+            refreshes: 0,
+            access_jwt: String::new(),
+            refresh_jwt: String::new(),
+            records: HashMap::new(),
+            puts: Vec::new(),
+            uploads: Vec::new(),
+        }
+    }
+}
+
+pub type SharedState = Arc<Mutex<MockState>>;
+
+pub struct MockBluesky {
+    pub state: SharedState,
+    pub base_url: String,
+}
+
+impl MockBluesky {
+    pub async fn start() -> Self {
+        let state = SharedState::default();
+
+        let app = Router::new()
+            .route("/xrpc/com.atproto.server.createSession", post(create_session))
+            .route("/xrpc/com.atproto.server.refreshSession", post(refresh_session))
+            .route("/xrpc/com.atproto.repo.getRecord", get(get_record))
+            .route("/xrpc/com.atproto.repo.putRecord", post(put_record))
+            .route("/xrpc/com.atproto.repo.uploadBlob", post(upload_blob))
+            .with_state(state.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        Self { state, base_url }
+    }
+
+    /// Adds a post record of the account with the record key `rkey`; returns its URI
+    pub fn add_post(&self, rkey: &str, value: Value) -> String {
+        let uri = format!("at://{}/app.bsky.feed.post/{}", DID, rkey);
+        let record = json!({ "uri": uri, "cid": format!("cid-{}-0", rkey), "value": value });
+        self.state.lock().unwrap().records.insert(uri.clone(), record);
+
+        uri
+    }
+}
+
+type XrpcResult = Result<Json<Value>, (StatusCode, Json<Value>)>;
+
+fn xrpc_error(status: StatusCode, error: &str) -> (StatusCode, Json<Value>) {
+    (status, Json(json!({ "error": error, "message": error })))
+}
+
+fn bearer(headers: &HeaderMap) -> &str {
+    headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default()
+}
+
+/// A JWT which is only good for its expiry; the counter makes every token unique
+fn jwt(kind: &str, counter: usize, lifetime: i64) -> String {
+    let claims = json!({ "scope": kind, "jti": counter, "exp": Utc::now().timestamp() + lifetime });
+    format!("eyJhbGciOiJub25lIn0.{}.", URL_SAFE_NO_PAD.encode(claims.to_string()))
+}
+
+fn issue(state: &mut MockState) -> Json<Value> {
+    let counter = state.logins + state.refreshes;
+    state.access_jwt = jwt("access", counter, state.access_lifetime);
+    state.refresh_jwt = jwt("refresh", counter, 90 * 86400);
+
+    Json(json!({
+        "accessJwt": state.access_jwt,
+        "refreshJwt": state.refresh_jwt,
+        "did": DID,
+        "handle": HANDLE,
+    }))
+}
+
+async fn create_session(State(state): State<SharedState>, Json(body): Json<Value>) -> XrpcResult {
+    if body["identifier"] != HANDLE || body["password"] != "app-password" {
+        return Err(xrpc_error(StatusCode::UNAUTHORIZED, "AuthenticationRequired"));
+    }
+
+    let mut state = state.lock().unwrap();
+    state.logins += 1;
+
+    Ok(issue(&mut state))
+}
+
+async fn refresh_session(State(state): State<SharedState>, headers: HeaderMap) -> XrpcResult {
+    let mut state = state.lock().unwrap();
+    if state.reject_refresh || bearer(&headers) != state.refresh_jwt {
+        return Err(xrpc_error(StatusCode::BAD_REQUEST, "ExpiredToken"));
+    }
+
+    state.refreshes += 1;
+
+    Ok(issue(&mut state))
+}
+
+fn authorize(state: &MockState, headers: &HeaderMap) -> Result<(), (StatusCode, Json<Value>)> {
+    if state.access_jwt.is_empty() || bearer(headers) != state.access_jwt {
+        return Err(xrpc_error(StatusCode::UNAUTHORIZED, "InvalidToken"));
+    }
+
+    Ok(())
+}
+
+async fn get_record(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Query(params): Query<HashMap<String, String>>,
+) -> XrpcResult {
+    let state = state.lock().unwrap();
+    authorize(&state, &headers)?;
+
+    let uri = format!("at://{}/{}/{}", params["repo"], params["collection"], params["rkey"]);
+    let record = state.records.get(&uri).cloned();
+
+    record
+        .map(Json)
+        .ok_or_else(|| xrpc_error(StatusCode::BAD_REQUEST, "RecordNotFound"))
+}
+
+async fn put_record(State(state): State<SharedState>, headers: HeaderMap, Json(body): Json<Value>) -> XrpcResult {
+    let mut state = state.lock().unwrap();
+    authorize(&state, &headers)?;
+
+    let uri = format!(
+        "at://{}/{}/{}",
+        body["repo"].as_str().unwrap_or_default(),
+        body["collection"].as_str().unwrap_or_default(),
+        body["rkey"].as_str().unwrap_or_default()
+    );
+    let version = state.puts.len() + 1;
+    let record = state
+        .records
+        .get_mut(&uri)
+        .ok_or_else(|| xrpc_error(StatusCode::BAD_REQUEST, "RecordNotFound"))?;
+    if record["cid"] != body["swapRecord"] {
+        return Err(xrpc_error(StatusCode::BAD_REQUEST, "InvalidSwap"));
+    }
+
+    record["cid"] = json!(format!("cid-{}-{}", body["rkey"].as_str().unwrap_or_default(), version));
+    record["value"] = body["record"].clone();
+    let rsp = json!({ "uri": uri, "cid": record["cid"] });
+
+    state.puts.push(body);
+
+    Ok(Json(rsp))
+}
+
+async fn upload_blob(State(state): State<SharedState>, headers: HeaderMap, data: Bytes) -> XrpcResult {
+    let mut state = state.lock().unwrap();
+    authorize(&state, &headers)?;
+
+    state.uploads.push(data.len());
+    let mime = headers
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    Ok(Json(json!({
+        "blob": {
+            "$type": "blob",
+            "ref": { "$link": format!("uploaded-{}", state.uploads.len()) },
+            "mimeType": mime,
+            "size": data.len(),
+        }
+    })))
+}
@@ -0,0 +1,183 @@
+//! A minimal Mastodon API on a random local port, for testing the syndication and the backfeed
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::extract::{Path, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use mastodon_async::{Data, Mastodon};
+use serde_json::{Value, json};
+
+#[derive(Default)]
+pub struct MockState {
+    pub statuses: HashMap<String, Value>,
+    /// the text and the content warning a status was posted with
+    pub sources: HashMap<String, (String, String)>,
+    /// the bodies of the edits, by status ID
+    pub edits: Vec<(String, Value)>,
+    /// the accounts which favourited a status
+    pub favourited_by: HashMap<String, Vec<Value>>,
+    /// the accounts which reblogged a status
+    pub reblogged_by: HashMap<String, Vec<Value>>,
+    /// the replies to a status and to its replies
+    pub descendants: HashMap<String, Vec<Value>>,
+}
+
+pub type SharedState = Arc<Mutex<MockState>>;
+
+pub struct MockMastodon {
+    pub state: SharedState,
+    pub base_url: String,
+    pub mastodon: Mastodon,
+}
+
+impl MockMastodon {
+    pub async fn start() -> Self {
+        let state = SharedState::default();
+
+        let app = Router::new()
+            .route("/api/v1/statuses/{id}", get(get_status).put(edit_status))
+            .route("/api/v1/statuses/{id}/source", get(get_source))
+            .route("/api/v1/statuses/{id}/favourited_by", get(get_favourited_by))
+            .route("/api/v1/statuses/{id}/reblogged_by", get(get_reblogged_by))
+            .route("/api/v1/statuses/{id}/context", get(get_context))
+            .with_state(state.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mastodon = Mastodon::from(Data {
+            base: base_url.clone().into(),
+            client_id: "client".into(),
+            client_secret: "secret".into(),
+            redirect: "urn:ietf:wg:oauth:2.0:oob".into(),
+            token: "token".into(),
+        });
+
+        Self {
+            state,
+            base_url,
+            mastodon,
+        }
+    }
+
+    /// Adds a status of `account` with `text` as its source
+    pub fn add_status(&self, id: &str, account: Value, text: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.statuses.insert(id.to_owned(), status(id, account, text));
+        state.sources.insert(id.to_owned(), (text.to_owned(), String::new()));
+    }
+}
+
+pub fn account(id: &str, acct: &str) -> Value {
+    json!({
+        "id": id,
+        "username": acct,
+        "acct": acct,
+        "display_name": acct,
+        "locked": false,
+        "created_at": "2026-01-01T00:00:00Z",
+        "followers_count": 0,
+        "following_count": 0,
+        "statuses_count": 0,
+        "note": "",
+        "url": format!("https://mastodon.example/@{}", acct),
+        "avatar": format!("https://mastodon.example/avatars/{}.png", acct),
+        "avatar_static": "",
+        "header": "",
+        "header_static": "",
+        "emojis": [],
+        "fields": [],
+    })
+}
+
+pub fn status(id: &str, account: Value, text: &str) -> Value {
+    json!({
+        "id": id,
+        "uri": format!("https://mastodon.example/users/{}/statuses/{}", account["acct"].as_str().unwrap(), id),
+        "url": format!("https://mastodon.example/@{}/{}", account["acct"].as_str().unwrap(), id),
+        "account": account,
+        "in_reply_to_id": null,
+        "in_reply_to_account_id": null,
+        "reblog": null,
+        "content": format!("<p>{}</p>", text),
+        "created_at": "2026-10-18T00:00:00Z",
+        "emojis": [],
+        "replies_count": 0,
+        "reblogs_count": 0,
+        "favourites_count": 0,
+        "reblogged": false,
+        "favourited": false,
+        "sensitive": false,
+        "spoiler_text": "",
+        "visibility": "public",
+        "media_attachments": [],
+        "mentions": [],
+        "tags": [],
+        "card": null,
+        "application": null,
+        "language": "en",
+        "pinned": false,
+    })
+}
+
+fn not_found() -> (axum::http::StatusCode, Json<Value>) {
+    (axum::http::StatusCode::NOT_FOUND, Json(json!({ "error": "Record not found" })))
+}
+
+async fn get_status(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, (axum::http::StatusCode, Json<Value>)> {
+    let state = state.lock().unwrap();
+    state.statuses.get(&id).cloned().map(Json).ok_or_else(not_found)
+}
+
+async fn get_favourited_by(State(state): State<SharedState>, Path(id): Path<String>) -> Json<Value> {
+    let state = state.lock().unwrap();
+    Json(json!(state.favourited_by.get(&id).cloned().unwrap_or_default()))
+}
+
+async fn get_reblogged_by(State(state): State<SharedState>, Path(id): Path<String>) -> Json<Value> {
+    let state = state.lock().unwrap();
+    Json(json!(state.reblogged_by.get(&id).cloned().unwrap_or_default()))
+}
+
+async fn get_context(State(state): State<SharedState>, Path(id): Path<String>) -> Json<Value> {
+    let state = state.lock().unwrap();
+    let descendants = state.descendants.get(&id).cloned().unwrap_or_default();
+
+    Json(json!({ "ancestors": [], "descendants": descendants }))
+}
+
+async fn get_source(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, (axum::http::StatusCode, Json<Value>)> {
+    let state = state.lock().unwrap();
+    let (text, spoiler_text) = state.sources.get(&id).ok_or_else(not_found)?;
+
+    Ok(Json(json!({ "id": id, "text": text, "spoiler_text": spoiler_text })))
+}
+
+async fn edit_status(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+    Json(body): Json<Value>,
+) -> Result<Json<Value>, (axum::http::StatusCode, Json<Value>)> {
+    let mut state = state.lock().unwrap();
+    let text = body["status"].as_str().unwrap_or_default().to_owned();
+    let spoiler_text = body["spoiler_text"].as_str().unwrap_or_default().to_owned();
+
+    let status = state.statuses.get_mut(&id).ok_or_else(not_found)?;
+    status["content"] = json!(format!("<p>{}</p>", text));
+    status["spoiler_text"] = json!(spoiler_text);
+    let status = status.clone();
+
+    state.sources.insert(id.clone(), (text, spoiler_text));
+    state.edits.push((id, body));
+
+    Ok(Json(status))
+}
@@ -0,0 +1,265 @@
+use anyhow::Result;
+use sqlx::{PgConnection, PgPool};
+
+use super::actions::{delete_syndication, list_syndications, record_syndication_error, upsert_syndication};
+use super::syndicator::{Content, Image, Syndicated, Syndicator};
+use super::{syndicator, targets};
+use crate::models::{Article, Deafie, Like, Note, Picture};
+use crate::uri_helpers::{article_uri, deafie_uri, note_uri, picture_uri};
+use crate::utils::image_base_path;
+use crate::webmentions::actions::ObjectType;
+
+fn note_content(note: &Note) -> Content {
+    let mut content = Content::new(&note.title, note_uri(note), &note.posse_visibility, &note.content_warning);
+    content.lang = Some(note.lang.clone());
+
+    if note.note_type == "reply" {
+        content.in_reply_to = note.in_reply_to.clone();
+    }
+
+    content
+}
+
+fn picture_content(picture: &Picture) -> Content {
+    let mut content =
+        Content::new(&picture.title, picture_uri(picture), &picture.posse_visibility, &picture.content_warning);
+    content.lang = Some(picture.lang.clone());
+
+    let base = format!("{}/{}", image_base_path(), picture.id);
+    content.image = Some(Image {
+        path: format!("{}/original/{}", base, picture.image_file_name),
+        large_path: Some(format!("{}/large/{}", base, picture.image_file_name)),
+        alt: picture.alt.clone(),
+    });
+
+    content
+}
+
+fn article_content(article: &Article) -> Content {
+    let mut content =
+        Content::new(&article.title, article_uri(article), &article.posse_visibility, &article.content_warning);
+    content.lang = Some(article.lang.clone());
+
+    content
+}
+
+fn deafie_content(deafie: &Deafie) -> Content {
+    let mut content =
+        Content::new(&deafie.title, deafie_uri(deafie), &deafie.posse_visibility, &deafie.content_warning);
+    content.lang = Some("de".to_owned());
+
+    content
+}
+
+/// Posts the note to the services it selected and records the copies; a repost of a post on a service reposts
+/// that post there instead
+pub async fn syndicate_note(note: Note, pool: PgPool) {
+    let interaction = match (note.note_type.as_str(), &note.in_reply_to) {
+        ("repost", Some(url)) => Some((url.as_str(), "repost")),
+        _ => None,
+    };
+
+    let targets = targets(note.posse, &note.posse_targets);
+    syndicate(ObjectType::Note, note.id, &targets, Some(note_content(&note)), interaction, &pool).await;
+}
+
+pub async fn syndicate_picture(picture: Picture, pool: PgPool) {
+    let targets = targets(picture.posse, &picture.posse_targets);
+    syndicate(ObjectType::Picture, picture.id, &targets, Some(picture_content(&picture)), None, &pool).await;
+}
+
+pub async fn syndicate_article(article: Article, pool: PgPool) {
+    let targets = targets(article.posse, &article.posse_targets);
+    syndicate(ObjectType::Article, article.id, &targets, Some(article_content(&article)), None, &pool).await;
+}
+
+/// Deafies are syndicated whenever they are published
+pub async fn syndicate_deafie(deafie: Deafie, pool: PgPool) {
+    let targets = targets(true, &deafie.posse_targets);
+    syndicate(ObjectType::Deafie, deafie.id, &targets, Some(deafie_content(&deafie)), None, &pool).await;
+}
+
+/// Favourites the liked post on the services it is on; likes of anything else stay on the site
+pub async fn syndicate_like(like: Like, pool: PgPool) {
+    let targets = targets(like.posse, &like.posse_targets);
+    let interaction = Some((like.in_reply_to.as_str(), "like"));
+    syndicate(ObjectType::Like, like.id, &targets, None, interaction, &pool).await;
+}
+
+/// Syndicates a post to the services it has no copy on yet: interacts with the post it refers to if that is
+/// on the service, posts the content otherwise
+async fn syndicate(
+    object_type: ObjectType,
+    id: i32,
+    targets: &[&dyn Syndicator],
+    content: Option<Content>,
+    interaction: Option<(&str, &str)>,
+    pool: &PgPool,
+) {
+    let existing = match pool.acquire().await {
+        Ok(mut conn) => list_syndications(object_type.clone(), id, &mut conn).await,
+        Err(e) => Err(e),
+    };
+
+    let existing = match existing {
+        Ok(existing) => existing,
+        Err(e) => {
+            tracing::error!("could not list the syndications of {} {}: {}", object_type.as_str(), id, e);
+            return;
+        }
+    };
+
+    for syndicator in targets {
+        if existing
+            .iter()
+            .any(|syndication| syndication.service == syndicator.service())
+        {
+            continue;
+        }
+
+        let syndicated = syndicate_to(*syndicator, content.as_ref(), interaction).await;
+        record(object_type.clone(), id, *syndicator, syndicated, pool).await;
+    }
+}
+
+async fn syndicate_to(
+    syndicator: &dyn Syndicator,
+    content: Option<&Content>,
+    interaction: Option<(&str, &str)>,
+) -> Result<Option<Syndicated>> {
+    if let Some((url, syndication_type)) = interaction {
+        if let Some(syndicated) = syndicator.interact(url, syndication_type).await? {
+            return Ok(Some(syndicated));
+        }
+    }
+
+    match content {
+        Some(content) if content.is_public() || syndicator.capabilities().private => {
+            syndicator.post(content).await.map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+async fn record(
+    object_type: ObjectType,
+    id: i32,
+    syndicator: &dyn Syndicator,
+    syndicated: Result<Option<Syndicated>>,
+    pool: &PgPool,
+) {
+    let syndicated = match syndicated {
+        Ok(Some(syndicated)) => syndicated,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::error!("could not syndicate {} {} to {}: {}", object_type.as_str(), id, syndicator.service(), e);
+            return;
+        }
+    };
+
+    let result = match pool.acquire().await {
+        Ok(mut conn) => save(object_type.clone(), id, syndicator, &syndicated, &mut conn).await,
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        tracing::error!("could not record the syndication of {} {}: {}", object_type.as_str(), id, e);
+    }
+}
+
+async fn save(
+    object_type: ObjectType,
+    id: i32,
+    syndicator: &dyn Syndicator,
+    syndicated: &Syndicated,
+    conn: &mut PgConnection,
+) -> sqlx::Result<()> {
+    upsert_syndication(
+        object_type,
+        id,
+        syndicator.service(),
+        &syndicated.syndication_type,
+        &syndicated.url,
+        syndicated.remote_id.as_deref(),
+        conn,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Edits the copies of an edited note
+pub async fn update_syndicated_note(note: Note, pool: PgPool) {
+    update_syndicated(ObjectType::Note, note.id, note_content(&note), &pool).await;
+}
+
+pub async fn update_syndicated_picture(picture: Picture, pool: PgPool) {
+    update_syndicated(ObjectType::Picture, picture.id, picture_content(&picture), &pool).await;
+}
+
+pub async fn update_syndicated_article(article: Article, pool: PgPool) {
+    update_syndicated(ObjectType::Article, article.id, article_content(&article), &pool).await;
+}
+
+pub async fn update_syndicated_deafie(deafie: Deafie, pool: PgPool) {
+    update_syndicated(ObjectType::Deafie, deafie.id, deafie_content(&deafie), &pool).await;
+}
+
+async fn update_syndicated(object_type: ObjectType, id: i32, content: Content, pool: &PgPool) {
+    if let Err(e) = try_update_syndicated(object_type.clone(), id, &content, pool).await {
+        tracing::error!("could not update the copies of {} {}: {}", object_type.as_str(), id, e);
+    }
+}
+
+async fn try_update_syndicated(object_type: ObjectType, id: i32, content: &Content, pool: &PgPool) -> sqlx::Result<()> {
+    let mut conn = pool.acquire().await?;
+
+    for syndication in list_syndications(object_type.clone(), id, &mut conn).await? {
+        // favourites and reposts have nothing to edit
+        if !syndication.is_copy() || syndication.remote_id.is_none() {
+            continue;
+        }
+
+        let Some(syndicator) = syndicator(&syndication.service) else {
+            continue;
+        };
+
+        match syndicator.edit(&syndication, content).await {
+            Ok(syndicated) => save(object_type.clone(), id, syndicator, &syndicated, &mut conn).await?,
+            Err(e) => record_syndication_error(syndication.id, &format!("update failed: {}", e), &mut conn).await?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Deletes the copies of a deleted post, or takes back the favourites and reposts; when that fails, the
+/// syndication is kept with the error
+pub async fn delete_syndicated(object_type: ObjectType, id: i32, pool: PgPool) {
+    if let Err(e) = try_delete_syndicated(object_type.clone(), id, &pool).await {
+        tracing::error!("could not delete the copies of {} {}: {}", object_type.as_str(), id, e);
+    }
+}
+
+async fn try_delete_syndicated(object_type: ObjectType, id: i32, pool: &PgPool) -> sqlx::Result<()> {
+    let mut conn = pool.acquire().await?;
+
+    for syndication in list_syndications(object_type, id, &mut conn).await? {
+        if syndication.remote_id.is_none() {
+            delete_syndication(syndication.id, &mut conn).await?;
+            continue;
+        }
+
+        let result = match syndicator(&syndication.service) {
+            Some(syndicator) => syndicator.delete(&syndication).await,
+            None => Err(anyhow::anyhow!("{} isn't configured", syndication.service_name())),
+        };
+
+        match result {
+            Ok(_) => delete_syndication(syndication.id, &mut conn).await?,
+            Err(e) => record_syndication_error(syndication.id, &format!("delete failed: {}", e), &mut conn).await?,
+        }
+    }
+
+    Ok(())
+}
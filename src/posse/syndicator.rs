@@ -0,0 +1,134 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::Syndication;
+
+/// What a service can show of a post; the content of a post is fit to it before posting
+#[derive(Debug, Clone, Copy)]
+pub struct Capabilities {
+    /// the maximum length of a post in characters
+    pub max_length: usize,
+    /// the length a link counts with, `None` if links count with their full length
+    pub link_length: Option<usize>,
+    /// whether a post can carry an image
+    pub media: bool,
+    /// the maximum size of an image in bytes
+    pub max_image_size: Option<u64>,
+    /// whether a post can be restricted to followers or mentioned accounts; posts which aren't public or
+    /// unlisted aren't syndicated to services which can't
+    pub private: bool,
+}
+
+/// An image attached to a post
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub path: String,
+    /// a smaller version, used when the image is too large for a service
+    pub large_path: Option<String>,
+    pub alt: Option<String>,
+}
+
+impl Image {
+    /// Returns the path of the largest version of the image the service accepts
+    pub fn path_for(&self, capabilities: &Capabilities) -> Option<&str> {
+        if !capabilities.media {
+            return None;
+        }
+
+        let Some(max_size) = capabilities.max_image_size else {
+            return Some(&self.path);
+        };
+
+        let fits = |path: &str| std::fs::metadata(path).is_ok_and(|meta| meta.len() <= max_size);
+
+        if fits(&self.path) {
+            Some(&self.path)
+        } else {
+            self.large_path.as_deref().filter(|path| fits(path))
+        }
+    }
+}
+
+/// A post as it is syndicated: its title with a link to it, and the settings from the POSSE form fields
+#[derive(Debug, Clone)]
+pub struct Content {
+    pub title: String,
+    pub url: String,
+    pub lang: Option<String>,
+    /// `public`, `unlisted`, `private` or `direct`
+    pub visibility: String,
+    pub content_warning: Option<String>,
+    pub image: Option<Image>,
+    /// the URL of the post this is a reply to
+    pub in_reply_to: Option<String>,
+}
+
+impl Content {
+    pub fn new(title: &str, url: String, visibility: &str, content_warning: &Option<String>) -> Self {
+        Self {
+            title: title.to_owned(),
+            url,
+            lang: None,
+            visibility: visibility.to_owned(),
+            content_warning: content_warning.clone().filter(|cw| !cw.is_empty()),
+            image: None,
+            in_reply_to: None,
+        }
+    }
+
+    pub fn is_public(&self) -> bool {
+        self.visibility == "public" || self.visibility == "unlisted"
+    }
+
+    /// Returns the text of the post, `title (url)`; the title is shortened to fit the service, keeping
+    /// `reserved` characters for what the service adds to the text
+    pub fn text(&self, capabilities: &Capabilities, reserved: usize) -> String {
+        let link_length = capabilities.link_length.unwrap_or_else(|| self.url.chars().count());
+        let available = capabilities.max_length.saturating_sub(reserved + link_length + 3);
+
+        let title = if self.title.chars().count() > available {
+            let shortened = self.title.chars().take(available.saturating_sub(1)).collect::<String>();
+            format!("{}…", shortened.trim_end())
+        } else {
+            self.title.clone()
+        };
+
+        format!("{} ({})", title, self.url)
+    }
+}
+
+/// A copy of a post on a service, or a favourite or repost of the post it refers to
+#[derive(Debug, Clone)]
+pub struct Syndicated {
+    /// `post`, `like` or `repost`
+    pub syndication_type: String,
+    pub url: String,
+    pub remote_id: Option<String>,
+}
+
+/// A service posts are syndicated to
+#[async_trait]
+pub trait Syndicator: Send + Sync {
+    /// the name syndications to the service are recorded with and posts select it with, e.g. `mastodon`
+    fn service(&self) -> &'static str;
+
+    /// the name of the service shown in the admin forms
+    fn name(&self) -> &'static str;
+
+    /// the UID of the service in the Micropub `syndicate-to` list
+    fn uid(&self) -> String;
+
+    fn capabilities(&self) -> Capabilities;
+
+    async fn post(&self, content: &Content) -> Result<Syndicated>;
+
+    /// Edits the copy to match the post; services without editing replace the copy by a new one
+    async fn edit(&self, syndication: &Syndication, content: &Content) -> Result<Syndicated>;
+
+    /// Deletes the copy, or takes back the favourite or repost
+    async fn delete(&self, syndication: &Syndication) -> Result<()>;
+
+    /// Favourites (`like`) or reposts (`repost`) the post `url` points to; `None` if it isn't a post on the
+    /// service
+    async fn interact(&self, url: &str, syndication_type: &str) -> Result<Option<Syndicated>>;
+}
@@ -1,20 +1,37 @@
+use std::net::{IpAddr, SocketAddr};
+
 use askama::Template;
-use axum::extract::{Form, State};
-use axum::http::StatusCode;
+use axum::extract::{ConnectInfo, Form, Query, State};
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::{Html, IntoResponse, Redirect, Response};
 use axum::routing::{get, post};
 use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
 
 use crate::errors::AppError;
+use crate::models::Author;
 use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
+use crate::utils::rate_limit::client_ip;
 use crate::{AppRouter, AppState, AuthSession};
 
 pub mod actions;
+pub mod indieauth;
+pub mod passkey;
+pub mod password_reset;
+pub mod store;
+pub mod two_factor;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LoginForm {
     email: String,
     password: String,
+    next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NextParam {
+    next: Option<String>,
 }
 
 #[derive(Template)]
@@ -28,15 +45,61 @@ pub struct Show<'a> {
     logged_in: bool,
 
     email: String,
+    next: Option<String>,
+    error: Option<String>,
 }
 
 pub fn configure(app: AppRouter) -> AppRouter {
     app.route("/login", get(new_session))
         .route("/login", post(login))
+        .route("/login/indieauth", post(indieauth::start))
+        .route("/login/indieauth/callback", get(indieauth::callback))
+        .route("/login/passkey", post(passkey::login))
+        .route("/login/passkey/challenge", post(passkey::challenge))
+        .route("/login/two-factor", get(two_factor::show))
+        .route("/login/two-factor", post(two_factor::verify))
+        .route("/password-reset", get(password_reset::new))
+        .route("/password-reset", post(password_reset::create))
+        .route("/password-reset/{token}", get(password_reset::edit))
+        .route("/password-reset/{token}", post(password_reset::update))
         .route("/logout", post(logout))
 }
 
-pub async fn new_session() -> Result<Response, AppError> {
+/// Only local paths are followed after logging in, anything else would be an open redirect
+fn redirect_target(next: Option<&str>) -> String {
+    match next {
+        Some(next) if next.starts_with('/') && !next.starts_with("//") && !next.contains('\\') => {
+            let mut uri = root_uri();
+            if !uri.ends_with('/') {
+                uri.push('/');
+            }
+
+            uri.push_str(next.trim_start_matches('/'));
+            uri
+        }
+        _ => root_uri(),
+    }
+}
+
+/// Logs in an author who passed all factors; failed attempts counted so far are forgotten
+async fn log_in(
+    auth: &mut AuthSession,
+    author: &Author,
+    path: &str,
+    ip: IpAddr,
+    conn: &mut PgConnection,
+) -> Result<(), AppError> {
+    auth.login(author)
+        .await
+        .map_err(|e| AppError::InternalError(format!("error logging in: {}", e)))?;
+
+    actions::reset_failed_logins(author.id, conn).await?;
+    actions::audit_login("login", path, Some(author.id), None, ip, conn).await?;
+
+    Ok(())
+}
+
+pub async fn new_session(Query(params): Query<NextParam>) -> Result<Response, AppError> {
     let html = Show {
         lang: "en",
         title: Some("Login"),
@@ -45,43 +108,74 @@ pub async fn new_session() -> Result<Response, AppError> {
         body_id: None,
         logged_in: false,
         email: "".to_owned(),
+        next: params.next,
+        error: None,
     }
     .render()?;
 
     Ok(Html(html).into_response())
 }
 
+/// the same message for all failures, so that it doesn't give away whether an account exists or is locked
+const LOGIN_FAILED: &str = "The email address or password is wrong or the account is locked for a while.";
+
 pub async fn login(
-    mut auth: AuthSession,
+    auth: AuthSession,
     State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Form(form): Form<LoginForm>,
 ) -> Result<Response, AppError> {
+    let ip = client_ip(&headers, peer);
+    if !state.login_ip_limiter.check(&ip.to_string()).await {
+        return Err(AppError::TooManyRequests("too many login attempts, try again later".to_owned()));
+    }
+
     let mut conn = state.pool.acquire().await?;
     let author = actions::get_author_by_email(&form.email, &mut conn).await?;
+    let password_valid = actions::verify_password(author.as_ref(), &form.password);
 
-    if actions::verify_password(&author, &form.password) {
-        auth.login(&author)
-            .await
-            .map_err(|e| AppError::InternalError(format!("error logging in: {}", e)))?;
-
-        Ok(Redirect::to(&root_uri()).into_response())
-    } else {
-        let html = Show {
-            lang: "en",
-            title: Some("Login"),
-            page_type: None,
-            page_image: None,
-            body_id: None,
-            logged_in: false,
-            email: form.email,
+    match author {
+        Some(author) if password_valid && author.may_log_in() => {
+            two_factor::complete_login(auth, author, form.next, "/login", ip, &mut conn).await
         }
-        .render()?;
 
-        Ok((StatusCode::UNAUTHORIZED, Html(html)).into_response())
+        author => {
+            let author_id = author.map(|author| author.id);
+            if let Some(author_id) = author_id {
+                actions::record_failed_login(author_id, &mut conn).await?;
+            }
+
+            actions::audit_login("login_failed", "/login", author_id, Some(form.email.clone()), ip, &mut conn).await?;
+
+            let html = Show {
+                lang: "en",
+                title: Some("Login"),
+                page_type: None,
+                page_image: None,
+                body_id: None,
+                logged_in: false,
+                email: form.email,
+                next: form.next,
+                error: Some(LOGIN_FAILED.to_owned()),
+            }
+            .render()?;
+
+            Ok((StatusCode::UNAUTHORIZED, Html(html)).into_response())
+        }
     }
 }
 
-pub async fn logout(mut auth: AuthSession) -> Result<impl IntoResponse, AppError> {
-    let _ = auth.logout().await;
+pub async fn logout(
+    mut auth: AuthSession,
+    State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    if let Ok(Some(author)) = auth.logout().await {
+        let mut conn = state.pool.acquire().await?;
+        actions::audit_login("logout", "/logout", Some(author.id), None, client_ip(&headers, peer), &mut conn).await?;
+    }
+
     Ok(Redirect::to(&root_uri()))
 }
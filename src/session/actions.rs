@@ -1,23 +1,177 @@
+use std::net::IpAddr;
+use std::sync::LazyLock;
+
 use argon2::Argon2;
-use argon2::password_hash::{PasswordHash, PasswordVerifier};
-use sqlx::{PgConnection, query_as};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use chrono::{Duration, Utc};
+use rand::RngCore;
+use sqlx::{PgConnection, query, query_as};
+
+use crate::audit;
+use crate::models::{Author, NewAuditLogEntry, Passkey};
+use crate::utils::tokens::hash_token;
+use crate::utils::totp::{normalize_recovery_code, verify_code};
+
+/// after this many failed logins in a row the account is locked for `LOCKOUT_DURATION`
+const MAX_FAILED_LOGINS: i32 = 5;
+const LOCKOUT_DURATION: Duration = Duration::minutes(15);
+
+/// Verified instead of a password hash when there is no author with the given email address, so that
+/// failing logins take the same time whether the address exists or not
+static DUMMY_PASSWORD_HASH: LazyLock<String> =
+    LazyLock::new(|| hash_password("not a password").expect("could not hash dummy password"));
+
+/// Hashes a password with argon2 and a random salt, in the PHC string format stored in `encrypted_password`
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let mut salt = [0u8; 16];
+    rand::rng().fill_bytes(&mut salt);
 
-use crate::errors::AppError;
-use crate::models::Author;
+    let salt = SaltString::encode_b64(&salt)?;
+    let hash = Argon2::default().hash_password(password.as_bytes(), &salt)?;
 
-pub async fn get_author_by_email(user_email: &str, conn: &mut PgConnection) -> Result<Author, AppError> {
-    let author = query_as!(Author, "SELECT * FROM authors WHERE email = $1", user_email)
+    Ok(hash.to_string())
+}
+
+pub async fn get_author_by_email(user_email: &str, conn: &mut PgConnection) -> Result<Option<Author>, sqlx::Error> {
+    query_as!(Author, "SELECT * FROM authors WHERE email = $1", user_email)
+        .fetch_optional(conn)
+        .await
+}
+
+pub async fn get_author_by_id(id: i32, conn: &mut PgConnection) -> Result<Author, sqlx::Error> {
+    query_as!(Author, "SELECT * FROM authors WHERE id = $1", id)
         .fetch_one(conn)
+        .await
+}
+
+pub async fn get_author_by_url(url: &str, conn: &mut PgConnection) -> Result<Option<Author>, sqlx::Error> {
+    query_as!(Author, "SELECT * FROM authors WHERE url = $1", url)
+        .fetch_optional(conn)
+        .await
+}
+
+pub async fn get_passkey_by_credential_id(
+    credential_id: &str,
+    conn: &mut PgConnection,
+) -> Result<Option<Passkey>, sqlx::Error> {
+    query_as!(Passkey, "SELECT * FROM passkeys WHERE credential_id = $1", credential_id)
+        .fetch_optional(conn)
+        .await
+}
+
+/// Stores the updated signature counter and backup state after a successful passkey login
+pub async fn update_passkey_credential(
+    id: i32,
+    credential: &webauthn_rs::prelude::Passkey,
+    conn: &mut PgConnection,
+) -> Result<(), sqlx::Error> {
+    let passkey = serde_json::to_value(credential).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    let now = Utc::now().naive_utc();
+
+    query!("UPDATE passkeys SET passkey = $1, last_used_at = $2, updated_at = $2 WHERE id = $3", passkey, now, id)
+        .execute(conn)
         .await?;
 
-    Ok(author)
+    Ok(())
 }
 
-pub fn verify_password(author: &Author, password: &str) -> bool {
-    match PasswordHash::new(&author.encrypted_password) {
+/// Verifies the password of an author; without an author a dummy hash is verified and `false` returned
+pub fn verify_password(author: Option<&Author>, password: &str) -> bool {
+    let hash = author.map_or(DUMMY_PASSWORD_HASH.as_str(), |author| author.encrypted_password.as_str());
+
+    let valid = match PasswordHash::new(hash) {
         Ok(parsed_hash) => Argon2::default()
             .verify_password(password.as_bytes(), &parsed_hash)
             .is_ok(),
         _ => false,
+    };
+
+    valid && author.is_some()
+}
+
+/// Counts a failed login and locks the account when there have been too many in a row
+pub async fn record_failed_login(author_id: i32, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    query!(
+        r#"
+            UPDATE authors
+            SET
+              failed_login_attempts = CASE
+                WHEN failed_login_attempts + 1 >= $1 THEN 0
+                ELSE failed_login_attempts + 1
+              END,
+              locked_until = CASE WHEN failed_login_attempts + 1 >= $1 THEN $2 ELSE locked_until END
+            WHERE id = $3
+        "#,
+        MAX_FAILED_LOGINS,
+        Utc::now().naive_utc() + LOCKOUT_DURATION,
+        author_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn reset_failed_logins(author_id: i32, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    query!("UPDATE authors SET failed_login_attempts = 0, locked_until = NULL WHERE id = $1", author_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Checks a second factor: either a TOTP code or one of the author's unused recovery codes, which is
+/// used up by this. Accepted TOTP codes can't be used a second time.
+pub async fn verify_second_factor(author: &Author, code: &str, conn: &mut PgConnection) -> Result<bool, sqlx::Error> {
+    let Some(secret) = author.totp_secret.as_deref().filter(|_| author.has_two_factor()) else {
+        return Ok(false);
+    };
+
+    if let Some(step) = verify_code(secret, code, author.totp_last_step) {
+        let updated = query!(
+            "UPDATE authors SET totp_last_step = $1 WHERE id = $2 AND (totp_last_step IS NULL OR totp_last_step < $1)",
+            step,
+            author.id
+        )
+        .execute(conn)
+        .await?;
+
+        return Ok(updated.rows_affected() == 1);
     }
+
+    let code = normalize_recovery_code(code);
+    if code.is_empty() {
+        return Ok(false);
+    }
+
+    let used = query!(
+        "UPDATE recovery_codes SET used_at = $1 WHERE author_id = $2 AND code_hash = $3 AND used_at IS NULL",
+        Utc::now().naive_utc(),
+        author.id,
+        hash_token(&code)
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(used.rows_affected() == 1)
+}
+
+/// Records a login, a failed login or a logout (`action`) at `path` in the audit log
+pub async fn audit_login(
+    action: &str,
+    path: &str,
+    author_id: Option<i32>,
+    email: Option<String>,
+    ip: IpAddr,
+    conn: &mut PgConnection,
+) -> Result<(), sqlx::Error> {
+    let entry = NewAuditLogEntry {
+        author_id,
+        action: action.to_owned(),
+        path: path.to_owned(),
+        email,
+        ip: Some(ip.to_string()),
+    };
+
+    audit::record(&entry, conn).await
 }
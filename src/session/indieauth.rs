@@ -0,0 +1,219 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use askama::Template;
+use axum::extract::{ConnectInfo, Form, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{Html, IntoResponse, Redirect, Response};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::two_factor::complete_login;
+use super::{Show, actions};
+use crate::errors::AppError;
+use crate::indieauth::discovery::{discover_endpoints, normalize_profile_url};
+use crate::uri_helpers::*;
+use crate::utils::rate_limit::client_ip;
+use crate::utils::tokens::{constant_time_eq, generate_token, pkce_challenge};
+use crate::webmentions::fetch::build_client;
+use crate::{AppState, AuthSession};
+
+/// the session key of a login in progress
+const PENDING_LOGIN_KEY: &str = "indieauth_login";
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Deserialize)]
+pub struct IndieauthLoginForm {
+    me: String,
+    next: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PendingLogin {
+    me: String,
+    state: String,
+    code_verifier: String,
+    authorization_endpoint: String,
+    issuer: Option<String>,
+    next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CallbackParams {
+    code: Option<String>,
+    state: String,
+    iss: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfileResponse {
+    me: String,
+}
+
+fn login_failed(next: Option<String>, error: String) -> Result<Response, AppError> {
+    let html = Show {
+        lang: "en",
+        title: Some("Login"),
+        page_type: None,
+        page_image: None,
+        body_id: None,
+        logged_in: false,
+        email: "".to_owned(),
+        next,
+        error: Some(error),
+    }
+    .render()?;
+
+    Ok((StatusCode::UNAUTHORIZED, Html(html)).into_response())
+}
+
+/// Starts a login with the author's personal URL: discovers its authorization endpoint and sends the
+/// author there
+pub async fn start(auth: AuthSession, Form(form): Form<IndieauthLoginForm>) -> Result<Response, AppError> {
+    let Some(me) = normalize_profile_url(&form.me) else {
+        return login_failed(form.next, format!("{} is not a valid profile URL", form.me));
+    };
+
+    let endpoints = match discover_endpoints(&me).await {
+        Ok(endpoints) => endpoints,
+        Err(e) => return login_failed(form.next, format!("could not discover IndieAuth endpoints: {}", e)),
+    };
+
+    let pending = PendingLogin {
+        me: endpoints.me.to_string(),
+        state: generate_token(),
+        code_verifier: generate_token(),
+        authorization_endpoint: endpoints.authorization_endpoint.to_string(),
+        issuer: endpoints.issuer,
+        next: form.next,
+    };
+
+    let mut uri = endpoints.authorization_endpoint;
+    uri.query_pairs_mut()
+        .append_pair("response_type", "code")
+        .append_pair("client_id", &root_uri())
+        .append_pair("redirect_uri", &indieauth_login_callback_uri())
+        .append_pair("state", &pending.state)
+        .append_pair("code_challenge", &pkce_challenge(&pending.code_verifier))
+        .append_pair("code_challenge_method", "S256")
+        .append_pair("me", &pending.me);
+
+    auth.session
+        .insert(PENDING_LOGIN_KEY, &pending)
+        .await
+        .map_err(|e| AppError::InternalError(format!("could not store session: {}", e)))?;
+
+    Ok(Redirect::to(uri.as_str()).into_response())
+}
+
+/// The redirect back from the authorization endpoint: exchanges the code for the verified profile URL
+/// and logs in the author with this URL
+pub async fn callback(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Query(params): Query<CallbackParams>,
+) -> Result<Response, AppError> {
+    let ip = client_ip(&headers, peer);
+    if !state.login_ip_limiter.check(&ip.to_string()).await {
+        return Err(AppError::TooManyRequests("too many login attempts, try again later".to_owned()));
+    }
+
+    let pending = auth
+        .session
+        .remove::<PendingLogin>(PENDING_LOGIN_KEY)
+        .await
+        .map_err(|e| AppError::InternalError(format!("could not read session: {}", e)))?;
+
+    let Some(pending) = pending else {
+        return login_failed(None, "no login in progress".to_owned());
+    };
+
+    if !constant_time_eq(&params.state, &pending.state) {
+        return login_failed(pending.next, "the state parameter does not match".to_owned());
+    }
+
+    if let Some(error) = params.error {
+        let description = params.error_description.unwrap_or_default();
+        return login_failed(pending.next, format!("authorization failed: {} {}", error, description));
+    }
+
+    if pending.issuer.is_some() && params.iss != pending.issuer {
+        return login_failed(pending.next, "the issuer does not match".to_owned());
+    }
+
+    let Some(code) = params.code else {
+        return login_failed(pending.next, "no authorization code given".to_owned());
+    };
+
+    let me = match redeem_code(&pending, &code).await {
+        Ok(me) => me,
+        Err(e) => return login_failed(pending.next, e),
+    };
+
+    let mut conn = state.pool.acquire().await?;
+    let author = actions::get_author_by_url(me.as_str(), &mut conn).await?;
+
+    match author {
+        Some(author) if author.may_log_in() => {
+            complete_login(auth, author, pending.next, "/login/indieauth/callback", ip, &mut conn).await
+        }
+
+        author => {
+            let author_id = author.map(|author| author.id);
+            actions::audit_login(
+                "login_failed",
+                "/login/indieauth/callback",
+                author_id,
+                Some(me.to_string()),
+                ip,
+                &mut conn,
+            )
+            .await?;
+
+            login_failed(pending.next, format!("{} can't log in", me))
+        }
+    }
+}
+
+/// Exchanges the authorization code at the authorization endpoint and verifies the returned profile URL:
+/// if it differs from the one we started with, it has to use the same authorization endpoint.
+async fn redeem_code(pending: &PendingLogin, code: &str) -> Result<Url, String> {
+    let client = build_client(FETCH_TIMEOUT).map_err(|e| format!("could not build HTTP client: {}", e))?;
+
+    let profile = client
+        .post(&pending.authorization_endpoint)
+        .header("Accept", "application/json")
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("client_id", &root_uri()),
+            ("redirect_uri", &indieauth_login_callback_uri()),
+            ("code_verifier", &pending.code_verifier),
+        ])
+        .send()
+        .await
+        .and_then(|rsp| rsp.error_for_status())
+        .map_err(|e| format!("could not redeem the authorization code: {}", e))?
+        .json::<ProfileResponse>()
+        .await
+        .map_err(|e| format!("invalid response from the authorization endpoint: {}", e))?;
+
+    let me = normalize_profile_url(&profile.me).ok_or_else(|| format!("{} is not a valid profile URL", profile.me))?;
+
+    if me.as_str() != pending.me {
+        let endpoints = discover_endpoints(&me)
+            .await
+            .map_err(|e| format!("could not verify {}: {}", me, e))?;
+
+        if endpoints.authorization_endpoint.as_str() != pending.authorization_endpoint {
+            return Err(format!("{} uses another authorization endpoint", me));
+        }
+    }
+
+    Ok(me)
+}
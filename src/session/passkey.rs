@@ -0,0 +1,116 @@
+use std::net::SocketAddr;
+
+use axum::Json;
+use axum::extract::{ConnectInfo, State};
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use webauthn_rs::prelude::{PasskeyAuthentication, PublicKeyCredential};
+
+use super::{actions, log_in, redirect_target};
+use crate::account::actions::list_passkeys;
+use crate::errors::AppError;
+use crate::store::Credentials;
+use crate::utils::rate_limit::client_ip;
+use crate::{AppState, AuthSession};
+
+/// the session key of a passkey login in progress
+const PENDING_KEY: &str = "passkey_login";
+
+#[derive(Debug, Deserialize)]
+pub struct ChallengeForm {
+    email: String,
+    next: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PendingLogin {
+    state: PasskeyAuthentication,
+    email: String,
+    next: Option<String>,
+}
+
+/// Starts a passkey login: returns the request options for `navigator.credentials.get()` with the passkeys
+/// registered for the given email address
+pub async fn challenge(
+    auth: AuthSession,
+    State(state): State<AppState>,
+    Json(form): Json<ChallengeForm>,
+) -> Result<Response, AppError> {
+    let mut conn = state.pool.acquire().await?;
+
+    let passkeys = match actions::get_author_by_email(&form.email, &mut conn).await? {
+        Some(author) => list_passkeys(author.id, &mut conn).await?,
+        None => Vec::new(),
+    };
+
+    let credentials = passkeys
+        .iter()
+        .filter_map(|passkey| passkey.credential().ok())
+        .collect::<Vec<_>>();
+
+    if credentials.is_empty() {
+        return Err(AppError::BadRequest("there are no passkeys for this email address".to_owned()));
+    }
+
+    let (options, authentication) = state
+        .webauthn
+        .start_passkey_authentication(&credentials)
+        .map_err(|e| AppError::InternalError(format!("could not start passkey login: {}", e)))?;
+
+    let pending = PendingLogin {
+        state: authentication,
+        email: form.email,
+        next: form.next,
+    };
+
+    auth.session
+        .insert(PENDING_KEY, &pending)
+        .await
+        .map_err(|e| AppError::InternalError(format!("error writing session: {}", e)))?;
+
+    Ok(Json(options).into_response())
+}
+
+/// Verifies the signed challenge; passkeys require user verification, so no second factor is asked for
+pub async fn login(
+    mut auth: AuthSession,
+    State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(credential): Json<PublicKeyCredential>,
+) -> Result<Response, AppError> {
+    let ip = client_ip(&headers, peer);
+    if !state.login_ip_limiter.check(&ip.to_string()).await {
+        return Err(AppError::TooManyRequests("too many login attempts, try again later".to_owned()));
+    }
+
+    let pending = auth
+        .session
+        .remove::<PendingLogin>(PENDING_KEY)
+        .await
+        .map_err(|e| AppError::InternalError(format!("error reading session: {}", e)))?
+        .ok_or_else(|| AppError::BadRequest("no passkey login in progress".to_owned()))?;
+
+    let credentials = Credentials::Passkey {
+        credential: Box::new(credential),
+        state: Box::new(pending.state),
+    };
+
+    let author = auth
+        .authenticate(credentials)
+        .await
+        .map_err(|e| AppError::InternalError(format!("error logging in: {}", e)))?;
+
+    let mut conn = state.pool.acquire().await?;
+
+    let Some(author) = author else {
+        actions::audit_login("login_failed", "/login/passkey", None, Some(pending.email), ip, &mut conn).await?;
+        return Err(AppError::Unauthorized);
+    };
+
+    log_in(&mut auth, &author, "/login/passkey", ip, &mut conn).await?;
+
+    Ok(Json(json!({ "location": redirect_target(pending.next.as_deref()) })).into_response())
+}
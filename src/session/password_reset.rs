@@ -0,0 +1,185 @@
+use std::net::SocketAddr;
+
+use askama::Template;
+use axum::extract::{ConnectInfo, Form, Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{Html, IntoResponse, Redirect, Response};
+use chrono::Duration;
+use serde::Deserialize;
+
+use super::actions;
+use crate::AppState;
+use crate::account::actions::{check_new_password, create_password_token, get_password_token, reset_password};
+use crate::account::mail_sender;
+use crate::errors::AppError;
+use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
+use crate::utils::rate_limit::client_ip;
+
+/// how long the link in a password reset mail can be used
+const RESET_VALIDITY: Duration = Duration::hours(1);
+
+#[derive(Debug, Deserialize)]
+pub struct RequestForm {
+    email: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PasswordForm {
+    password: String,
+    password_confirmation: String,
+}
+
+#[derive(Template)]
+#[template(path = "password_reset/new.html.j2")]
+pub struct New<'a> {
+    lang: &'a str,
+    title: Option<&'a str>,
+    page_type: Option<&'a str>,
+    page_image: Option<&'a str>,
+    body_id: Option<&'a str>,
+    logged_in: bool,
+
+    email: String,
+    error: Option<&'a str>,
+    sent: bool,
+}
+
+#[derive(Template)]
+#[template(path = "password_reset/edit.html.j2")]
+pub struct Edit<'a> {
+    lang: &'a str,
+    title: Option<&'a str>,
+    page_type: Option<&'a str>,
+    page_image: Option<&'a str>,
+    body_id: Option<&'a str>,
+    logged_in: bool,
+
+    token: String,
+    error: Option<String>,
+}
+
+fn render_new(email: String, error: Option<&str>, sent: bool) -> Result<String, AppError> {
+    let html = New {
+        lang: "en",
+        title: Some("Reset password"),
+        page_type: None,
+        page_image: None,
+        body_id: None,
+        logged_in: false,
+        email,
+        error,
+        sent,
+    }
+    .render()?;
+
+    Ok(html)
+}
+
+fn render_edit(token: String, error: Option<String>) -> Result<String, AppError> {
+    let html = Edit {
+        lang: "en",
+        title: Some("Choose a password"),
+        page_type: None,
+        page_image: None,
+        body_id: None,
+        logged_in: false,
+        token,
+        error,
+    }
+    .render()?;
+
+    Ok(html)
+}
+
+/// the message for unknown, used and expired links
+const INVALID_LINK: &str = "The link is invalid or has expired. Please request a new one.";
+
+pub async fn new() -> Result<impl IntoResponse, AppError> {
+    Ok(Html(render_new("".to_owned(), None, false)?))
+}
+
+/// Mails a link to choose a new password; the answer is the same whether there is an account for the address
+/// or not
+pub async fn create(
+    State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Form(form): Form<RequestForm>,
+) -> Result<Response, AppError> {
+    let ip = client_ip(&headers, peer);
+    if !state.login_ip_limiter.check(&ip.to_string()).await {
+        return Err(AppError::TooManyRequests("too many attempts, try again later".to_owned()));
+    }
+
+    let mut conn = state.pool.acquire().await?;
+    let email = form.email.trim();
+    let author = actions::get_author_by_email(email, &mut conn).await?;
+    let author_id = author.as_ref().map(|author| author.id);
+
+    if let Some(author) = author.filter(|author| !author.is_disabled()) {
+        let token = create_password_token(author.id, "reset", RESET_VALIDITY, &mut conn).await?;
+        tokio::task::spawn_blocking(move || mail_sender::send_password_reset(&author, &token));
+    }
+
+    actions::audit_login(
+        "password_reset_requested",
+        "/password-reset",
+        author_id,
+        Some(email.to_owned()),
+        ip,
+        &mut conn,
+    )
+    .await?;
+
+    Ok(Html(render_new(email.to_owned(), None, true)?).into_response())
+}
+
+pub async fn edit(State(state): State<AppState>, Path(token): Path<String>) -> Result<Response, AppError> {
+    let mut conn = state.pool.acquire().await?;
+
+    if get_password_token(&token, &mut conn).await?.is_none() {
+        let html = render_new("".to_owned(), Some(INVALID_LINK), false)?;
+        return Ok((StatusCode::NOT_FOUND, Html(html)).into_response());
+    }
+
+    Ok(Html(render_edit(token, None)?).into_response())
+}
+
+pub async fn update(
+    State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(token): Path<String>,
+    Form(form): Form<PasswordForm>,
+) -> Result<Response, AppError> {
+    let ip = client_ip(&headers, peer);
+    if !state.login_ip_limiter.check(&ip.to_string()).await {
+        return Err(AppError::TooManyRequests("too many attempts, try again later".to_owned()));
+    }
+
+    let mut conn = state.pool.acquire().await?;
+
+    let Some(password_token) = get_password_token(&token, &mut conn).await? else {
+        let html = render_new("".to_owned(), Some(INVALID_LINK), false)?;
+        return Ok((StatusCode::NOT_FOUND, Html(html)).into_response());
+    };
+
+    if let Err(error) = check_new_password(&form.password, &form.password_confirmation) {
+        let html = render_edit(token, Some(error))?;
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Html(html)).into_response());
+    }
+
+    let encrypted_password = actions::hash_password(&form.password)
+        .map_err(|e| AppError::InternalError(format!("could not hash password: {}", e)))?;
+
+    if !reset_password(&password_token, &encrypted_password, &mut conn).await? {
+        let html = render_new("".to_owned(), Some(INVALID_LINK), false)?;
+        return Ok((StatusCode::NOT_FOUND, Html(html)).into_response());
+    }
+
+    actions::audit_login("password_reset", "/password-reset", Some(password_token.author_id), None, ip, &mut conn)
+        .await?;
+
+    Ok(Redirect::to(&login_uri()).into_response())
+}
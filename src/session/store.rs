@@ -0,0 +1,196 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum_login::tower_sessions::cookie::time::OffsetDateTime;
+use axum_login::tower_sessions::session::{Id, Record};
+use axum_login::tower_sessions::session_store::{self, ExpiredDeletion, SessionStore};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::{PgConnection, PgPool, query, query_as};
+
+use crate::models::SessionInfo;
+
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// the session key of the `SessionInfo` of logged in authors
+pub const SESSION_INFO_KEY: &str = "session_info";
+
+/// Session store keeping the sessions in the `sessions` table, so that logins survive restarts
+#[derive(Debug, Clone)]
+pub struct PgSessionStore {
+    pool: PgPool,
+}
+
+struct SessionRow {
+    id: String,
+    data: serde_json::Value,
+    expiry_date: NaiveDateTime,
+}
+
+impl PgSessionStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Periodically removes expired sessions; meant to be run as a background task
+    pub async fn run_cleanup(self) {
+        loop {
+            if let Err(e) = self.delete_expired().await {
+                tracing::error!("could not delete expired sessions: {}", e);
+            }
+
+            tokio::time::sleep(CLEANUP_INTERVAL).await;
+        }
+    }
+
+    async fn insert_record(record: &Record, conn: &mut PgConnection) -> session_store::Result<()> {
+        let data = serde_json::to_value(&record.data).map_err(|e| session_store::Error::Encode(e.to_string()))?;
+        let info = session_info(record);
+
+        query!(
+            r#"
+                INSERT INTO sessions (id, data, expiry_date, author_id, ip, user_agent, inserted_at, last_seen_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            record.id.to_string(),
+            data,
+            to_naive(record.expiry_date),
+            info.as_ref().map(|info| info.author_id),
+            info.as_ref().map(|info| info.ip.clone()),
+            info.as_ref().and_then(|info| info.user_agent.clone()),
+            info.as_ref().map(|info| info.created_at),
+            info.as_ref().map(|info| info.last_seen_at)
+        )
+        .execute(conn)
+        .await
+        .map_err(backend)?;
+
+        Ok(())
+    }
+}
+
+/// The `SessionInfo` the session middleware stored in the session, if the session belongs to an author
+fn session_info(record: &Record) -> Option<SessionInfo> {
+    record
+        .data
+        .get(SESSION_INFO_KEY)
+        .and_then(|value| serde_json::from_value(value.clone()).ok())
+}
+
+#[async_trait]
+impl SessionStore for PgSessionStore {
+    async fn create(&self, record: &mut Record) -> session_store::Result<()> {
+        let mut tx = self.pool.begin().await.map_err(backend)?;
+
+        loop {
+            let exists = query!("SELECT id FROM sessions WHERE id = $1", record.id.to_string())
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(backend)?
+                .is_some();
+
+            if !exists {
+                break;
+            }
+
+            record.id = Id::default();
+        }
+
+        Self::insert_record(record, &mut tx).await?;
+        tx.commit().await.map_err(backend)?;
+
+        Ok(())
+    }
+
+    /// Only updates sessions still existing: a revoked session must not be written back by a request which
+    /// has been running while it was revoked
+    async fn save(&self, record: &Record) -> session_store::Result<()> {
+        let data = serde_json::to_value(&record.data).map_err(|e| session_store::Error::Encode(e.to_string()))?;
+        let info = session_info(record);
+
+        query!(
+            r#"
+                UPDATE sessions
+                SET data = $2, expiry_date = $3, author_id = $4, ip = $5, user_agent = $6, inserted_at = $7,
+                  last_seen_at = $8
+                WHERE id = $1
+            "#,
+            record.id.to_string(),
+            data,
+            to_naive(record.expiry_date),
+            info.as_ref().map(|info| info.author_id),
+            info.as_ref().map(|info| info.ip.clone()),
+            info.as_ref().and_then(|info| info.user_agent.clone()),
+            info.as_ref().map(|info| info.created_at),
+            info.as_ref().map(|info| info.last_seen_at)
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(backend)?;
+
+        Ok(())
+    }
+
+    async fn load(&self, session_id: &Id) -> session_store::Result<Option<Record>> {
+        let row = query_as!(
+            SessionRow,
+            "SELECT id, data, expiry_date FROM sessions WHERE id = $1 AND expiry_date > $2",
+            session_id.to_string(),
+            Utc::now().naive_utc()
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(backend)?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let id = row
+            .id
+            .parse::<Id>()
+            .map_err(|e| session_store::Error::Decode(e.to_string()))?;
+        let data = serde_json::from_value(row.data).map_err(|e| session_store::Error::Decode(e.to_string()))?;
+
+        Ok(Some(Record {
+            id,
+            data,
+            expiry_date: from_naive(row.expiry_date)?,
+        }))
+    }
+
+    async fn delete(&self, session_id: &Id) -> session_store::Result<()> {
+        query!("DELETE FROM sessions WHERE id = $1", session_id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(backend)?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ExpiredDeletion for PgSessionStore {
+    async fn delete_expired(&self) -> session_store::Result<()> {
+        query!("DELETE FROM sessions WHERE expiry_date <= $1", Utc::now().naive_utc())
+            .execute(&self.pool)
+            .await
+            .map_err(backend)?;
+
+        Ok(())
+    }
+}
+
+fn backend(e: sqlx::Error) -> session_store::Error {
+    session_store::Error::Backend(e.to_string())
+}
+
+fn to_naive(date: OffsetDateTime) -> NaiveDateTime {
+    DateTime::from_timestamp(date.unix_timestamp(), date.nanosecond())
+        .unwrap_or_default()
+        .naive_utc()
+}
+
+fn from_naive(date: NaiveDateTime) -> session_store::Result<OffsetDateTime> {
+    let timestamp = date.and_utc().timestamp_nanos_opt().unwrap_or_default();
+    OffsetDateTime::from_unix_timestamp_nanos(timestamp.into()).map_err(|e| session_store::Error::Decode(e.to_string()))
+}
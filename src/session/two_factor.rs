@@ -0,0 +1,148 @@
+use std::net::{IpAddr, SocketAddr};
+
+use askama::Template;
+use axum::extract::{ConnectInfo, Form, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{Html, IntoResponse, Redirect, Response};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+use super::{actions, log_in, redirect_target};
+use crate::errors::AppError;
+use crate::models::Author;
+use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
+use crate::utils::rate_limit::client_ip;
+use crate::{AppState, AuthSession};
+
+/// the session key of a login waiting for the second factor
+const PENDING_KEY: &str = "two_factor_login";
+
+/// how long the second factor may take after the first one, in seconds
+const PENDING_TTL: i64 = 5 * 60;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PendingTwoFactor {
+    author_id: i32,
+    next: Option<String>,
+    started_at: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TwoFactorForm {
+    code: String,
+}
+
+#[derive(Template)]
+#[template(path = "login_two_factor.html.j2")]
+pub struct Show<'a> {
+    lang: &'a str,
+    title: Option<&'a str>,
+    page_type: Option<&'a str>,
+    page_image: Option<&'a str>,
+    body_id: Option<&'a str>,
+    logged_in: bool,
+
+    error: Option<String>,
+}
+
+/// Finishes a login after the first factor: authors with two-factor authentication enabled have to
+/// provide their second factor before they are logged in.
+pub async fn complete_login(
+    mut auth: AuthSession,
+    author: Author,
+    next: Option<String>,
+    path: &str,
+    ip: IpAddr,
+    conn: &mut PgConnection,
+) -> Result<Response, AppError> {
+    if !author.has_two_factor() {
+        log_in(&mut auth, &author, path, ip, conn).await?;
+        return Ok(Redirect::to(&redirect_target(next.as_deref())).into_response());
+    }
+
+    let pending = PendingTwoFactor {
+        author_id: author.id,
+        next,
+        started_at: Utc::now().timestamp(),
+    };
+
+    auth.session
+        .insert(PENDING_KEY, &pending)
+        .await
+        .map_err(|e| AppError::InternalError(format!("error writing session: {}", e)))?;
+
+    Ok(Redirect::to(&two_factor_login_uri()).into_response())
+}
+
+async fn pending_login(auth: &AuthSession) -> Result<Option<PendingTwoFactor>, AppError> {
+    let pending = auth
+        .session
+        .get::<PendingTwoFactor>(PENDING_KEY)
+        .await
+        .map_err(|e| AppError::InternalError(format!("error reading session: {}", e)))?;
+
+    Ok(pending.filter(|pending| Utc::now().timestamp() - pending.started_at < PENDING_TTL))
+}
+
+fn render(error: Option<String>) -> Result<String, AppError> {
+    let html = Show {
+        lang: "en",
+        title: Some("Login"),
+        page_type: None,
+        page_image: None,
+        body_id: None,
+        logged_in: false,
+        error,
+    }
+    .render()?;
+
+    Ok(html)
+}
+
+pub async fn show(auth: AuthSession) -> Result<Response, AppError> {
+    if pending_login(&auth).await?.is_none() {
+        return Ok(Redirect::to(&login_uri()).into_response());
+    }
+
+    Ok(Html(render(None)?).into_response())
+}
+
+/// Failed codes count towards the lockout of the account just like wrong passwords
+pub async fn verify(
+    mut auth: AuthSession,
+    State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Form(form): Form<TwoFactorForm>,
+) -> Result<Response, AppError> {
+    let ip = client_ip(&headers, peer);
+    if !state.login_ip_limiter.check(&ip.to_string()).await {
+        return Err(AppError::TooManyRequests("too many login attempts, try again later".to_owned()));
+    }
+
+    let Some(pending) = pending_login(&auth).await? else {
+        return Ok(Redirect::to(&login_uri()).into_response());
+    };
+
+    let mut conn = state.pool.acquire().await?;
+    let author = actions::get_author_by_id(pending.author_id, &mut conn).await?;
+
+    if !author.may_log_in() || !actions::verify_second_factor(&author, &form.code, &mut conn).await? {
+        actions::record_failed_login(author.id, &mut conn).await?;
+        actions::audit_login("login_failed", "/login/two-factor", Some(author.id), None, ip, &mut conn).await?;
+
+        let html = render(Some("the code is invalid or the account is locked for a while".to_owned()))?;
+        return Ok((StatusCode::UNAUTHORIZED, Html(html)).into_response());
+    }
+
+    auth.session
+        .remove::<PendingTwoFactor>(PENDING_KEY)
+        .await
+        .map_err(|e| AppError::InternalError(format!("error writing session: {}", e)))?;
+
+    log_in(&mut auth, &author, "/login/two-factor", ip, &mut conn).await?;
+
+    Ok(Redirect::to(&redirect_target(pending.next.as_deref())).into_response())
+}
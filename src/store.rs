@@ -1,24 +1,79 @@
+use std::sync::Arc;
+
 use axum_login::AuthnBackend;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use serde::Deserialize;
 use sqlx::{PgPool, query_as};
+use webauthn_rs::prelude::{PasskeyAuthentication, PublicKeyCredential, Webauthn};
 
 use crate::models::Author;
+use crate::session::actions::{get_passkey_by_credential_id, update_passkey_credential};
 
 #[derive(Clone, Debug)]
 pub struct Store {
     pool: PgPool,
+    webauthn: Arc<Webauthn>,
 }
 
 impl Store {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(pool: PgPool, webauthn: Arc<Webauthn>) -> Self {
+        Self { pool, webauthn }
+    }
+
+    async fn authenticate_password(&self, email: &str, password: &str) -> Result<Option<Author>, sqlx::Error> {
+        let user = query_as!(Author, "SELECT * FROM authors WHERE email = $1", email)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        if crate::session::actions::verify_password(user.as_ref(), password) {
+            Ok(user.filter(|user| user.may_log_in()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Verifies a WebAuthn assertion against the challenge the login has been started with and updates the
+    /// signature counter of the passkey
+    async fn authenticate_passkey(
+        &self,
+        credential: &PublicKeyCredential,
+        state: &PasskeyAuthentication,
+    ) -> Result<Option<Author>, sqlx::Error> {
+        let mut conn = self.pool.acquire().await?;
+        let credential_id = URL_SAFE_NO_PAD.encode(credential.get_credential_id());
+
+        let Some(passkey) = get_passkey_by_credential_id(&credential_id, &mut conn).await? else {
+            return Ok(None);
+        };
+
+        let Ok(result) = self.webauthn.finish_passkey_authentication(credential, state) else {
+            return Ok(None);
+        };
+
+        let mut stored = passkey.credential().map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        if stored.cred_id() != result.cred_id() {
+            return Ok(None);
+        }
+
+        stored.update_credential(&result);
+        update_passkey_credential(passkey.id, &stored, &mut conn).await?;
+
+        let user = self.get_user(&passkey.author_id).await?;
+        Ok(user.filter(|user| user.may_log_in()))
     }
 }
 
 #[derive(Debug, Clone, Deserialize)]
-pub struct Credentials {
-    pub email: String,
-    pub password: String,
+pub enum Credentials {
+    Password {
+        email: String,
+        password: String,
+    },
+    Passkey {
+        credential: Box<PublicKeyCredential>,
+        state: Box<PasskeyAuthentication>,
+    },
 }
 
 impl AuthnBackend for Store {
@@ -27,18 +82,9 @@ impl AuthnBackend for Store {
     type Error = sqlx::Error;
 
     async fn authenticate(&self, creds: Self::Credentials) -> Result<Option<Self::User>, Self::Error> {
-        let user = query_as!(Author, "SELECT * FROM authors WHERE email = $1", creds.email)
-            .fetch_optional(&self.pool)
-            .await?;
-
-        let Some(user) = user else {
-            return Ok(None);
-        };
-
-        if crate::session::actions::verify_password(&user, &creds.password) {
-            Ok(Some(user))
-        } else {
-            Ok(None)
+        match creds {
+            Credentials::Password { email, password } => self.authenticate_password(&email, &password).await,
+            Credentials::Passkey { credential, state } => self.authenticate_passkey(&credential, &state).await,
         }
     }
 
@@ -47,6 +93,7 @@ impl AuthnBackend for Store {
             .fetch_optional(&self.pool)
             .await?;
 
-        Ok(user)
+        // sessions of disabled authors end with their next request
+        Ok(user.filter(|user| !user.is_disabled()))
     }
 }
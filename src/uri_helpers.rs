@@ -1,16 +1,26 @@
 use std::env;
 
+pub mod account;
 pub mod articles;
 pub mod deafies;
+pub mod indieauth;
 pub mod likes;
+pub mod media;
+pub mod mentions;
 pub mod notes;
 pub mod pictures;
+pub mod webmentions;
 
+pub use account::*;
 pub use articles::*;
 pub use deafies::*;
+pub use indieauth::*;
 pub use likes::*;
+pub use media::*;
+pub use mentions::*;
 pub use notes::*;
 pub use pictures::*;
+pub use webmentions::*;
 
 const ASSET_VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -62,6 +72,51 @@ pub fn login_uri() -> String {
     uri
 }
 
+pub fn indieauth_login_uri() -> String {
+    let mut uri = login_uri();
+    uri.push_str("/indieauth");
+    uri
+}
+
+pub fn indieauth_login_callback_uri() -> String {
+    let mut uri = indieauth_login_uri();
+    uri.push_str("/callback");
+    uri
+}
+
+pub fn passkey_login_uri() -> String {
+    let mut uri = login_uri();
+    uri.push_str("/passkey");
+    uri
+}
+
+pub fn passkey_login_challenge_uri() -> String {
+    let mut uri = passkey_login_uri();
+    uri.push_str("/challenge");
+    uri
+}
+
+pub fn two_factor_login_uri() -> String {
+    let mut uri = login_uri();
+    uri.push_str("/two-factor");
+    uri
+}
+
+pub fn password_reset_uri() -> String {
+    let mut uri = root_uri();
+    if !uri.ends_with('/') {
+        uri.push('/');
+    }
+
+    uri.push_str("password-reset");
+
+    uri
+}
+
+pub fn password_reset_token_uri(token: &str) -> String {
+    format!("{}/{}", password_reset_uri(), token)
+}
+
 pub fn logout_uri() -> String {
     let mut uri = root_uri();
     if !uri.ends_with('/') {
@@ -82,3 +137,26 @@ pub fn webmentions_endpoint_uri() -> String {
     uri.push_str("webmentions");
     uri
 }
+
+pub fn micropub_endpoint_uri() -> String {
+    let mut uri = root_uri();
+    if !uri.ends_with('/') {
+        uri.push('/');
+    }
+
+    uri.push_str("micropub");
+    uri
+}
+
+pub fn micropub_media_endpoint_uri() -> String {
+    let mut uri = micropub_endpoint_uri();
+    uri.push_str("/media");
+    uri
+}
+
+pub fn webmention_status_uri(status_token: &str) -> String {
+    let mut uri = webmentions_endpoint_uri();
+    uri.push('/');
+    uri.push_str(status_token);
+    uri
+}
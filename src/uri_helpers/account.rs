@@ -0,0 +1,75 @@
+use crate::models::{ActiveSession, Author, Passkey};
+use crate::uri_helpers::root_uri;
+
+fn account_uri(path: &str) -> String {
+    let mut uri = root_uri();
+    if !uri.ends_with('/') {
+        uri.push('/');
+    }
+
+    uri.push_str("admin/account");
+    uri.push_str(path);
+    uri
+}
+
+pub fn profile_uri() -> String {
+    account_uri("")
+}
+
+pub fn password_uri() -> String {
+    account_uri("/password")
+}
+
+pub fn two_factor_uri() -> String {
+    account_uri("/two-factor")
+}
+
+pub fn recovery_codes_uri() -> String {
+    account_uri("/two-factor/recovery-codes")
+}
+
+pub fn disable_two_factor_uri() -> String {
+    account_uri("/two-factor/disable")
+}
+
+pub fn passkeys_uri() -> String {
+    account_uri("/passkeys")
+}
+
+pub fn passkey_challenge_uri() -> String {
+    account_uri("/passkeys/challenge")
+}
+
+pub fn delete_passkey_uri(passkey: &Passkey) -> String {
+    account_uri(&format!("/passkeys/{}/delete", passkey.id))
+}
+
+pub fn sessions_uri() -> String {
+    account_uri("/sessions")
+}
+
+pub fn delete_session_uri(session: &ActiveSession) -> String {
+    account_uri(&format!("/sessions/{}/delete", session.key))
+}
+
+pub fn delete_other_sessions_uri() -> String {
+    account_uri("/sessions/others/delete")
+}
+
+pub fn authors_uri() -> String {
+    let mut uri = root_uri();
+    if !uri.ends_with('/') {
+        uri.push('/');
+    }
+
+    uri.push_str("admin/authors");
+    uri
+}
+
+pub fn disable_author_uri(author: &Author) -> String {
+    format!("{}/{}/disable", authors_uri(), author.id)
+}
+
+pub fn enable_author_uri(author: &Author) -> String {
+    format!("{}/{}/enable", authors_uri(), author.id)
+}
@@ -0,0 +1,35 @@
+use crate::uri_helpers::root_uri;
+
+fn indieauth_uri(path: &str) -> String {
+    let mut uri = root_uri();
+    if !uri.ends_with('/') {
+        uri.push('/');
+    }
+
+    uri.push_str(path);
+    uri
+}
+
+pub fn indieauth_metadata_uri() -> String {
+    indieauth_uri(".well-known/oauth-authorization-server")
+}
+
+pub fn authorization_endpoint_uri() -> String {
+    indieauth_uri("indieauth/auth")
+}
+
+pub fn authorization_approve_uri() -> String {
+    indieauth_uri("indieauth/auth/approve")
+}
+
+pub fn token_endpoint_uri() -> String {
+    indieauth_uri("indieauth/token")
+}
+
+pub fn introspection_endpoint_uri() -> String {
+    indieauth_uri("indieauth/introspect")
+}
+
+pub fn revocation_endpoint_uri() -> String {
+    indieauth_uri("indieauth/revoke")
+}
@@ -0,0 +1,16 @@
+use crate::models::MediaFile;
+use crate::uri_helpers::root_uri;
+
+pub fn media_file_uri(media_file: &MediaFile) -> String {
+    let mut uri = root_uri();
+    if !uri.ends_with('/') {
+        uri.push('/');
+    }
+
+    uri.push_str("media/");
+    uri.push_str(&media_file.id.to_string());
+    uri.push('/');
+    uri.push_str(&media_file.file_name);
+
+    uri
+}
@@ -0,0 +1,72 @@
+use crate::models::{Mention, MentionDomain};
+use crate::uri_helpers::root_uri;
+
+pub fn admin_mentions_uri(status: Option<&str>) -> String {
+    let mut uri = root_uri();
+    if !uri.ends_with('/') {
+        uri.push('/');
+    }
+
+    uri.push_str("admin/mentions");
+
+    if let Some(status) = status {
+        uri.push_str("?status=");
+        uri.push_str(status);
+    }
+
+    uri
+}
+
+pub fn filtered_admin_mentions_uri(status: &str, object_type: &str) -> String {
+    let mut uri = admin_mentions_uri(Some(status));
+
+    if !object_type.is_empty() {
+        uri.push_str("&object_type=");
+        uri.push_str(object_type);
+    }
+
+    uri
+}
+
+pub fn approve_mention_uri(mention: &Mention) -> String {
+    let mut uri = admin_mentions_uri(None);
+    uri.push('/');
+    uri.push_str(&mention.id.to_string());
+    uri.push_str("/approve");
+
+    uri
+}
+
+pub fn reject_mention_uri(mention: &Mention) -> String {
+    let mut uri = admin_mentions_uri(None);
+    uri.push('/');
+    uri.push_str(&mention.id.to_string());
+    uri.push_str("/reject");
+
+    uri
+}
+
+pub fn delete_mention_uri(mention: &Mention) -> String {
+    let mut uri = admin_mentions_uri(None);
+    uri.push('/');
+    uri.push_str(&mention.id.to_string());
+    uri.push_str("/delete");
+
+    uri
+}
+
+pub fn mention_domains_uri() -> String {
+    let mut uri = admin_mentions_uri(None);
+    uri.push_str("/domains");
+
+    uri
+}
+
+pub fn delete_mention_domain_uri(domain: &MentionDomain) -> String {
+    let mut uri = mention_domains_uri();
+    uri.push('/');
+    uri.push_str(&domain.id.to_string());
+    uri.push_str("/delete");
+
+    uri
+}
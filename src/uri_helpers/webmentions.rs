@@ -0,0 +1,19 @@
+use urlencoding::encode;
+
+use crate::uri_helpers::root_uri;
+
+pub fn sent_webmentions_uri(source: Option<&str>) -> String {
+    let mut uri = root_uri();
+    if !uri.ends_with('/') {
+        uri.push('/');
+    }
+
+    uri.push_str("admin/webmentions");
+
+    if let Some(source) = source {
+        uri.push_str("?source=");
+        uri.push_str(&encode(source));
+    }
+
+    uri
+}
@@ -7,8 +7,14 @@ use pulldown_cmark::{Options, Parser, html};
 
 use crate::models::Note;
 
+pub mod csrf;
 pub mod img;
+pub mod mail;
 pub mod paging;
+pub mod rate_limit;
+pub mod tokens;
+pub mod totp;
+pub mod webauthn;
 
 pub static MONTHS: [&str; 12] = [
     "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
@@ -22,10 +28,20 @@ pub fn image_base_path() -> String {
     env::var("IMAGE_BASE_PATH").expect("env variable IMAGE_BASE_PATH not set")
 }
 
+/// Media endpoint uploads live next to the pictures, in a directory of their own
+pub fn media_base_path() -> String {
+    format!("{}/media", image_base_path())
+}
+
 pub fn deafie_image_base_path() -> String {
     env::var("DEAFIE_IMAGE_BASE_PATH").expect("env variable DEAFIE_IMAGE_BASE_PATH not set")
 }
 
+/// When `REQUIRE_TWO_FACTOR` is set, authors have to enroll a second factor before they can use the admin
+pub fn two_factor_required() -> bool {
+    env::var("REQUIRE_TWO_FACTOR").is_ok_and(|value| value == "true" || value == "1")
+}
+
 pub fn static_path() -> String {
     let mut str = base_path();
     str.push_str("/static/");
@@ -0,0 +1,67 @@
+use std::future::Future;
+
+use askama::Template;
+
+use super::tokens::generate_token;
+use crate::AuthSession;
+use crate::uri_helpers::*;
+
+/// the session key of the CSRF token
+const SESSION_KEY: &str = "csrf_token";
+
+/// the form field carrying the CSRF token
+pub const FIELD_NAME: &str = "csrf_token";
+
+/// the header carrying the CSRF token for requests made from JavaScript
+pub const HEADER_NAME: &str = "x-csrf-token";
+
+tokio::task_local! {
+    static CSRF_TOKEN: String;
+}
+
+#[derive(Template)]
+#[template(path = "csrf_error.html.j2")]
+pub struct CsrfError<'a> {
+    lang: &'a str,
+    title: Option<&'a str>,
+    page_type: Option<&'a str>,
+    page_image: Option<&'a str>,
+    body_id: Option<&'a str>,
+    logged_in: bool,
+}
+
+impl CsrfError<'_> {
+    pub fn new() -> Self {
+        Self {
+            lang: "en",
+            title: Some("Invalid form submission"),
+            page_type: None,
+            page_image: None,
+            body_id: None,
+            logged_in: true,
+        }
+    }
+}
+
+/// Returns the CSRF token of the session, creating one on first use; tokens live as long as the session
+pub async fn session_token(auth: &AuthSession) -> Result<String, tower_sessions::session::Error> {
+    if let Some(token) = auth.session.get::<String>(SESSION_KEY).await? {
+        return Ok(token);
+    }
+
+    let token = generate_token();
+    auth.session.insert(SESSION_KEY, &token).await?;
+
+    Ok(token)
+}
+
+/// Runs `fut` with `token` being the CSRF token rendered into forms
+pub async fn with_token<F: Future>(token: String, fut: F) -> F::Output {
+    CSRF_TOKEN.scope(token, fut).await
+}
+
+/// The CSRF token of the current request, for the hidden `csrf_token` field of forms; empty for visitors
+/// who aren't logged in, their requests aren't checked.
+pub fn csrf_token() -> String {
+    CSRF_TOKEN.try_with(|token| token.clone()).unwrap_or_default()
+}
@@ -1,6 +1,8 @@
 use anyhow::{Error, Result, anyhow};
 use exif::{Exif, In, Tag};
-use image::DynamicImage;
+use image::{DynamicImage, GenericImageView, imageops};
+
+const THUMB_ASPEC_RATIO: f32 = 1.0;
 
 pub fn read_exif(path: &str) -> Result<Exif, Error> {
     let file = std::fs::File::open(path)?;
@@ -25,7 +27,7 @@ pub fn correct_orientation(mut img: DynamicImage, orientation: u32) -> DynamicIm
         img = img.rotate180();
     }
 
-    if orientation % 2 == 0 {
+    if orientation.is_multiple_of(2) {
         img = img.fliph();
     }
 
@@ -42,3 +44,41 @@ pub fn get_orientation(exif: &Exif) -> u32 {
         _ => 0,
     }
 }
+
+/// Generates the large and thumbnail versions of `{base}/original/{file_name}` into
+/// `{base}/large` and `{base}/thumbnail`
+pub fn generate_versions(base: &str, file_name: &str) -> Result<()> {
+    let path = format!("{}/original/{}", base, file_name);
+    let mut img = image::open(&path)?;
+
+    if let Ok(exif) = read_exif(&path) {
+        let orientation = get_orientation(&exif);
+        img = correct_orientation(img, orientation);
+    }
+
+    let path = format!("{}/large/{}", base, file_name);
+    let new_img = img.resize(800, 600, imageops::FilterType::CatmullRom);
+    new_img.save(path)?;
+
+    let path = format!("{}/thumbnail/{}", base, file_name);
+    let (width, height) = img.dimensions();
+    let aspect_ratio = width as f32 / height as f32;
+
+    let img = if aspect_ratio != THUMB_ASPEC_RATIO {
+        let mid_x = width / 2;
+        let mid_y = height / 2;
+
+        if width > height {
+            img.crop(mid_x - (height / 2), mid_y - (height / 2), height, height)
+        } else {
+            img.crop(mid_x - (width / 2), mid_y - (width / 2), width, width)
+        }
+    } else {
+        img
+    };
+
+    let new_img = img.resize_exact(600, 600, imageops::FilterType::CatmullRom);
+    new_img.save(path)?;
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use std::env;
+
+use anyhow::Result;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+
+/// the sender of all mails the site sends
+const FROM: &str = "Christian Kruse <christian@kruse.cool>";
+
+/// Sends a plain text mail via the SMTP relay configured in `MAIL_HOST`, `MAIL_USER` and `MAIL_PASS`. This
+/// blocks, so call it from `spawn_blocking`.
+pub fn send_mail(to: &str, subject: &str, body: String) -> Result<()> {
+    let host = env::var("MAIL_HOST")?;
+    let mail_user = env::var("MAIL_USER")?;
+    let mail_pass = env::var("MAIL_PASS")?;
+
+    let email = Message::builder()
+        .from(FROM.parse()?)
+        .to(to.parse()?)
+        .subject(subject)
+        .body(body)?;
+
+    let creds = Credentials::new(mail_user, mail_pass);
+    let mailer = SmtpTransport::relay(host.as_str())?.credentials(creds).build();
+    mailer.send(&email)?;
+
+    Ok(())
+}
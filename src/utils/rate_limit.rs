@@ -0,0 +1,50 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use axum::http::HeaderMap;
+use moka::future::Cache;
+
+/// A fixed window rate limiter: every key may be hit `limit` times per `window`.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    hits: Cache<String, Arc<AtomicU32>>,
+    limit: u32,
+}
+
+impl RateLimiter {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            hits: Cache::builder().max_capacity(100_000).time_to_live(window).build(),
+            limit,
+        }
+    }
+
+    /// Counts a hit for `key`; returns `false` when the key is over its limit for the current window.
+    pub async fn check(&self, key: &str) -> bool {
+        let hits = self
+            .hits
+            .get_with(key.to_owned(), async { Arc::new(AtomicU32::new(0)) })
+            .await;
+
+        hits.fetch_add(1, Ordering::Relaxed) < self.limit
+    }
+}
+
+/// Returns the IP address of the client. We run behind a reverse proxy, so the address set by the
+/// proxy in `X-Forwarded-For` is used when the request comes from the loopback interface.
+pub fn client_ip(headers: &HeaderMap, peer: SocketAddr) -> IpAddr {
+    if !peer.ip().is_loopback() {
+        return peer.ip();
+    }
+
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|ip| ip.trim().parse::<IpAddr>().ok())
+        .next_back()
+        .unwrap_or_else(|| peer.ip())
+}
@@ -0,0 +1,79 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::RngCore;
+use totp_rs::{Algorithm, Secret, TOTP};
+use url::Url;
+
+use crate::uri_helpers::root_uri;
+
+const DIGITS: usize = 6;
+const STEP: u64 = 30;
+/// how many steps a code may be off to account for clock drift
+const SKEW: i64 = 1;
+
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Generates a new base32 encoded TOTP secret with 160 bits of entropy
+pub fn generate_secret() -> String {
+    Secret::generate_secret().to_encoded().to_string()
+}
+
+fn totp(secret: &str, account_name: &str) -> Result<TOTP, String> {
+    let secret = Secret::Encoded(secret.to_owned())
+        .to_bytes()
+        .map_err(|e| format!("invalid TOTP secret: {:?}", e))?;
+    let issuer = Url::parse(&root_uri())
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.to_owned()));
+
+    TOTP::new(Algorithm::SHA1, DIGITS, 0, STEP, secret, issuer, account_name.replace(':', ""))
+        .map_err(|e| format!("could not create TOTP: {:?}", e))
+}
+
+/// Returns a base64 encoded PNG with the provisioning QR code for authenticator apps
+pub fn qr_code(secret: &str, account_name: &str) -> Result<String, String> {
+    totp(secret, account_name)?.get_qr_base64()
+}
+
+/// Checks a code against the current time and returns the time step it is valid for; codes for steps up to
+/// and including `last_step` are rejected since they have already been used.
+pub fn verify_code(secret: &str, code: &str, last_step: Option<i64>) -> Option<i64> {
+    let code = code.trim().replace(' ', "");
+    if code.len() != DIGITS || !code.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let totp = totp(secret, "").ok()?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+    let current_step = now / STEP as i64;
+
+    (current_step - SKEW..=current_step + SKEW)
+        .filter(|step| last_step.is_none_or(|last_step| *step > last_step))
+        .find(|step| totp.check(&code, (*step as u64) * STEP))
+}
+
+/// Generates single-use recovery codes of the form `xxxx-xxxx-xxxx-xxxx` (64 bits of entropy)
+pub fn generate_recovery_codes() -> Vec<String> {
+    let mut rng = rand::rng();
+
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut bytes = [0u8; 8];
+            rng.fill_bytes(&mut bytes);
+
+            bytes
+                .chunks(2)
+                .map(|chunk| format!("{:02x}{:02x}", chunk[0], chunk[1]))
+                .collect::<Vec<_>>()
+                .join("-")
+        })
+        .collect()
+}
+
+/// Recovery codes are compared case insensitive and without separators
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
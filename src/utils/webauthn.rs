@@ -0,0 +1,12 @@
+use url::Url;
+use webauthn_rs::prelude::{Webauthn, WebauthnBuilder, WebauthnResult};
+
+use crate::uri_helpers::root_uri;
+
+/// The relying party is the host of `BASE_URI`; passkeys are bound to it and stop working when it changes
+pub fn build_webauthn() -> WebauthnResult<Webauthn> {
+    let origin = Url::parse(&root_uri()).expect("BASE_URI is not a valid URL");
+    let rp_id = origin.host_str().expect("BASE_URI has no host").to_owned();
+
+    WebauthnBuilder::new(&rp_id, &origin)?.rp_name("WWWTech").build()
+}
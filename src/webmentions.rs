@@ -1,24 +1,42 @@
-use axum::debug_handler;
-use axum::extract::{Form, State};
+use std::net::SocketAddr;
+
+use axum::extract::{ConnectInfo, Form, Path, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::middleware::from_fn;
 use axum::response::IntoResponse;
-use axum::routing::post;
+use axum::routing::{get, post};
+use axum::{Router, debug_handler};
+use axum_login::login_required;
 use serde::{Deserialize, Serialize};
 use url::Url;
-use visdom::Vis;
-use visdom::types::IAttrValue;
 
-use self::actions::{ObjectType, create_mention, mention_exists, target_exists};
+use self::actions::{enqueue_webmention, get_queued_webmention_by_status_token};
+use self::fetch::is_public_url;
 use crate::errors::AppError;
-use crate::uri_helpers::root_uri;
+use crate::middleware::two_factor_middleware;
+use crate::store::Store;
+use crate::uri_helpers::{root_uri, webmention_status_uri};
+use crate::utils::rate_limit::client_ip;
 use crate::{AppRouter, AppState};
 
 pub mod actions;
+pub mod fetch;
+pub mod mail_sender;
+pub mod mf2;
+pub mod outbox;
 pub mod send;
-
-mod mail_sender;
+pub mod sent;
+pub mod worker;
 
 pub fn configure(app: AppRouter) -> AppRouter {
+    let authed_router: AppRouter = Router::new()
+        .route("/admin/webmentions", get(sent::index))
+        .route_layer(from_fn(two_factor_middleware))
+        .route_layer(login_required!(Store, login_url = "/login"));
+
     app.route("/webmentions", post(receive_webmention))
+        .route("/webmentions/{token}", get(webmention_status))
+        .merge(authed_router)
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Default)]
@@ -30,59 +48,60 @@ pub struct MentionValues {
 #[debug_handler]
 pub async fn receive_webmention(
     State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Form(values): Form<MentionValues>,
 ) -> Result<impl IntoResponse, AppError> {
-    let mut conn = state.pool.acquire().await?;
+    let ip = client_ip(&headers, peer);
+    if !state.webmention_ip_limiter.check(&ip.to_string()).await {
+        return Err(AppError::TooManyRequests("too many webmentions from your address".to_owned()));
+    }
+
     let root_url = Url::parse(&root_uri()).unwrap();
-    let source_url =
-        Url::parse(&values.source).map_err(|_| AppError::InternalError("source url invalid".to_owned()))?;
-    let target_url =
-        Url::parse(&values.target).map_err(|_| AppError::InternalError("target url invalid".to_owned()))?;
+    let source_url = Url::parse(&values.source).map_err(|_| AppError::BadRequest("source url invalid".to_owned()))?;
+    let target_url = Url::parse(&values.target).map_err(|_| AppError::BadRequest("target url invalid".to_owned()))?;
+
+    // refuses other schemes as well as loopback and private IP addresses
+    if !is_public_url(&source_url) {
+        return Err(AppError::BadRequest("source url invalid".to_owned()));
+    }
 
     if target_url.host_str() != root_url.host_str() {
         return Err(AppError::BadRequest("target url invalid".to_owned()));
     }
 
-    let (object_type, id) = target_exists(&target_url, &mut conn)
-        .await
-        .unwrap_or((ObjectType::Article, 0));
-
-    let surl = source_url.to_string();
-    let body = reqwest::get(surl)
-        .await
-        .map_err(|e| AppError::InternalError(format!("request error: {}", e)))?
-        .text()
-        .await
-        .map_err(|e| AppError::InternalError(format!("request error: {}", e)))?;
-
-    if !body.contains(&target_url.to_string()) {
-        return Err(AppError::BadRequest("source url invalid".to_owned()));
+    if source_url == target_url {
+        return Err(AppError::BadRequest("source and target must not be the same".to_owned()));
     }
 
-    let mention_exists = mention_exists(source_url.as_ref(), target_url.as_ref(), &mut conn).await;
-
-    if mention_exists {
-        return Ok("OK");
+    let source_domain = source_url.host_str().unwrap_or_default().to_lowercase();
+    if !state.webmention_domain_limiter.check(&source_domain).await {
+        return Err(AppError::TooManyRequests("too many webmentions from this source".to_owned()));
     }
 
-    let (title, author) = {
-        let tree = Vis::load(&body).map_err(|_| AppError::BadRequest("could not parse source document".to_owned()))?;
-        let title = tree.find("title").text();
-        let author = match tree.find("meta[name=author]").attr("content") {
-            Some(IAttrValue::Value(author, _)) => author,
-            _ => "unknown".to_owned(),
-        };
+    let mut conn = state.pool.acquire().await?;
+    let queued = enqueue_webmention(source_url.as_str(), target_url.as_str(), &mut conn).await?;
+    let status_uri = webmention_status_uri(&queued.status_token);
+
+    Ok((
+        StatusCode::ACCEPTED,
+        [(header::LOCATION, status_uri.clone())],
+        format!("webmention queued for processing, see {}", status_uri),
+    ))
+}
 
-        (title, author)
-    };
+pub async fn webmention_status(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let mut conn = state.pool.acquire().await?;
+    let queued = get_queued_webmention_by_status_token(&token, &mut conn).await?;
 
-    let mention =
-        create_mention(source_url.to_string(), target_url.to_string(), object_type, id, author, title, &mut conn)
-            .await?;
+    let mut body = format!("source: {}\ntarget: {}\nstatus: {}\n", queued.source_url, queued.target_url, queued.status);
 
-    if source_url.host() != root_url.host() {
-        tokio::task::spawn_blocking(move || mail_sender::send_mail(mention));
+    if let Some(error) = queued.last_error {
+        body.push_str(&format!("reason: {}\n", error));
     }
 
-    Ok("OK")
+    Ok(body)
 }
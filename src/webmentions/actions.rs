@@ -1,50 +1,48 @@
 use std::str::FromStr;
 
-use sqlx::{PgConnection, query_as, query_scalar};
+use chrono::NaiveDateTime;
+use sqlx::{Connection, PgConnection, query, query_as, query_scalar};
 use url::Url;
 
-use crate::models::{Mention, NewMention};
+use super::mf2::SourceData;
+use crate::models::{Mention, NewMention, OutgoingWebmention, QueuedWebmention};
+use crate::utils::tokens::generate_token;
 
-pub async fn target_exists(url: &Url, conn: &mut PgConnection) -> Option<(ObjectType, i32)> {
-    let (object_type, obj_id) = match get_object_type_and_id(url) {
-        Some((object_type, obj_id)) => (object_type, obj_id),
-        None => return None,
+/// Looks up the object a URL points to; `None` if it isn't one of our posts or the post doesn't exist
+pub async fn target_exists(url: &Url, conn: &mut PgConnection) -> Result<Option<(ObjectType, i32)>, sqlx::Error> {
+    let Some((object_type, obj_id)) = get_object_type_and_id(url) else {
+        return Ok(None);
     };
 
-    let exists: bool = match object_type {
-        ObjectType::Article => query_scalar!("SELECT EXISTS(SELECT true FROM articles WHERE id = $1)", obj_id)
-            .fetch_one(conn)
-            .await
-            .ok()
-            .flatten()
-            .unwrap_or(false),
-        ObjectType::Deafie => query_scalar!("SELECT EXISTS(SELECT true FROM deafies WHERE id = $1)", obj_id)
-            .fetch_one(conn)
-            .await
-            .ok()
-            .flatten()
-            .unwrap_or(false),
-        ObjectType::Note => query_scalar!("SELECT EXISTS(SELECT true FROM notes WHERE id = $1)", obj_id)
-            .fetch_one(conn)
-            .await
-            .ok()
-            .flatten()
-            .unwrap_or(false),
-        ObjectType::Picture => query_scalar!("SELECT EXISTS(SELECT 1 FROM pictures WHERE id = $1)", obj_id)
-            .fetch_one(conn)
-            .await
-            .ok()
-            .flatten()
-            .unwrap_or(false),
-        ObjectType::Like => query_scalar!("SELECT EXISTS(SELECT 1 FROM likes WHERE id = $1)", obj_id)
-            .fetch_one(conn)
-            .await
-            .ok()
-            .flatten()
-            .unwrap_or(false),
+    let exists = match object_type {
+        ObjectType::Article => {
+            query_scalar!("SELECT EXISTS(SELECT true FROM articles WHERE id = $1)", obj_id)
+                .fetch_one(conn)
+                .await?
+        }
+        ObjectType::Deafie => {
+            query_scalar!("SELECT EXISTS(SELECT true FROM deafies WHERE id = $1)", obj_id)
+                .fetch_one(conn)
+                .await?
+        }
+        ObjectType::Note => {
+            query_scalar!("SELECT EXISTS(SELECT true FROM notes WHERE id = $1)", obj_id)
+                .fetch_one(conn)
+                .await?
+        }
+        ObjectType::Picture => {
+            query_scalar!("SELECT EXISTS(SELECT 1 FROM pictures WHERE id = $1)", obj_id)
+                .fetch_one(conn)
+                .await?
+        }
+        ObjectType::Like => {
+            query_scalar!("SELECT EXISTS(SELECT 1 FROM likes WHERE id = $1)", obj_id)
+                .fetch_one(conn)
+                .await?
+        }
     };
 
-    if exists { Some((object_type, obj_id)) } else { None }
+    Ok(exists.unwrap_or(false).then_some((object_type, obj_id)))
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -71,6 +69,19 @@ impl FromStr for ObjectType {
     }
 }
 
+impl ObjectType {
+    /// The path segment of the object type, the counterpart of `from_str()`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ObjectType::Article => "articles",
+            ObjectType::Note => "notes",
+            ObjectType::Picture => "pictures",
+            ObjectType::Like => "likes",
+            ObjectType::Deafie => "deafies",
+        }
+    }
+}
+
 pub fn get_object_type_and_id(url: &Url) -> Option<(ObjectType, i32)> {
     let path = url.path();
     let pieces = path.split('/').collect::<Vec<_>>();
@@ -84,13 +95,19 @@ pub fn get_object_type_and_id(url: &Url) -> Option<(ObjectType, i32)> {
     Some((object_type, id))
 }
 
-pub async fn mention_exists(source: &str, target: &str, conn: &mut PgConnection) -> bool {
-    query_scalar!("SELECT EXISTS(SELECT true FROM mentions WHERE source_url = $1 AND target_url = $2)", source, target)
-        .fetch_one(conn)
-        .await
-        .ok()
-        .flatten()
-        .unwrap_or(false)
+pub async fn get_mention_by_source_and_target(
+    source: &str,
+    target: &str,
+    conn: &mut PgConnection,
+) -> Result<Option<Mention>, sqlx::Error> {
+    query_as!(
+        Mention,
+        "SELECT * FROM mentions WHERE source_url = $1 AND target_url = $2 ORDER BY id LIMIT 1",
+        source,
+        target
+    )
+    .fetch_optional(conn)
+    .await
 }
 
 pub async fn create_mention(
@@ -98,17 +115,22 @@ pub async fn create_mention(
     target_url: String,
     object_type: ObjectType,
     id: i32,
-    author: String,
-    title: String,
+    source: SourceData,
+    status: &str,
     conn: &mut PgConnection,
 ) -> Result<Mention, sqlx::Error> {
     let now = chrono::Utc::now().naive_utc();
     let mut data = NewMention {
         source_url,
         target_url,
-        author,
-        title,
-        mention_type: "TODO:".to_owned(),
+        title: source.title,
+        excerpt: source.excerpt,
+        author: source.author,
+        author_url: source.author_url,
+        author_avatar: source.author_avatar,
+        mention_type: source.mention_type,
+        status: status.to_owned(),
+        remote_id: source.remote_id,
         inserted_at: Some(now),
         updated_at: Some(now),
         ..Default::default()
@@ -125,29 +147,512 @@ pub async fn create_mention(
             data.article_id = Some(id);
         }
         ObjectType::Deafie => {
-            data.article_id = Some(id);
+            data.deafie_id = Some(id);
+        }
+        ObjectType::Like => {
+            data.like_id = Some(id);
         }
-        _ => {}
     };
 
     query_as!(
         Mention,
         r#"
-        INSERT INTO mentions (source_url, target_url, author, title, mention_type, inserted_at, updated_at, note_id, picture_id, article_id)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        INSERT INTO mentions (source_url, target_url, author, title, excerpt, author_url, author_avatar, mention_type,
+                              status, inserted_at, updated_at, note_id, picture_id, article_id, deafie_id, like_id,
+                              remote_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
         RETURNING *
         "#,
         data.source_url,
         data.target_url,
         data.author,
         data.title,
+        data.excerpt,
+        data.author_url,
+        data.author_avatar,
         data.mention_type,
+        data.status,
         data.inserted_at,
         data.updated_at,
         data.note_id,
         data.picture_id,
-        data.article_id
+        data.article_id,
+        data.deafie_id,
+        data.like_id,
+        data.remote_id
+    )
+    .fetch_one(conn)
+    .await
+}
+
+/// Lists the approved mentions of an object, oldest first
+pub async fn list_mentions(
+    object_type: ObjectType,
+    id: i32,
+    conn: &mut PgConnection,
+) -> Result<Vec<Mention>, sqlx::Error> {
+    match object_type {
+        ObjectType::Article => {
+            query_as!(
+                Mention,
+                "SELECT * FROM mentions WHERE article_id = $1 AND status = 'approved' ORDER BY inserted_at, id",
+                id
+            )
+            .fetch_all(conn)
+            .await
+        }
+        ObjectType::Note => {
+            query_as!(
+                Mention,
+                "SELECT * FROM mentions WHERE note_id = $1 AND status = 'approved' ORDER BY inserted_at, id",
+                id
+            )
+            .fetch_all(conn)
+            .await
+        }
+        ObjectType::Picture => {
+            query_as!(
+                Mention,
+                "SELECT * FROM mentions WHERE picture_id = $1 AND status = 'approved' ORDER BY inserted_at, id",
+                id
+            )
+            .fetch_all(conn)
+            .await
+        }
+        ObjectType::Like => {
+            query_as!(
+                Mention,
+                "SELECT * FROM mentions WHERE like_id = $1 AND status = 'approved' ORDER BY inserted_at, id",
+                id
+            )
+            .fetch_all(conn)
+            .await
+        }
+        ObjectType::Deafie => {
+            query_as!(
+                Mention,
+                "SELECT * FROM mentions WHERE deafie_id = $1 AND status = 'approved' ORDER BY inserted_at, id",
+                id
+            )
+            .fetch_all(conn)
+            .await
+        }
+    }
+}
+
+pub async fn get_mention_by_remote_id(
+    remote_id: &str,
+    conn: &mut PgConnection,
+) -> Result<Option<Mention>, sqlx::Error> {
+    query_as!(Mention, "SELECT * FROM mentions WHERE remote_id = $1", remote_id)
+        .fetch_optional(conn)
+        .await
+}
+
+/// Lists the mentions of an object backfed from `service` which haven't been removed
+pub async fn list_backfed_mentions(
+    object_type: &ObjectType,
+    id: i32,
+    service: &str,
+    conn: &mut PgConnection,
+) -> Result<Vec<Mention>, sqlx::Error> {
+    let prefix = format!("{}:%", service);
+
+    match object_type {
+        ObjectType::Article => query_as!(
+            Mention,
+            "SELECT * FROM mentions WHERE article_id = $1 AND remote_id LIKE $2 AND status != 'deleted' ORDER BY id",
+            id,
+            prefix
+        )
+        .fetch_all(conn)
+        .await,
+        ObjectType::Note => {
+            query_as!(
+                Mention,
+                "SELECT * FROM mentions WHERE note_id = $1 AND remote_id LIKE $2 AND status != 'deleted' ORDER BY id",
+                id,
+                prefix
+            )
+            .fetch_all(conn)
+            .await
+        }
+        ObjectType::Picture => query_as!(
+            Mention,
+            "SELECT * FROM mentions WHERE picture_id = $1 AND remote_id LIKE $2 AND status != 'deleted' ORDER BY id",
+            id,
+            prefix
+        )
+        .fetch_all(conn)
+        .await,
+        ObjectType::Like => {
+            query_as!(
+                Mention,
+                "SELECT * FROM mentions WHERE like_id = $1 AND remote_id LIKE $2 AND status != 'deleted' ORDER BY id",
+                id,
+                prefix
+            )
+            .fetch_all(conn)
+            .await
+        }
+        ObjectType::Deafie => {
+            query_as!(
+                Mention,
+                "SELECT * FROM mentions WHERE deafie_id = $1 AND remote_id LIKE $2 AND status != 'deleted' ORDER BY id",
+                id,
+                prefix
+            )
+            .fetch_all(conn)
+            .await
+        }
+    }
+}
+
+pub async fn update_mention(
+    mention_id: i32,
+    source: SourceData,
+    status: &str,
+    conn: &mut PgConnection,
+) -> Result<Mention, sqlx::Error> {
+    let now = chrono::Utc::now().naive_utc();
+
+    query_as!(
+        Mention,
+        r#"
+        UPDATE mentions
+        SET title = $1, excerpt = $2, author = $3, author_url = $4, author_avatar = $5, mention_type = $6, status = $7,
+            updated_at = $8
+        WHERE id = $9
+        RETURNING *
+        "#,
+        source.title,
+        source.excerpt,
+        source.author,
+        source.author_url,
+        source.author_avatar,
+        source.mention_type,
+        status,
+        now,
+        mention_id
+    )
+    .fetch_one(conn)
+    .await
+}
+
+pub async fn enqueue_webmention(
+    source_url: &str,
+    target_url: &str,
+    conn: &mut PgConnection,
+) -> Result<QueuedWebmention, sqlx::Error> {
+    let now = chrono::Utc::now().naive_utc();
+    let status_token = generate_token();
+
+    query_as!(
+        QueuedWebmention,
+        r#"
+        INSERT INTO webmention_queue (source_url, target_url, status_token, status, attempts, next_try_at, inserted_at,
+                                      updated_at)
+        VALUES ($1, $2, $3, 'pending', 0, $4, $4, $4)
+        RETURNING *
+        "#,
+        source_url,
+        target_url,
+        status_token,
+        now
     )
     .fetch_one(conn)
     .await
 }
+
+pub async fn get_queued_webmention_by_status_token(
+    status_token: &str,
+    conn: &mut PgConnection,
+) -> Result<QueuedWebmention, sqlx::Error> {
+    query_as!(QueuedWebmention, "SELECT * FROM webmention_queue WHERE status_token = $1", status_token)
+        .fetch_one(conn)
+        .await
+}
+
+/// Claims up to `limit` due webmentions by switching them to `processing`; rows locked by another
+/// worker are skipped.
+pub async fn claim_due_webmentions(limit: i64, conn: &mut PgConnection) -> Result<Vec<QueuedWebmention>, sqlx::Error> {
+    let now = chrono::Utc::now().naive_utc();
+
+    query_as!(
+        QueuedWebmention,
+        r#"
+        UPDATE webmention_queue
+        SET status = 'processing', attempts = attempts + 1, updated_at = $1
+        WHERE id IN (
+            SELECT id FROM webmention_queue
+            WHERE status = 'pending' AND next_try_at <= $1
+            ORDER BY next_try_at, id
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING *
+        "#,
+        now,
+        limit
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Puts webmentions left in `processing` by a previous run back into the queue.
+pub async fn requeue_stale_webmentions(conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    query!("UPDATE webmention_queue SET status = 'pending' WHERE status = 'processing'")
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn accept_queued_webmention(id: i32, mention_id: i32, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    let now = chrono::Utc::now().naive_utc();
+
+    query!(
+        r#"
+        UPDATE webmention_queue
+        SET status = 'accepted', mention_id = $1, last_error = NULL, updated_at = $2
+        WHERE id = $3
+        "#,
+        mention_id,
+        now,
+        id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn reject_queued_webmention(id: i32, reason: &str, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    let now = chrono::Utc::now().naive_utc();
+
+    query!(
+        "UPDATE webmention_queue SET status = 'rejected', last_error = $1, updated_at = $2 WHERE id = $3",
+        reason,
+        now,
+        id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Records a failed attempt; the webmention is retried at `next_try_at` or, when `None`, marked as
+/// `failed` for good.
+pub async fn retry_queued_webmention(
+    id: i32,
+    error: &str,
+    next_try_at: Option<NaiveDateTime>,
+    conn: &mut PgConnection,
+) -> Result<(), sqlx::Error> {
+    let now = chrono::Utc::now().naive_utc();
+
+    match next_try_at {
+        Some(next_try_at) => {
+            query!(
+                r#"
+                UPDATE webmention_queue
+                SET status = 'pending', last_error = $1, next_try_at = $2, updated_at = $3
+                WHERE id = $4
+                "#,
+                error,
+                next_try_at,
+                now,
+                id
+            )
+            .execute(conn)
+            .await?
+        }
+        None => {
+            query!(
+                "UPDATE webmention_queue SET status = 'failed', last_error = $1, updated_at = $2 WHERE id = $3",
+                error,
+                now,
+                id
+            )
+            .execute(conn)
+            .await?
+        }
+    };
+
+    Ok(())
+}
+
+/// Puts a webmention from `source_url` to `target_url` into the outbox; a known pair is sent again
+/// from scratch.
+pub async fn enqueue_outgoing_webmention(
+    source_url: &str,
+    target_url: &str,
+    conn: &mut PgConnection,
+) -> Result<OutgoingWebmention, sqlx::Error> {
+    let now = chrono::Utc::now().naive_utc();
+
+    query_as!(
+        OutgoingWebmention,
+        r#"
+        INSERT INTO outgoing_webmentions
+            (source_url, target_url, status, attempts, next_retry_at, inserted_at, updated_at)
+        VALUES ($1, $2, 'pending', 0, $3, $3, $3)
+        ON CONFLICT (source_url, target_url)
+        DO UPDATE SET status = 'pending', attempts = 0, last_error = NULL, next_retry_at = $3, updated_at = $3
+        RETURNING *
+        "#,
+        source_url,
+        target_url,
+        now
+    )
+    .fetch_one(conn)
+    .await
+}
+
+pub async fn list_outgoing_webmentions(
+    source_url: Option<&str>,
+    limit: i64,
+    offset: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<OutgoingWebmention>, sqlx::Error> {
+    query_as!(
+        OutgoingWebmention,
+        "SELECT * FROM outgoing_webmentions WHERE $1::text IS NULL OR source_url = $1 \
+         ORDER BY updated_at DESC, id DESC LIMIT $2 OFFSET $3",
+        source_url,
+        limit,
+        offset
+    )
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn count_outgoing_webmentions(source_url: Option<&str>, conn: &mut PgConnection) -> Result<i64, sqlx::Error> {
+    query_scalar("SELECT COUNT(*) FROM outgoing_webmentions WHERE $1::text IS NULL OR source_url = $1")
+        .bind(source_url)
+        .fetch_one(conn)
+        .await
+}
+
+/// Claims up to `limit` due outgoing webmentions by switching them to `processing`.
+pub async fn claim_due_outgoing_webmentions(
+    limit: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<OutgoingWebmention>, sqlx::Error> {
+    let now = chrono::Utc::now().naive_utc();
+
+    query_as!(
+        OutgoingWebmention,
+        r#"
+        UPDATE outgoing_webmentions
+        SET status = 'processing', attempts = attempts + 1, updated_at = $1
+        WHERE id IN (
+            SELECT id FROM outgoing_webmentions
+            WHERE status = 'pending' AND next_retry_at <= $1
+            ORDER BY next_retry_at, id
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING *
+        "#,
+        now,
+        limit
+    )
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn requeue_stale_outgoing_webmentions(conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    query!("UPDATE outgoing_webmentions SET status = 'pending' WHERE status = 'processing'")
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Records the outcome of a delivery attempt. A `pending` status schedules the next attempt at
+/// `next_retry_at`.
+pub async fn record_outgoing_webmention(
+    id: i32,
+    status: &str,
+    endpoint: Option<&str>,
+    response_code: Option<i32>,
+    error: Option<&str>,
+    next_retry_at: NaiveDateTime,
+    conn: &mut PgConnection,
+) -> Result<(), sqlx::Error> {
+    let now = chrono::Utc::now().naive_utc();
+
+    query!(
+        "UPDATE outgoing_webmentions SET status = $1, endpoint = $2, response_code = $3, last_error = $4, \
+         next_retry_at = $5, updated_at = $6 WHERE id = $7",
+        status,
+        endpoint,
+        response_code,
+        error,
+        next_retry_at,
+        now,
+        id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Returns the targets `source_url` linked to when webmentions were sent the last time.
+pub async fn list_outbound_links(source_url: &str, conn: &mut PgConnection) -> Result<Vec<String>, sqlx::Error> {
+    query_scalar!("SELECT target_url FROM outbound_links WHERE source_url = $1 ORDER BY id", source_url)
+        .fetch_all(conn)
+        .await
+}
+
+pub async fn replace_outbound_links(
+    source_url: &str,
+    targets: &[String],
+    conn: &mut PgConnection,
+) -> Result<(), sqlx::Error> {
+    let now = chrono::Utc::now().naive_utc();
+    let mut tx = conn.begin().await?;
+
+    query!("DELETE FROM outbound_links WHERE source_url = $1", source_url)
+        .execute(&mut *tx)
+        .await?;
+
+    query!(
+        "INSERT INTO outbound_links (source_url, target_url, inserted_at) SELECT $1, UNNEST($2::text[]), $3 \
+         ON CONFLICT DO NOTHING",
+        source_url,
+        targets,
+        now
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Remembers that the post at `url` has been deleted, so it can be served as `410 Gone`.
+pub async fn create_tombstone(url: &str, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    let now = chrono::Utc::now().naive_utc();
+
+    query!(
+        "INSERT INTO tombstones (url, deleted_at) VALUES ($1, $2) ON CONFLICT (url) DO UPDATE SET deleted_at = $2",
+        url,
+        now
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn tombstone_exists(url: &str, conn: &mut PgConnection) -> Result<bool, sqlx::Error> {
+    query_scalar!("SELECT EXISTS(SELECT true FROM tombstones WHERE url = $1)", url)
+        .fetch_one(conn)
+        .await
+        .map(|exists| exists.unwrap_or(false))
+}
@@ -0,0 +1,258 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::{Client, redirect};
+use url::{Host, Url};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_REDIRECTS: usize = 5;
+
+/// Builds a HTTP client for fetching documents from untrusted URLs: it refuses to connect to
+/// loopback, private, link-local and other non-public addresses, both for host names (checked after
+/// DNS resolution) and IP literals in redirects.
+pub fn build_client(timeout: Duration) -> reqwest::Result<Client> {
+    let policy = redirect::Policy::custom(|attempt| {
+        if attempt.previous().len() >= MAX_REDIRECTS {
+            attempt.error("too many redirects")
+        } else if !is_public_url(attempt.url()) {
+            attempt.error("refusing to follow redirect to a non-public address")
+        } else {
+            attempt.follow()
+        }
+    });
+
+    Client::builder()
+        .timeout(timeout)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .redirect(policy)
+        .dns_resolver(Arc::new(PublicResolver))
+        // a proxy would resolve the host names for us, bypassing the address check
+        .no_proxy()
+        .build()
+}
+
+/// Checks scheme and, for IP literals, the address of `url`; host names are checked by the resolver
+/// once they are resolved.
+pub fn is_public_url(url: &Url) -> bool {
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return false;
+    }
+
+    match url.host() {
+        Some(Host::Domain(_)) => true,
+        Some(Host::Ipv4(ip)) => is_public_ip(IpAddr::V4(ip)),
+        Some(Host::Ipv6(ip)) => is_public_ip(IpAddr::V6(ip)),
+        None => false,
+    }
+}
+
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => match embedded_ipv4(ip) {
+            Some(ip) => is_public_ipv4(ip),
+            None => is_public_ipv6(ip),
+        },
+    }
+}
+
+/// Returns the IPv4 address an IPv6 address carries, which is where the packets end up
+fn embedded_ipv4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let segments = ip.segments();
+    let from_segments = |high: u16, low: u16| Ipv4Addr::from((u32::from(high) << 16) | u32::from(low));
+
+    match segments {
+        // 64:ff9b::/96, NAT64
+        [0x64, 0xff9b, 0, 0, 0, 0, high, low] => Some(from_segments(high, low)),
+        // 2002::/16, 6to4
+        [0x2002, high, low, ..] => Some(from_segments(high, low)),
+        // ::ffff:a.b.c.d, IPv4-mapped, and the deprecated IPv4-compatible ::a.b.c.d
+        _ => ip.to_ipv4(),
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        // 0.0.0.0/8, "this network"
+        || a == 0
+        // 100.64.0.0/10, carrier-grade NAT
+        || (a == 100 && (b & 0xc0) == 64)
+        // 192.0.0.0/24, IETF protocol assignments
+        || (a == 192 && b == 0 && c == 0)
+        // 198.18.0.0/15, benchmarking
+        || (a == 198 && (b & 0xfe) == 18)
+        // 240.0.0.0/4, reserved
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // fc00::/7, unique local addresses
+        || (segments[0] & 0xfe00) == 0xfc00
+        // fe80::/10, link-local addresses
+        || (segments[0] & 0xffc0) == 0xfe80
+        // 2001:db8::/32, documentation
+        || (segments[0] == 0x2001 && segments[1] == 0x0db8)
+        // 64:ff9b:1::/48, local-use NAT64
+        || (segments[0] == 0x64 && segments[1] == 0xff9b && segments[2] == 1))
+}
+
+/// resolves host names and drops every non-public address
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect::<Vec<SocketAddr>>();
+
+            if addrs.is_empty() {
+                return Err(format!("refusing to connect to {}: no public address", name.as_str()).into());
+            }
+
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axum::Router;
+    use axum::response::Redirect;
+    use axum::routing::get;
+
+    use super::*;
+
+    fn public(ip: &str) -> bool {
+        is_public_ip(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn refuses_loopback_private_and_link_local_ipv4() {
+        for ip in [
+            "127.0.0.1",
+            "127.255.255.254",
+            "0.0.0.0",
+            "10.0.0.1",
+            "172.16.0.1",
+            "172.31.255.255",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "100.127.255.255",
+            "255.255.255.255",
+        ] {
+            assert!(!public(ip), "{} is not public", ip);
+        }
+    }
+
+    #[test]
+    fn accepts_public_ipv4() {
+        for ip in ["93.184.216.34", "172.32.0.1", "100.128.0.1", "1.1.1.1"] {
+            assert!(public(ip), "{} is public", ip);
+        }
+    }
+
+    #[test]
+    fn refuses_non_public_ipv6() {
+        for ip in [
+            "::",
+            "::1",
+            "fe80::1",
+            "fc00::1",
+            "fd12:3456::1",
+            "2001:db8::1",
+            "64:ff9b:1::1",
+        ] {
+            assert!(!public(ip), "{} is not public", ip);
+        }
+
+        assert!(public("2606:4700:4700::1111"));
+    }
+
+    #[test]
+    fn checks_the_ipv4_address_embedded_in_ipv6() {
+        for ip in [
+            // IPv4-mapped and IPv4-compatible
+            "::ffff:127.0.0.1",
+            "::ffff:10.0.0.1",
+            "::ffff:169.254.169.254",
+            "::127.0.0.1",
+            // NAT64
+            "64:ff9b::7f00:1",
+            "64:ff9b::a9fe:a9fe",
+            // 6to4
+            "2002:7f00:1::",
+            "2002:c0a8:101::1",
+        ] {
+            assert!(!public(ip), "{} is not public", ip);
+        }
+
+        for ip in ["::ffff:93.184.216.34", "64:ff9b::5db8:d822", "2002:5db8:d822::1"] {
+            assert!(public(ip), "{} is public", ip);
+        }
+    }
+
+    #[test]
+    fn checks_urls() {
+        assert!(is_public_url(&Url::parse("https://example.com/").unwrap()));
+        assert!(!is_public_url(&Url::parse("http://127.0.0.1/").unwrap()));
+        assert!(!is_public_url(&Url::parse("http://[::ffff:7f00:1]/").unwrap()));
+        assert!(!is_public_url(&Url::parse("ftp://example.com/").unwrap()));
+    }
+
+    #[tokio::test]
+    async fn resolver_drops_non_public_addresses() {
+        let resolved = PublicResolver.resolve("localhost".parse().unwrap()).await;
+        assert!(resolved.is_err());
+    }
+
+    #[tokio::test]
+    async fn refuses_redirects_to_private_addresses() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let target_hits = hits.clone();
+
+        let app = Router::new()
+            .route("/redirect", get(|| async { Redirect::temporary("http://10.0.0.1/") }))
+            .route("/redirect-back", get(|| async { Redirect::temporary("/target") }))
+            .route(
+                "/target",
+                get(move || async move {
+                    target_hits.fetch_add(1, Ordering::SeqCst);
+                    "secret"
+                }),
+            );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        // the first request goes to an IP literal, which callers check with `is_public_url()`, so that the
+        // redirect policy is what stops the client
+        let client = build_client(Duration::from_secs(5)).unwrap();
+        for path in ["/redirect", "/redirect-back"] {
+            let err = client.get(format!("{}{}", base, path)).send().await.unwrap_err();
+            assert!(err.is_redirect(), "{} is refused as a redirect: {}", path, err);
+        }
+
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,338 @@
+use microformats::types::{Class, Item, KnownClass, PropertyValue};
+use url::Url;
+use visdom::Vis;
+use visdom::types::IAttrValue;
+
+/// the data we extract from a webmention source document
+#[derive(Debug, Clone, Default)]
+pub struct SourceData {
+    pub mention_type: String,
+    pub title: Option<String>,
+    pub excerpt: Option<String>,
+    pub author: String,
+    pub author_url: Option<String>,
+    pub author_avatar: Option<String>,
+    /// set for interactions backfed from a syndicated copy instead of being sent as webmention
+    pub remote_id: Option<String>,
+}
+
+// the mentions columns are VARCHAR(255)
+const MAX_LEN: usize = 255;
+
+/// Parses the source document as microformats2 and classifies the mention of `target`. Falls back to
+/// `<title>` and `<meta name="author">` when the source has no h-entry.
+pub fn parse_source(body: &str, source_url: &Url, target_url: &Url) -> SourceData {
+    let doc = microformats::from_html(body, source_url).ok();
+    let items = doc.map(|doc| doc.items).unwrap_or_default();
+
+    let entry = find_entry(&items, source_url);
+    let card = entry
+        .and_then(|entry| first_item(entry, "author"))
+        .or_else(|| find_item(&items, KnownClass::Card));
+
+    let mut data = SourceData {
+        mention_type: entry
+            .map(|entry| mention_type(entry, target_url))
+            .unwrap_or_else(|| "mention".to_owned()),
+        ..Default::default()
+    };
+
+    if let Some(entry) = entry {
+        let content = first_text(entry, "content");
+        let name = first_text(entry, "name");
+
+        // an entry without an explicit name gets the content as implied name, so it has no real title
+        data.title = name.filter(|name| content.as_ref().is_none_or(|content| !same_text(name, content)));
+        data.excerpt = first_text(entry, "summary").or(content).map(|s| truncate(&s));
+
+        if card.is_none() {
+            match first_value(entry, "author") {
+                Some(value @ PropertyValue::Url(_)) => data.author_url = value_url(&value),
+                Some(PropertyValue::Plain(name)) => data.author = name.to_string(),
+                _ => {}
+            }
+        }
+    }
+
+    if let Some(card) = card {
+        data.author = first_text(card, "name").unwrap_or_default();
+        data.author_url = first_url(card, "url");
+        data.author_avatar = first_url(card, "photo");
+    }
+
+    if data.title.is_none() || data.author.is_empty() {
+        fill_from_html(body, &mut data);
+    }
+
+    data.title = data.title.map(|s| truncate(&s)).filter(|s| !s.is_empty());
+    data.author = truncate(&data.author);
+    data.author_url = data.author_url.filter(|u| u.len() <= MAX_LEN);
+    data.author_avatar = data.author_avatar.filter(|u| u.len() <= MAX_LEN);
+
+    data
+}
+
+fn fill_from_html(body: &str, data: &mut SourceData) {
+    let Ok(tree) = Vis::load(body) else {
+        return;
+    };
+
+    if data.title.is_none() {
+        data.title = Some(tree.find("title").text().trim().to_owned());
+    }
+
+    if data.author.is_empty() {
+        data.author = match tree.find("meta[name=author]").attr("content") {
+            Some(IAttrValue::Value(author, _)) => author,
+            _ => "unknown".to_owned(),
+        };
+    }
+}
+
+fn mention_type(entry: &Item, target_url: &Url) -> String {
+    let types = [
+        ("like-of", "like"),
+        ("repost-of", "repost"),
+        ("bookmark-of", "bookmark"),
+        ("in-reply-to", "reply"),
+    ];
+
+    for (property, mention_type) in types {
+        let refers_to_target = entry
+            .get_property(property)
+            .unwrap_or_default()
+            .iter()
+            .any(|value| value_url(value).is_some_and(|url| same_url(&url, target_url.as_str())));
+
+        if refers_to_target {
+            return mention_type.to_owned();
+        }
+    }
+
+    "mention".to_owned()
+}
+
+/// Returns the h-entry describing the source: the one with the source URL as `u-url`, else the
+/// first one found
+fn find_entry<'a>(items: &'a [Item], source_url: &Url) -> Option<&'a Item> {
+    let entries = all_items(items)
+        .into_iter()
+        .filter(|item| item.r#type.contains(&Class::Known(KnownClass::Entry)))
+        .collect::<Vec<_>>();
+
+    entries
+        .iter()
+        .find(|entry| first_url(entry, "url").is_some_and(|url| same_url(&url, source_url.as_str())))
+        .or_else(|| entries.first())
+        .copied()
+}
+
+fn find_item(items: &[Item], class: KnownClass) -> Option<&Item> {
+    all_items(items)
+        .into_iter()
+        .find(|item| item.r#type.contains(&Class::Known(class.clone())))
+}
+
+fn all_items(items: &[Item]) -> Vec<&Item> {
+    items
+        .iter()
+        .flat_map(|item| {
+            let mut found = vec![item];
+            found.extend(all_items(&item.children));
+            found
+        })
+        .collect()
+}
+
+fn first_value(item: &Item, property: &str) -> Option<PropertyValue> {
+    item.get_property(property)?.into_iter().next()
+}
+
+fn first_item<'a>(item: &'a Item, property: &str) -> Option<&'a Item> {
+    item.properties.get(property)?.iter().find_map(|value| match value {
+        PropertyValue::Item(item) => Some(item),
+        _ => None,
+    })
+}
+
+fn first_text(item: &Item, property: &str) -> Option<String> {
+    let text = match first_value(item, property)? {
+        PropertyValue::Plain(text) => text.to_string(),
+        PropertyValue::Fragment(fragment) => fragment.value,
+        PropertyValue::Url(url) => url.to_string(),
+        PropertyValue::Item(item) => match item.value {
+            Some(microformats::types::ValueKind::Plain(text)) => text,
+            Some(microformats::types::ValueKind::Url(url)) => url.to_string(),
+            None => return None,
+        },
+        _ => return None,
+    };
+
+    let text = text.trim().to_owned();
+    if text.is_empty() { None } else { Some(text) }
+}
+
+fn first_url(item: &Item, property: &str) -> Option<String> {
+    item.get_property(property)?.iter().find_map(value_url)
+}
+
+/// Returns the URL of a property value; only http and https URLs are taken, the URLs end up in links and
+/// images on our pages
+fn value_url(value: &PropertyValue) -> Option<String> {
+    let url = match value {
+        PropertyValue::Url(url) => Url::parse(url.as_str()).ok(),
+        PropertyValue::Image(image) => Some(image.value.clone()),
+        PropertyValue::Plain(text) => Url::parse(text).ok(),
+        PropertyValue::Item(item) => {
+            return first_url(item, "url").or_else(|| match &item.value {
+                Some(microformats::types::ValueKind::Url(url)) => web_url(url.clone()),
+                _ => None,
+            });
+        }
+        _ => None,
+    };
+
+    url.and_then(web_url)
+}
+
+fn web_url(url: Url) -> Option<String> {
+    matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+}
+
+fn same_url(a: &str, b: &str) -> bool {
+    a.trim_end_matches('/') == b.trim_end_matches('/')
+}
+
+fn same_text(a: &str, b: &str) -> bool {
+    let normalize = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+    let (a, b) = (normalize(a), normalize(b));
+
+    b.starts_with(&a) || a.starts_with(&b)
+}
+
+pub fn truncate(s: &str) -> String {
+    if s.chars().count() <= MAX_LEN {
+        return s.to_owned();
+    }
+
+    let mut truncated = s.chars().take(MAX_LEN - 1).collect::<String>();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: &str = "https://wwwtech.example/notes/1";
+
+    fn parse(body: &str) -> SourceData {
+        let source_url = Url::parse("https://source.example/posts/1").unwrap();
+        parse_source(body, &source_url, &Url::parse(TARGET).unwrap())
+    }
+
+    /// An h-entry by Alice with the `property` pointing to the target
+    fn entry(property: &str) -> String {
+        format!(
+            r#"<html><body><article class="h-entry">
+                 <a class="p-author h-card" href="https://alice.example/">Alice</a>
+                 <a class="{}" href="{}">this</a>
+                 <p class="e-content">Great post!</p>
+               </article></body></html>"#,
+            property, TARGET
+        )
+    }
+
+    #[test]
+    fn classifies_likes() {
+        let data = parse(&entry("u-like-of"));
+
+        assert_eq!(data.mention_type, "like");
+        assert_eq!(data.author, "Alice");
+        assert_eq!(data.author_url.as_deref(), Some("https://alice.example/"));
+    }
+
+    #[test]
+    fn classifies_reposts() {
+        assert_eq!(parse(&entry("u-repost-of")).mention_type, "repost");
+    }
+
+    #[test]
+    fn classifies_bookmarks() {
+        assert_eq!(parse(&entry("u-bookmark-of")).mention_type, "bookmark");
+    }
+
+    #[test]
+    fn classifies_replies() {
+        let data = parse(&entry("u-in-reply-to"));
+
+        assert_eq!(data.mention_type, "reply");
+        assert_eq!(data.excerpt.as_deref(), Some("Great post!"));
+        assert_eq!(data.title, None);
+    }
+
+    #[test]
+    fn classifies_other_links_as_mentions() {
+        let body = format!(
+            r#"<div class="h-entry"><a class="u-like-of" href="https://other.example/">that</a>
+               <p class="e-content">see <a href="{}">this</a></p></div>"#,
+            TARGET
+        );
+
+        assert_eq!(parse(&body).mention_type, "mention");
+    }
+
+    #[test]
+    fn truncates_long_texts() {
+        let body = format!(
+            r#"<div class="h-entry"><span class="p-author">{}</span><a class="u-in-reply-to" href="{}">this</a>
+               <p class="e-content">{}</p></div>"#,
+            "A".repeat(300),
+            TARGET,
+            "b".repeat(300)
+        );
+        let data = parse(&body);
+
+        let excerpt = data.excerpt.unwrap();
+        assert_eq!(excerpt.chars().count(), MAX_LEN);
+        assert!(excerpt.ends_with('…'));
+        assert_eq!(data.author.chars().count(), MAX_LEN);
+    }
+
+    #[test]
+    fn only_takes_web_urls() {
+        let body = format!(
+            r#"<div class="h-entry">
+                 <div class="p-author h-card">
+                   <a class="p-name u-url" href="javascript:alert(1)">Mallory</a>
+                   <img class="u-photo" src="data:image/png;base64,AAAA" alt="">
+                 </div>
+                 <a class="u-like-of" href="{}">this</a>
+               </div>"#,
+            TARGET
+        );
+        let data = parse(&body);
+
+        assert_eq!(data.mention_type, "like");
+        assert_eq!(data.author, "Mallory");
+        assert_eq!(data.author_url, None);
+        assert_eq!(data.author_avatar, None);
+    }
+
+    #[test]
+    fn falls_back_to_title_and_meta_author() {
+        let body = format!(
+            r#"<html><head><title> A page </title><meta name="author" content="Bob"></head>
+               <body><a href="{}">a link</a></body></html>"#,
+            TARGET
+        );
+        let data = parse(&body);
+
+        assert_eq!(data.mention_type, "mention");
+        assert_eq!(data.title.as_deref(), Some("A page"));
+        assert_eq!(data.author, "Bob");
+        assert_eq!(data.excerpt, None);
+
+        assert_eq!(parse("<html><head><title>Anonymous</title></head></html>").author, "unknown");
+    }
+}
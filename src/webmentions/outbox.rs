@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use chrono::{NaiveDateTime, Utc};
+use reqwest::Client;
+use sqlx::PgPool;
+
+use super::actions::{claim_due_outgoing_webmentions, record_outgoing_webmention, requeue_stale_outgoing_webmentions};
+use super::fetch::build_client;
+use super::send::{DeliveryResult, send_mention};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+const SEND_TIMEOUT: Duration = Duration::from_secs(20);
+const BATCH_SIZE: i64 = 10;
+const MAX_ATTEMPTS: i32 = 6;
+
+pub async fn run(pool: PgPool) {
+    match pool.acquire().await {
+        Ok(mut conn) => {
+            if let Err(e) = requeue_stale_outgoing_webmentions(&mut conn).await {
+                tracing::error!("could not requeue stale outgoing webmentions: {}", e);
+            }
+        }
+        Err(e) => tracing::error!("could not requeue stale outgoing webmentions: {}", e),
+    }
+
+    // targets and the endpoints they advertise are chosen by others, so they get the same checks as sources
+    let client = build_client(SEND_TIMEOUT).expect("could not build HTTP client");
+
+    loop {
+        if let Err(e) = send_due_webmentions(&pool, &client).await {
+            tracing::error!("error processing webmention outbox: {}", e);
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn send_due_webmentions(pool: &PgPool, client: &Client) -> Result<(), sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    let due = claim_due_outgoing_webmentions(BATCH_SIZE, &mut conn).await?;
+
+    for webmention in due {
+        let delivery = send_mention(client, &webmention.source_url, &webmention.target_url).await;
+        let now = Utc::now().naive_utc();
+
+        let (status, error, next_retry_at) = match delivery.result {
+            DeliveryResult::Sent => ("sent", None, now),
+            DeliveryResult::NoEndpoint => ("no_endpoint", None, now),
+            DeliveryResult::Rejected(reason) => {
+                tracing::info!("webmention {} was rejected: {}", webmention.id, reason);
+                ("rejected", Some(reason), now)
+            }
+            DeliveryResult::Temporary(error) => {
+                tracing::warn!(
+                    "sending webmention {} failed (attempt {}): {}",
+                    webmention.id,
+                    webmention.attempts,
+                    error
+                );
+
+                match next_retry_at(webmention.attempts) {
+                    Some(next_retry_at) => ("pending", Some(error), next_retry_at),
+                    None => ("failed", Some(error), now),
+                }
+            }
+        };
+
+        record_outgoing_webmention(
+            webmention.id,
+            status,
+            delivery.endpoint.as_deref(),
+            delivery.response_code.map(i32::from),
+            error.as_deref(),
+            next_retry_at,
+            &mut conn,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Exponential backoff starting at one minute; `None` once the attempts are used up.
+fn next_retry_at(attempts: i32) -> Option<NaiveDateTime> {
+    if attempts >= MAX_ATTEMPTS {
+        return None;
+    }
+
+    let delay = chrono::Duration::minutes(1_i64 << attempts);
+    Some(Utc::now().naive_utc() + delay)
+}
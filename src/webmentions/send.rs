@@ -1,24 +1,98 @@
-use anyhow::{Result, anyhow, bail};
+use anyhow::{Result, anyhow};
 use itertools::Itertools;
-use regex::Regex;
-use reqwest::blocking::Client;
-use reqwest::header::LINK;
+use reqwest::Client;
+use reqwest::header::{CONTENT_TYPE, LINK};
+use sqlx::PgPool;
 use url::Url;
 use urlencoding::encode;
 use visdom::Vis;
 use visdom::types::IAttrValue;
 
-pub fn send_mentions(source_url: &str) -> Result<()> {
-    let client = Client::new();
-    let body = client
-        .get(source_url)
-        .send()
-        .map_err(|e| anyhow!("could not GET source document: {}", e.to_string()))?
+use super::actions::{enqueue_outgoing_webmention, list_outbound_links, replace_outbound_links};
+
+/// the outcome of a single delivery attempt
+pub struct Delivery {
+    pub endpoint: Option<String>,
+    pub response_code: Option<u16>,
+    pub result: DeliveryResult,
+}
+
+pub enum DeliveryResult {
+    /// the endpoint accepted the webmention
+    Sent,
+    /// the target doesn't advertise a webmention endpoint
+    NoEndpoint,
+    /// the target or the endpoint refused the webmention, retrying won't help
+    Rejected(String),
+    /// something went wrong on the way, try again later
+    Temporary(String),
+}
+
+/// Fetches the source document and puts a webmention for each linked document into the outbox;
+/// the outbox worker does the actual delivery. Documents which were linked the last time but
+/// aren't any more get a webmention as well, so they can remove the mention.
+pub async fn send_mentions(source_url: String, pool: PgPool) {
+    if let Err(e) = queue_mentions(&source_url, &pool).await {
+        tracing::error!("could not queue webmentions for {}: {}", source_url, e);
+    }
+}
+
+/// Notifies every document the deleted post at `source_url` used to link to.
+pub async fn send_mentions_for_deleted(source_url: String, pool: PgPool) {
+    if let Err(e) = queue_mentions_for_deleted(&source_url, &pool).await {
+        tracing::error!("could not queue webmentions for deleted {}: {}", source_url, e);
+    }
+}
+
+async fn queue_mentions(source_url: &str, pool: &PgPool) -> Result<()> {
+    let body = reqwest::get(source_url)
+        .await
+        .and_then(|rsp| rsp.error_for_status())
+        .map_err(|e| anyhow!("could not GET source document: {}", e))?
         .text()
-        .map_err(|e| anyhow!("could not GET source document: {}", e.to_string()))?;
+        .await
+        .map_err(|e| anyhow!("could not GET source document: {}", e))?;
+
+    let links = find_links(&body)?
+        .into_iter()
+        .map(|link| link.to_string())
+        .collect::<Vec<_>>();
+
+    let mut conn = pool.acquire().await?;
+    let previous_links = list_outbound_links(source_url, &mut conn).await?;
+
+    let targets = links
+        .iter()
+        .chain(previous_links.iter())
+        .unique()
+        .cloned()
+        .collect::<Vec<_>>();
+
+    for target in targets.iter() {
+        enqueue_outgoing_webmention(source_url, target, &mut conn).await?;
+    }
+
+    replace_outbound_links(source_url, &links, &mut conn).await?;
+
+    Ok(())
+}
+
+async fn queue_mentions_for_deleted(source_url: &str, pool: &PgPool) -> Result<()> {
+    let mut conn = pool.acquire().await?;
+    let previous_links = list_outbound_links(source_url, &mut conn).await?;
+
+    for link in previous_links.iter() {
+        enqueue_outgoing_webmention(source_url, link, &mut conn).await?;
+    }
+
+    replace_outbound_links(source_url, &[], &mut conn).await?;
 
-    let tree = Vis::load(&body).map_err(|e| anyhow!("could not parse source document: {}", e.to_string()))?;
-    let links: Vec<Url> = tree
+    Ok(())
+}
+
+fn find_links(body: &str) -> Result<Vec<Url>> {
+    let tree = Vis::load(body).map_err(|e| anyhow!("could not parse source document: {}", e))?;
+    let links = tree
         .find("a[href]")
         .into_iter()
         .filter_map(|link| match link.get_attribute("href") {
@@ -29,59 +103,353 @@ pub fn send_mentions(source_url: &str) -> Result<()> {
         .unique()
         .collect();
 
-    for link in links.iter() {
-        // we don't care about the result; if it fails, it fails 🤷‍♂️
-        let _ = send_mention(&client, source_url, link.as_ref());
-    }
+    Ok(links)
+}
 
-    Ok(())
+pub async fn send_mention(client: &Client, source_url: &str, target_url: &str) -> Delivery {
+    let endpoint = match discover_endpoint(client, target_url).await {
+        Ok(Some(endpoint)) => endpoint,
+        Ok(None) => return delivery(None, None, DeliveryResult::NoEndpoint),
+        Err(result) => return delivery(None, None, result),
+    };
+
+    let data = format!("target={}&source={}", encode(target_url), encode(source_url));
+
+    let rsp = client
+        .post(endpoint.as_str())
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body(data)
+        .send()
+        .await;
+
+    let endpoint = Some(endpoint.to_string());
+
+    let rsp = match rsp {
+        Ok(rsp) => rsp,
+        Err(e) => {
+            let result = DeliveryResult::Temporary(format!("could not send webmention: {}", e));
+            return delivery(endpoint, None, result);
+        }
+    };
+
+    let status = rsp.status();
+    let result = if status.is_success() {
+        DeliveryResult::Sent
+    } else if status.is_client_error() {
+        DeliveryResult::Rejected(format!("endpoint returned {}", status))
+    } else {
+        DeliveryResult::Temporary(format!("endpoint returned {}", status))
+    };
+
+    delivery(endpoint, Some(status.as_u16()), result)
 }
 
-pub fn send_mention(client: &Client, source_url: &str, target_url: &str) -> Result<()> {
+fn delivery(endpoint: Option<String>, response_code: Option<u16>, result: DeliveryResult) -> Delivery {
+    Delivery {
+        endpoint,
+        response_code,
+        result,
+    }
+}
+
+/// Discovers the webmention endpoint of `target_url` as described in the spec: redirects are followed,
+/// `Link` headers take precedence over the first `<link>` or `<a>` element with `rel="webmention"`,
+/// and relative endpoints are resolved against the final URL of the target.
+async fn discover_endpoint(client: &Client, target_url: &str) -> Result<Option<Url>, DeliveryResult> {
     let rsp = client
         .get(target_url)
         .send()
-        .map_err(|e| anyhow!("could not get target document: {}", e.to_string()))?;
-    if !rsp.status().is_success() {
-        bail!("could not get target document");
+        .await
+        .map_err(|e| DeliveryResult::Temporary(format!("could not get target document: {}", e)))?;
+
+    let status = rsp.status();
+    if status.is_client_error() {
+        return Err(DeliveryResult::Rejected(format!("target returned {}", status)));
+    } else if !status.is_success() {
+        return Err(DeliveryResult::Temporary(format!("target returned {}", status)));
     }
-    let re = Regex::new(r#"rel="?webmention"?"#).unwrap();
 
-    let mut link_hdr = rsp
+    let base_url = rsp.url().clone();
+
+    let mut endpoint = rsp
         .headers()
         .get_all(LINK)
         .into_iter()
-        .find(|hdr| re.is_match(hdr.to_str().unwrap()))
-        .map(|v| v.to_str().unwrap().to_owned());
-
-    if link_hdr.is_none() {
-        let body = rsp.text().map_err(|_| anyhow!("could not get text body"))?;
-        let tree = Vis::load(&body).map_err(|e| anyhow!("could not parse source document: {}", e.to_string()))?;
-        link_hdr = match tree.find("link[rel=webmention]").attr("href") {
-            Some(IAttrValue::Value(val, _)) => Some(val),
+        .filter_map(|hdr| hdr.to_str().ok())
+        .flat_map(parse_link_header)
+        .find(|link| is_webmention_rel(&link.rel))
+        .map(|link| link.uri);
+
+    let is_html = rsp
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|hdr| hdr.to_str().ok())
+        .is_none_or(|content_type| content_type.contains("html"));
+
+    if endpoint.is_none() && is_html {
+        let body = rsp
+            .text()
+            .await
+            .map_err(|_| DeliveryResult::Temporary("could not get text body".to_owned()))?;
+        endpoint = find_endpoint_in_html(&body)?;
+    }
+
+    let Some(endpoint) = endpoint else {
+        return Ok(None);
+    };
+
+    let url = base_url
+        .join(&endpoint)
+        .map_err(|_| DeliveryResult::Rejected(format!("could not parse endpoint URI: {}", endpoint)))?;
+
+    Ok(Some(url))
+}
+
+fn find_endpoint_in_html(body: &str) -> Result<Option<String>, DeliveryResult> {
+    let tree =
+        Vis::load(body).map_err(|e| DeliveryResult::Rejected(format!("could not parse target document: {}", e)))?;
+
+    let endpoint = tree.find("[rel][href]").into_iter().find_map(|element| {
+        let tag_name = element.tag_name();
+        if tag_name != "LINK" && tag_name != "A" {
+            return None;
+        }
+
+        match (element.get_attribute("rel"), element.get_attribute("href")) {
+            (Some(IAttrValue::Value(rel, _)), Some(IAttrValue::Value(href, _))) if is_webmention_rel(&rel) => {
+                Some(href)
+            }
+            // a bare href attribute is an empty URL and thus points to the target itself
+            (Some(IAttrValue::Value(rel, _)), Some(IAttrValue::True)) if is_webmention_rel(&rel) => Some(String::new()),
             _ => None,
-        };
+        }
+    });
+
+    Ok(endpoint)
+}
+
+fn is_webmention_rel(rel: &str) -> bool {
+    rel.split_ascii_whitespace()
+        .any(|rel| rel.eq_ignore_ascii_case("webmention"))
+}
+
+pub struct Link {
+    pub uri: String,
+    pub rel: String,
+}
+
+/// Parses the value of a `Link` header (RFC 8288) into its links; a header may contain several
+/// comma-separated links, each with a list of parameters.
+pub fn parse_link_header(value: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut chars = value.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == ',').is_some() {}
+
+        if chars.next() != Some('<') {
+            break;
+        }
+
+        let uri = chars.by_ref().take_while(|c| *c != '>').collect::<String>();
+        let mut rel = String::new();
+
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+            if chars.next_if_eq(&';').is_none() {
+                break;
+            }
+
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+            let name = std::iter::from_fn(|| chars.next_if(|c| !matches!(c, '=' | ';' | ',')))
+                .collect::<String>()
+                .trim()
+                .to_ascii_lowercase();
+
+            let mut param_value = String::new();
+            if chars.next_if_eq(&'=').is_some() {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+                if chars.next_if_eq(&'"').is_some() {
+                    while let Some(c) = chars.next() {
+                        match c {
+                            '"' => break,
+                            '\\' => param_value.extend(chars.next()),
+                            c => param_value.push(c),
+                        }
+                    }
+                } else {
+                    param_value = std::iter::from_fn(|| chars.next_if(|c| !matches!(c, ';' | ',')))
+                        .collect::<String>()
+                        .trim()
+                        .to_owned();
+                }
+            }
+
+            // only the first rel parameter counts
+            if name == "rel" && rel.is_empty() {
+                rel = param_value;
+            }
+        }
+
+        // skip anything we don't understand up to the next link
+        while chars.next_if(|c| *c != ',').is_some() {}
+
+        links.push(Link { uri, rel });
+    }
+
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::Router;
+    use axum::http::header::LINK;
+    use axum::response::{Html, Redirect};
+    use axum::routing::get;
+
+    use super::*;
+
+    /// Serves the discovery cases of webmention.rocks on a random local port
+    async fn fixture_server() -> Url {
+        let app = Router::new()
+            .route(
+                "/several-links",
+                get(|| async {
+                    (
+                        [
+                            (
+                                LINK,
+                                r#"<https://example.com/other>; rel="other", </endpoint/several>; rel="webmention""#,
+                            ),
+                        ],
+                        Html(""),
+                    )
+                }),
+            )
+            .route(
+                "/quoted-rel",
+                get(|| async { ([(LINK, r#"</endpoint/quoted>; REL="nothing webmention other""#)], Html("")) }),
+            )
+            .route(
+                "/unquoted-rel",
+                get(|| async { ([(LINK, "</endpoint/unquoted>; title=\"a, b\"; rel=webmention")], Html("")) }),
+            )
+            .route("/redirect", get(|| async { Redirect::temporary("/nested/page") }))
+            .route("/nested/page", get(|| async { ([(LINK, "<endpoint>; rel=\"webmention\"")], Html("")) }))
+            .route("/redirect-html", get(|| async { Redirect::permanent("/nested/html") }))
+            .route(
+                "/nested/html",
+                get(|| async { Html(r#"<html><head><link rel="webmention" href="../endpoint/html"></head></html>"#) }),
+            )
+            .route(
+                "/empty-href",
+                get(|| async { Html(r#"<html><head><link rel="webmention" href=""></head></html>"#) }),
+            )
+            .route(
+                "/anchor",
+                get(|| async {
+                    Html(r#"<html><body><a rel=webmention href="/endpoint/anchor">endpoint</a></body></html>"#)
+                }),
+            )
+            .route(
+                "/header-wins",
+                get(|| async {
+                    (
+                        [(LINK, "</endpoint/header>; rel=\"webmention\"")],
+                        Html(r#"<html><head><link rel="webmention" href="/endpoint/html"></head></html>"#),
+                    )
+                }),
+            )
+            .route(
+                "/comments",
+                get(|| async {
+                    Html(
+                        r#"<html><head><!-- <link rel="webmention" href="/endpoint/decoy"> --></head>
+                        <body><!-- <a rel="webmention" href="/endpoint/decoy">x</a> -->
+                        <a rel="webmention" href="/endpoint/real">endpoint</a></body></html>"#,
+                    )
+                }),
+            )
+            .route("/none", get(|| async { Html("<html><body><a href=\"/elsewhere\">x</a></body></html>") }));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        Url::parse(&format!("http://{}/", addr)).unwrap()
     }
 
-    if link_hdr.is_none() {
-        bail!("could not discover endpoint");
+    async fn discover(base: &Url, path: &str) -> Option<String> {
+        let client = Client::builder().no_proxy().build().unwrap();
+        let target = base.join(path).unwrap();
+
+        match discover_endpoint(&client, target.as_str()).await {
+            Ok(endpoint) => endpoint.map(|url| url.path().to_owned()),
+            Err(_) => panic!("discovering the endpoint of {} failed", target),
+        }
     }
 
-    let link_hdr = link_hdr.unwrap();
-    let re = Regex::new(r"^<|>$").unwrap();
-    let re1 = Regex::new(r#";?\s*rel="?webmention"?"#).unwrap();
-    let link_hdr = re1.replace_all(&link_hdr, "");
-    let clean_uri = re.replace_all(&link_hdr, "");
-    let url = Url::parse(&clean_uri).map_err(|_| anyhow!("could not parse endpoint URI: {}", clean_uri))?;
+    #[tokio::test]
+    async fn finds_endpoint_among_several_links_in_one_header() {
+        let base = fixture_server().await;
+        assert_eq!(discover(&base, "/several-links").await.as_deref(), Some("/endpoint/several"));
+    }
 
-    let data = format!("target={}&source={}", encode(target_url), encode(source_url));
+    #[tokio::test]
+    async fn finds_endpoint_with_quoted_and_multi_valued_rel() {
+        let base = fixture_server().await;
+        assert_eq!(discover(&base, "/quoted-rel").await.as_deref(), Some("/endpoint/quoted"));
+        assert_eq!(discover(&base, "/unquoted-rel").await.as_deref(), Some("/endpoint/unquoted"));
+    }
 
-    client
-        .post(url)
-        .header("content-type", "application/x-www-form-urlencoded")
-        .body(data)
-        .send()
-        .map_err(|e| anyhow!("could not send webmention: {}", e.to_string()))?;
+    #[tokio::test]
+    async fn resolves_relative_endpoints_against_the_url_after_redirects() {
+        let base = fixture_server().await;
+        assert_eq!(discover(&base, "/redirect").await.as_deref(), Some("/nested/endpoint"));
+        assert_eq!(discover(&base, "/redirect-html").await.as_deref(), Some("/endpoint/html"));
+    }
 
-    Ok(())
+    #[tokio::test]
+    async fn empty_href_points_to_the_target_itself() {
+        let base = fixture_server().await;
+        assert_eq!(discover(&base, "/empty-href").await.as_deref(), Some("/empty-href"));
+    }
+
+    #[tokio::test]
+    async fn finds_endpoint_in_anchor() {
+        let base = fixture_server().await;
+        assert_eq!(discover(&base, "/anchor").await.as_deref(), Some("/endpoint/anchor"));
+    }
+
+    #[tokio::test]
+    async fn link_header_wins_over_html() {
+        let base = fixture_server().await;
+        assert_eq!(discover(&base, "/header-wins").await.as_deref(), Some("/endpoint/header"));
+    }
+
+    #[tokio::test]
+    async fn ignores_endpoints_in_html_comments() {
+        let base = fixture_server().await;
+        assert_eq!(discover(&base, "/comments").await.as_deref(), Some("/endpoint/real"));
+    }
+
+    #[tokio::test]
+    async fn finds_no_endpoint() {
+        let base = fixture_server().await;
+        assert_eq!(discover(&base, "/none").await, None);
+    }
+
+    #[test]
+    fn parses_link_header() {
+        let links = parse_link_header(r##"<https://a.example/>; rel="a b", <https://b.example/>; anchor="#x"; rel=c"##);
+
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].uri, "https://a.example/");
+        assert_eq!(links[0].rel, "a b");
+        assert_eq!(links[1].uri, "https://b.example/");
+        assert_eq!(links[1].rel, "c");
+    }
 }
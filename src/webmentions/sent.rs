@@ -0,0 +1,64 @@
+use askama::Template;
+use axum::extract::{Query, State};
+use axum::response::{Html, IntoResponse};
+use serde::Deserialize;
+
+use super::actions::{count_outgoing_webmentions, list_outgoing_webmentions};
+use crate::errors::AppError;
+use crate::models::OutgoingWebmention;
+use crate::uri_helpers::*;
+use crate::utils::csrf::csrf_token;
+use crate::utils::paging::{PageParams, Paging, get_page, get_paging};
+use crate::{AppState, utils as filters};
+
+static PER_PAGE: i64 = 50;
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct SentFilter {
+    pub source: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "webmentions/sent.html.j2")]
+pub struct Index<'a> {
+    lang: &'a str,
+    title: Option<&'a str>,
+    page_type: Option<&'a str>,
+    page_image: Option<&'a str>,
+    body_id: Option<&'a str>,
+    logged_in: bool,
+
+    webmentions: Vec<OutgoingWebmention>,
+    paging: Paging,
+    source: Option<String>,
+}
+
+pub async fn index(
+    State(state): State<AppState>,
+    page: Query<PageParams>,
+    Query(filter): Query<SentFilter>,
+) -> Result<impl IntoResponse, AppError> {
+    let p = get_page(&page);
+    let source = filter.source.filter(|source| !source.is_empty());
+
+    let mut conn = state.pool.acquire().await?;
+    let webmentions = list_outgoing_webmentions(source.as_deref(), PER_PAGE, p * PER_PAGE, &mut conn).await?;
+    let count = count_outgoing_webmentions(source.as_deref(), &mut conn).await?;
+
+    let paging = get_paging(count, p, PER_PAGE);
+
+    let html = Index {
+        lang: "en",
+        title: Some("Sent webmentions"),
+        page_type: None,
+        page_image: None,
+        body_id: None,
+        logged_in: true,
+        webmentions,
+        paging,
+        source,
+    }
+    .render()?;
+
+    Ok(Html(html))
+}
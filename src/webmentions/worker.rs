@@ -0,0 +1,222 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, Response, StatusCode};
+use sqlx::{PgConnection, PgPool};
+use url::Url;
+
+use super::actions::{
+    accept_queued_webmention, claim_due_webmentions, create_mention, get_mention_by_source_and_target,
+    reject_queued_webmention, requeue_stale_webmentions, retry_queued_webmention, target_exists, update_mention,
+};
+use super::fetch::{build_client, is_public_url};
+use super::mail_sender;
+use super::mf2::parse_source;
+use crate::mentions::actions::{domain_rule, set_mention_status};
+use crate::models::{Mention, QueuedWebmention};
+use crate::uri_helpers::root_uri;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+/// the content types `parse_source()` can find the link in
+const ALLOWED_CONTENT_TYPES: [&str; 2] = ["text/html", "application/xhtml+xml"];
+const BATCH_SIZE: i64 = 10;
+const MAX_ATTEMPTS: i32 = 6;
+
+enum ProcessError {
+    /// the webmention is invalid and must not be retried
+    Rejected(String),
+    /// something went wrong on the way, try again later
+    Temporary(String),
+}
+
+impl From<sqlx::Error> for ProcessError {
+    fn from(e: sqlx::Error) -> Self {
+        ProcessError::Temporary(format!("db error: {}", e))
+    }
+}
+
+pub async fn run(pool: PgPool) {
+    match pool.acquire().await {
+        Ok(mut conn) => {
+            if let Err(e) = requeue_stale_webmentions(&mut conn).await {
+                tracing::error!("could not requeue stale webmentions: {}", e);
+            }
+        }
+        Err(e) => tracing::error!("could not requeue stale webmentions: {}", e),
+    }
+
+    let client = build_client(FETCH_TIMEOUT).expect("could not build HTTP client");
+
+    loop {
+        if let Err(e) = process_due_webmentions(&pool, &client).await {
+            tracing::error!("error processing webmention queue: {}", e);
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn process_due_webmentions(pool: &PgPool, client: &Client) -> Result<(), sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    let queued = claim_due_webmentions(BATCH_SIZE, &mut conn).await?;
+
+    for webmention in queued {
+        match process_webmention(&webmention, client, &mut conn).await {
+            Ok(mention) => {
+                accept_queued_webmention(webmention.id, mention.id, &mut conn).await?;
+            }
+
+            Err(ProcessError::Rejected(reason)) => {
+                tracing::info!("rejected webmention {}: {}", webmention.id, reason);
+                reject_queued_webmention(webmention.id, &reason, &mut conn).await?;
+            }
+
+            Err(ProcessError::Temporary(error)) => {
+                tracing::warn!("webmention {} failed (attempt {}): {}", webmention.id, webmention.attempts, error);
+                retry_queued_webmention(webmention.id, &error, next_try_at(webmention.attempts), &mut conn).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Exponential backoff starting at one minute; `None` once the attempts are used up.
+fn next_try_at(attempts: i32) -> Option<chrono::NaiveDateTime> {
+    if attempts >= MAX_ATTEMPTS {
+        return None;
+    }
+
+    let delay = chrono::Duration::minutes(1_i64 << attempts);
+    Some(Utc::now().naive_utc() + delay)
+}
+
+async fn process_webmention(
+    webmention: &QueuedWebmention,
+    client: &Client,
+    conn: &mut PgConnection,
+) -> Result<Mention, ProcessError> {
+    let root_url = Url::parse(&root_uri()).unwrap();
+    let source_url =
+        Url::parse(&webmention.source_url).map_err(|_| ProcessError::Rejected("source url invalid".to_owned()))?;
+    let target_url =
+        Url::parse(&webmention.target_url).map_err(|_| ProcessError::Rejected("target url invalid".to_owned()))?;
+
+    if !is_public_url(&source_url) {
+        return Err(ProcessError::Rejected("source url points to a non-public address".to_owned()));
+    }
+
+    let rule = domain_rule(source_url.host_str().unwrap_or_default(), conn).await?;
+    if rule.as_deref() == Some("block") {
+        return Err(ProcessError::Rejected("source domain is blocked".to_owned()));
+    }
+
+    let Some((object_type, id)) = target_exists(&target_url, conn).await? else {
+        return Err(ProcessError::Rejected("target does not exist".to_owned()));
+    };
+
+    // a repeated webmention for a known pair means the source has been updated or deleted
+    let existing = get_mention_by_source_and_target(source_url.as_str(), target_url.as_str(), conn).await?;
+
+    let rsp = client
+        .get(source_url.as_str())
+        .send()
+        .await
+        .map_err(|e| ProcessError::Temporary(format!("request error: {}", e)))?;
+
+    let status = rsp.status();
+    if status == StatusCode::NOT_FOUND || status == StatusCode::GONE {
+        return Err(remove_mention(existing, &format!("source returned {}", status), conn).await);
+    } else if status.is_client_error() {
+        return Err(ProcessError::Rejected(format!("source returned {}", status)));
+    } else if !status.is_success() {
+        return Err(ProcessError::Temporary(format!("source returned {}", status)));
+    }
+
+    let content_type = rsp
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    if !ALLOWED_CONTENT_TYPES
+        .iter()
+        .any(|allowed| content_type.starts_with(allowed))
+    {
+        return Err(ProcessError::Rejected(format!("unsupported content type: {}", content_type)));
+    }
+
+    let body = read_body(rsp).await?;
+
+    if !body.contains(target_url.as_str()) {
+        return Err(remove_mention(existing, "source does not link to target", conn).await);
+    }
+
+    let status = if rule.as_deref() == Some("approve") || source_url.host() == root_url.host() {
+        "approved"
+    } else {
+        "pending"
+    };
+
+    let source = parse_source(&body, &source_url, &target_url);
+
+    if let Some(existing) = existing {
+        // keep the moderation decision unless the mention had been removed in the meantime
+        let status = if existing.status == "deleted" {
+            status
+        } else {
+            &existing.status
+        };
+        let mention = update_mention(existing.id, source, status, conn).await?;
+        return Ok(mention);
+    }
+
+    let mention =
+        create_mention(source_url.to_string(), target_url.to_string(), object_type, id, source, status, conn).await?;
+
+    if source_url.host() != root_url.host() {
+        let mention_ = mention.clone();
+        tokio::task::spawn_blocking(move || mail_sender::send_mail(mention_));
+    }
+
+    Ok(mention)
+}
+
+/// Reads the response body, giving up once it exceeds `MAX_BODY_SIZE`
+async fn read_body(mut rsp: Response) -> Result<String, ProcessError> {
+    if rsp.content_length().is_some_and(|len| len > MAX_BODY_SIZE as u64) {
+        return Err(ProcessError::Rejected("source document is too large".to_owned()));
+    }
+
+    let mut body = Vec::new();
+
+    while let Some(chunk) = rsp
+        .chunk()
+        .await
+        .map_err(|e| ProcessError::Temporary(format!("request error: {}", e)))?
+    {
+        body.extend_from_slice(&chunk);
+
+        if body.len() > MAX_BODY_SIZE {
+            return Err(ProcessError::Rejected("source document is too large".to_owned()));
+        }
+    }
+
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// Tombstones the mention of a source which is gone or no longer links to us
+async fn remove_mention(mention: Option<Mention>, reason: &str, conn: &mut PgConnection) -> ProcessError {
+    let Some(mention) = mention else {
+        return ProcessError::Rejected(reason.to_owned());
+    };
+
+    match set_mention_status(mention.id, "deleted", conn).await {
+        Ok(_) => ProcessError::Rejected(format!("{}, mention removed", reason)),
+        Err(e) => e.into(),
+    }
+}
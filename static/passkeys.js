@@ -0,0 +1,130 @@
+(function () {
+  "use strict";
+
+  function decode(value) {
+    const base64 = value.replace(/-/g, "+").replace(/_/g, "/");
+    const padded = base64 + "===".slice((base64.length + 3) % 4);
+    return Uint8Array.from(atob(padded), (c) => c.charCodeAt(0)).buffer;
+  }
+
+  function encode(buffer) {
+    const bytes = String.fromCharCode(...new Uint8Array(buffer));
+    return btoa(bytes).replace(/\+/g, "-").replace(/\//g, "_").replace(/=+$/, "");
+  }
+
+  async function post(form, url, body) {
+    const headers = { "Content-Type": "application/json", Accept: "application/json" };
+    const csrfToken = form.querySelector("[name=csrf_token]");
+    if (csrfToken) {
+      headers["X-CSRF-Token"] = csrfToken.value;
+    }
+
+    const rsp = await fetch(url, {
+      method: "POST",
+      headers,
+      credentials: "same-origin",
+      body: JSON.stringify(body),
+    });
+
+    if (!rsp.ok) {
+      throw new Error(await rsp.text());
+    }
+
+    return rsp.json();
+  }
+
+  function showError(form, error) {
+    const alert = form.querySelector(".passkey-error");
+    alert.textContent = error.message;
+    alert.hidden = false;
+  }
+
+  async function login(ev) {
+    ev.preventDefault();
+    const form = ev.target;
+
+    try {
+      const options = await post(form, form.dataset.challengeUrl, {
+        email: form.querySelector("[name=email]").value,
+        next: form.querySelector("[name=next]")?.value,
+      });
+
+      const publicKey = options.publicKey;
+      publicKey.challenge = decode(publicKey.challenge);
+      publicKey.allowCredentials = (publicKey.allowCredentials || []).map((cred) => ({
+        ...cred,
+        id: decode(cred.id),
+      }));
+
+      const credential = await navigator.credentials.get({ publicKey });
+      const result = await post(form, form.action, {
+        id: credential.id,
+        rawId: encode(credential.rawId),
+        type: credential.type,
+        response: {
+          authenticatorData: encode(credential.response.authenticatorData),
+          clientDataJSON: encode(credential.response.clientDataJSON),
+          signature: encode(credential.response.signature),
+          userHandle: credential.response.userHandle ? encode(credential.response.userHandle) : null,
+        },
+        extensions: credential.getClientExtensionResults(),
+      });
+
+      window.location = result.location;
+    } catch (error) {
+      showError(form, error);
+    }
+  }
+
+  async function register(ev) {
+    ev.preventDefault();
+    const form = ev.target;
+
+    try {
+      const options = await post(form, form.dataset.challengeUrl, {});
+
+      const publicKey = options.publicKey;
+      publicKey.challenge = decode(publicKey.challenge);
+      publicKey.user.id = decode(publicKey.user.id);
+      publicKey.excludeCredentials = (publicKey.excludeCredentials || []).map((cred) => ({
+        ...cred,
+        id: decode(cred.id),
+      }));
+
+      const credential = await navigator.credentials.create({ publicKey });
+      const result = await post(form, form.action, {
+        name: form.querySelector("[name=name]").value,
+        credential: {
+          id: credential.id,
+          rawId: encode(credential.rawId),
+          type: credential.type,
+          response: {
+            attestationObject: encode(credential.response.attestationObject),
+            clientDataJSON: encode(credential.response.clientDataJSON),
+          },
+          extensions: credential.getClientExtensionResults(),
+        },
+      });
+
+      window.location = result.location;
+    } catch (error) {
+      showError(form, error);
+    }
+  }
+
+  document.addEventListener("DOMContentLoaded", () => {
+    if (!window.PublicKeyCredential) {
+      return;
+    }
+
+    document.querySelectorAll("form.passkey-login").forEach((form) => {
+      form.hidden = false;
+      form.addEventListener("submit", login);
+    });
+
+    document.querySelectorAll("form.passkey-register").forEach((form) => {
+      form.hidden = false;
+      form.addEventListener("submit", register);
+    });
+  });
+})();